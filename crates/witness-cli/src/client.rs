@@ -2,8 +2,9 @@ use anyhow::{Context, Result};
 use reqwest::Client;
 use std::time::Duration;
 use witness_core::{
-    ExternalAnchorProof, FreebirdToken, NetworkConfig, SignedAttestation, TimestampRequest,
-    TimestampResponse, VerifyRequest, VerifyResponse,
+    BatchTimestampRequest, BatchTimestampResponse, ExternalAnchorProof, FederatedVerifyRequest,
+    FederatedVerifyResponse, FreebirdToken, NetworkConfig, SignedAttestation, SignedManifest,
+    TimestampRequest, TimestampResponse, VerifyRequest, VerifyResponse, WitnessReceipt,
 };
 
 pub struct WitnessClient {
@@ -28,7 +29,7 @@ impl WitnessClient {
         &self,
         hash: &str,
         freebird_token: Option<FreebirdToken>,
-    ) -> Result<SignedAttestation> {
+    ) -> Result<TimestampResponse> {
         let url = format!("{}/v1/timestamp", self.gateway_url);
 
         let request = TimestampRequest {
@@ -55,10 +56,45 @@ impl WitnessClient {
             .await
             .context("Failed to parse gateway response")?;
 
-        Ok(timestamp_response.attestation)
+        Ok(timestamp_response)
     }
 
-    pub async fn get_timestamp(&self, hash: &str) -> Result<SignedAttestation> {
+    /// Timestamp many hashes under one signed merkle root
+    pub async fn timestamp_batch(
+        &self,
+        hashes: Vec<String>,
+        freebird_token: Option<FreebirdToken>,
+    ) -> Result<Vec<TimestampResponse>> {
+        let url = format!("{}/v1/timestamp/batch", self.gateway_url);
+
+        let request = BatchTimestampRequest {
+            hashes,
+            freebird_token,
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to connect to gateway")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gateway returned error {}: {}", status, error_text);
+        }
+
+        let batch_response: BatchTimestampResponse = response
+            .json()
+            .await
+            .context("Failed to parse gateway response")?;
+
+        Ok(batch_response.timestamps)
+    }
+
+    pub async fn get_timestamp(&self, hash: &str) -> Result<TimestampResponse> {
         let url = format!("{}/v1/timestamp/{}", self.gateway_url, hash);
 
         let response = self
@@ -79,7 +115,7 @@ impl WitnessClient {
             .await
             .context("Failed to parse gateway response")?;
 
-        Ok(timestamp_response.attestation)
+        Ok(timestamp_response)
     }
 
     pub async fn verify(&self, attestation: &SignedAttestation) -> Result<VerifyResponse> {
@@ -111,6 +147,35 @@ impl WitnessClient {
         Ok(verify_response)
     }
 
+    pub async fn verify_federated(&self, hash: &str) -> Result<FederatedVerifyResponse> {
+        let url = format!("{}/v1/verify/federated", self.gateway_url);
+
+        let request = FederatedVerifyRequest {
+            hash: hash.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to connect to gateway")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gateway returned error {}: {}", status, error_text);
+        }
+
+        let verify_response: FederatedVerifyResponse = response
+            .json()
+            .await
+            .context("Failed to parse gateway response")?;
+
+        Ok(verify_response)
+    }
+
     pub async fn get_config(&self) -> Result<NetworkConfig> {
         let url = format!("{}/v1/config", self.gateway_url);
 
@@ -135,6 +200,34 @@ impl WitnessClient {
         Ok(config)
     }
 
+    /// Fetch the latest signed manifest, or a specific version
+    pub async fn get_manifest(&self, version: Option<u64>) -> Result<SignedManifest> {
+        let url = match version {
+            Some(version) => format!("{}/v1/manifest/{}", self.gateway_url, version),
+            None => format!("{}/v1/manifest", self.gateway_url),
+        };
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to connect to gateway")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gateway returned error {}: {}", status, error_text);
+        }
+
+        let manifest: SignedManifest = response
+            .json()
+            .await
+            .context("Failed to parse gateway response")?;
+
+        Ok(manifest)
+    }
+
     pub async fn get_batch_anchors(&self, hash: &str) -> Result<Vec<ExternalAnchorProof>> {
         let url = format!("{}/v1/anchors/{}", self.gateway_url, hash);
 
@@ -163,4 +256,28 @@ impl WitnessClient {
 
         Ok(anchors)
     }
+
+    pub async fn get_receipt(&self, hash: &str) -> Result<WitnessReceipt> {
+        let url = format!("{}/v1/receipt/{}", self.gateway_url, hash);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to connect to gateway")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Gateway returned error {}: {}", status, error_text);
+        }
+
+        let receipt: WitnessReceipt = response
+            .json()
+            .await
+            .context("Failed to parse gateway response")?;
+
+        Ok(receipt)
+    }
 }
@@ -0,0 +1,372 @@
+//! DKG command: coordinate a distributed key generation ceremony
+//!
+//! The coordinator relays signed deals and encrypted shares between witness
+//! nodes, resolves complaints by asking the accused dealer to reveal the
+//! disputed share, and writes the resulting threshold BLS network config.
+//! Every request is signed with the coordinator key the witnesses list in
+//! `dkg_coordinators`. A dealer only reveals a share in answer to a complaint
+//! signed by its recipient, so the coordinator never sees any other share.
+
+use anyhow::{Context, Result};
+use ed25519_dalek::SigningKey;
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use witness_core::{
+    DkgComplaint, DkgComplaintsResponse, DkgDeal, DkgDealsRequest, DkgFinalizeRequest,
+    DkgFinalizeResponse, DkgParticipantInfo, DkgReceiveRequest, DkgRevealRequest,
+    DkgRevealedShare, DkgSharesResponse, DkgStartRequest, NetworkConfig, RequestAuth,
+    SignatureScheme, WitnessInfo,
+};
+
+/// Ceremony participant as listed in the participants file
+#[derive(Debug, Deserialize)]
+struct Participant {
+    id: String,
+    endpoint: String,
+
+    /// The witness's current long-term public key (hex encoded)
+    pubkey: String,
+
+    #[serde(default)]
+    signature_scheme: SignatureScheme,
+}
+
+struct DkgClient {
+    client: Client,
+    coordinator_key: SigningKey,
+}
+
+impl DkgClient {
+    fn new(coordinator_key: SigningKey) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            coordinator_key,
+        }
+    }
+
+    async fn post<T: Serialize, R: DeserializeOwned>(
+        &self,
+        participant: &DkgParticipantInfo,
+        path: &str,
+        body: &T,
+    ) -> Result<R> {
+        let path = format!("/v1/dkg/{}", path);
+        let body = serde_json::to_vec(body)?;
+
+        let mut builder = self
+            .client
+            .post(format!("{}{}", participant.endpoint, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in RequestAuth::sign(&path, &body, &self.coordinator_key).headers() {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder
+            .body(body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to connect to witness {}", participant.id))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Witness {} returned error {}: {}", participant.id, status, error_text);
+        }
+
+        response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse response from witness {}", participant.id))
+    }
+}
+
+pub async fn run(
+    participants_path: &str,
+    threshold: usize,
+    network_id: &str,
+    ceremony_id: Option<String>,
+    coordinator_key_path: &str,
+    output_path: &str,
+) -> Result<()> {
+    let coordinator_key = load_coordinator_key(coordinator_key_path)?;
+
+    let content = std::fs::read_to_string(participants_path)
+        .with_context(|| format!("Failed to read participants file: {}", participants_path))?;
+    let listed: Vec<Participant> =
+        serde_json::from_str(&content).context("Failed to parse participants JSON")?;
+
+    let participants: Vec<DkgParticipantInfo> = listed
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| DkgParticipantInfo {
+            index: i as u32 + 1,
+            id: p.id,
+            endpoint: p.endpoint.trim_end_matches('/').to_string(),
+            pubkey: p.pubkey,
+            signature_scheme: p.signature_scheme,
+        })
+        .collect();
+
+    if threshold == 0 || threshold > participants.len() {
+        anyhow::bail!(
+            "Threshold must be between 1 and the number of participants ({})",
+            participants.len()
+        );
+    }
+
+    let ceremony_id = ceremony_id.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        format!("{}-{}", network_id, now)
+    });
+
+    let client = DkgClient::new(coordinator_key);
+
+    println!(
+        "Starting DKG ceremony {} ({}-of-{})",
+        ceremony_id,
+        threshold,
+        participants.len()
+    );
+
+    // Round 1: every participant deals and publishes signed commitments
+    let start = DkgStartRequest {
+        ceremony_id: ceremony_id.clone(),
+        threshold,
+        participants: participants.clone(),
+    };
+
+    let mut deals: Vec<DkgDeal> = Vec::new();
+    for participant in &participants {
+        let deal: DkgDeal = client.post(participant, "start", &start).await?;
+
+        // A bad deal leaves nobody able to encrypt shares to this participant
+        witness_core::verify_dkg_deal(&deal, participant)
+            .with_context(|| format!("Witness {} sent an invalid deal", participant.id))?;
+        if deal.commitments.len() != threshold {
+            anyhow::bail!("Witness {} committed to the wrong threshold", participant.id);
+        }
+
+        deals.push(deal);
+    }
+    println!("✓ Collected {} signed deals", deals.len());
+
+    // Round 2: relay deals; each participant encrypts its shares to the others
+    let deals_request = DkgDealsRequest {
+        ceremony_id: ceremony_id.clone(),
+        deals: deals.clone(),
+    };
+
+    let mut inboxes: BTreeMap<u32, Vec<_>> = BTreeMap::new();
+    for participant in &participants {
+        let response: DkgSharesResponse = client.post(participant, "deals", &deals_request).await?;
+
+        for share in response.shares {
+            if share.dealer != participant.index {
+                anyhow::bail!("Witness {} sent shares on behalf of another dealer", participant.id);
+            }
+            inboxes.entry(share.recipient).or_default().push(share);
+        }
+    }
+    println!("✓ Collected encrypted shares");
+
+    // Round 3: deliver shares and collect signed complaints
+    let mut complaints: Vec<DkgComplaint> = Vec::new();
+    for participant in &participants {
+        let request = DkgReceiveRequest {
+            ceremony_id: ceremony_id.clone(),
+            shares: inboxes.remove(&participant.index).unwrap_or_default(),
+        };
+        let response: DkgComplaintsResponse = client.post(participant, "receive", &request).await?;
+
+        for complaint in response.complaints {
+            check_complaint(&complaint, participant, &ceremony_id, participants.len())?;
+            complaints.push(complaint);
+        }
+    }
+
+    // Resolve complaints: the accused dealer must reveal the disputed share.
+    // The complaint against each disqualified dealer goes to finalize as
+    // evidence, and every revealed share goes along so nodes can check that
+    // no dealer that answered is dropped.
+    let mut disqualified: BTreeMap<u32, DkgComplaint> = BTreeMap::new();
+    let mut reveals: Vec<DkgRevealedShare> = Vec::new();
+    for complaint in complaints {
+        let (complainer, dealer) = (complaint.complainer, complaint.dealer);
+        let accused = &participants[dealer as usize - 1];
+        let complaining = &participants[complainer as usize - 1];
+        println!("! {} complained about dealer {}", complaining.id, accused.id);
+
+        let request = DkgRevealRequest {
+            ceremony_id: ceremony_id.clone(),
+            complaint: complaint.clone(),
+        };
+
+        let revealed: DkgRevealedShare = match client.post(accused, "reveal", &request).await {
+            Ok(revealed) => revealed,
+            Err(e) => {
+                println!("✗ Dealer {} did not answer the complaint: {:#}", accused.id, e);
+                disqualified.insert(dealer, complaint);
+                continue;
+            }
+        };
+
+        let valid = witness_core::decode_bls_secret_key(&revealed.share)
+            .and_then(|share| {
+                witness_core::verify_dkg_share(&deals[dealer as usize - 1], complainer, &share)
+            })
+            .unwrap_or(false);
+
+        if revealed.dealer != dealer || revealed.recipient != complainer || !valid {
+            println!("✗ Dealer {} revealed an invalid share; disqualified", accused.id);
+            disqualified.insert(dealer, complaint);
+            continue;
+        }
+
+        // A share that matches the dealer's commitments answers the complaint,
+        // whatever the complainer says next
+        reveals.push(revealed.clone());
+        let response: DkgComplaintsResponse = client.post(complaining, "resolve", &revealed).await?;
+        if response.complaints.iter().any(|c| c.dealer == dealer) {
+            println!(
+                "! {} still complains about {} after a valid reveal; ignoring",
+                complaining.id, accused.id
+            );
+        }
+        println!("✓ Complaint against {} resolved", accused.id);
+    }
+
+    let qualified: Vec<u32> = participants
+        .iter()
+        .map(|p| p.index)
+        .filter(|index| !disqualified.contains_key(index))
+        .collect();
+
+    if qualified.len() < threshold {
+        anyhow::bail!(
+            "Only {} dealers qualified, at least {} required; rerun the ceremony",
+            qualified.len(),
+            threshold
+        );
+    }
+
+    // Round 4: every participant derives its share; all must agree on the public outcome
+    let finalize = DkgFinalizeRequest {
+        ceremony_id: ceremony_id.clone(),
+        qualified: qualified.clone(),
+        complaints: disqualified.into_values().collect(),
+        reveals,
+    };
+
+    let mut outcome: Option<DkgFinalizeResponse> = None;
+    for participant in &participants {
+        let response: DkgFinalizeResponse = client.post(participant, "finalize", &finalize).await?;
+
+        if response.index != participant.index {
+            anyhow::bail!("Witness {} finalized with the wrong index", participant.id);
+        }
+
+        if let Some(expected) = &outcome {
+            if response.group_public_key != expected.group_public_key
+                || response.verification_keys != expected.verification_keys
+            {
+                anyhow::bail!(
+                    "Witness {} derived a different group key; do not use this ceremony",
+                    participant.id
+                );
+            }
+        } else {
+            outcome = Some(response);
+        }
+    }
+
+    let outcome = outcome.context("No participants")?;
+
+    let config = NetworkConfig {
+        id: network_id.to_string(),
+        witnesses: participants
+            .iter()
+            .map(|p| {
+                Ok(WitnessInfo {
+                    id: p.id.clone(),
+                    pubkey: outcome
+                        .verification_keys
+                        .get(&p.index)
+                        .cloned()
+                        .with_context(|| format!("No verification key for witness {}", p.id))?,
+                    endpoint: p.endpoint.clone(),
+                    share_index: Some(p.index),
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+        threshold,
+        signature_scheme: SignatureScheme::ThresholdBLS,
+        group_public_key: Some(outcome.group_public_key.clone()),
+        federation: Default::default(),
+        external_anchors: Default::default(),
+        federation_peers: vec![],
+        legacy_encoding_until: None,
+    };
+    config.validate()?;
+
+    std::fs::write(output_path, serde_json::to_string_pretty(&config)?)
+        .with_context(|| format!("Failed to write network config to: {}", output_path))?;
+
+    println!();
+    println!("✓ DKG ceremony complete");
+    println!("  Qualified dealers: {}/{}", qualified.len(), participants.len());
+    println!("  Group public key:  {}", outcome.group_public_key);
+    println!("  Network config:    {}", output_path);
+    println!();
+    println!("Each witness wrote its new key share config to its dkg_dir.");
+    println!("Restart the witnesses with those configs and the gateway with the network config.");
+
+    Ok(())
+}
+
+/// Check a complaint came from `participant`, about a known dealer, for this ceremony
+fn check_complaint(
+    complaint: &DkgComplaint,
+    participant: &DkgParticipantInfo,
+    ceremony_id: &str,
+    participant_count: usize,
+) -> Result<()> {
+    if complaint.ceremony_id != ceremony_id
+        || complaint.dealer == 0
+        || complaint.dealer as usize > participant_count
+    {
+        anyhow::bail!(
+            "Witness {} complained about unknown dealer {}",
+            participant.id,
+            complaint.dealer
+        );
+    }
+
+    witness_core::verify_dkg_complaint(complaint, participant)
+        .with_context(|| format!("Witness {} sent an invalid complaint", participant.id))
+}
+
+/// Read a hex-encoded Ed25519 private key from a file
+fn load_coordinator_key(path: &str) -> Result<SigningKey> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read coordinator key: {}", path))?;
+
+    let bytes: [u8; 32] = hex::decode(content.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("Coordinator key must be 32 hex-encoded bytes: {}", path))?;
+
+    Ok(SigningKey::from_bytes(&bytes))
+}
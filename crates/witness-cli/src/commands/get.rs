@@ -17,27 +17,55 @@ pub async fn run(gateway_url: &str, hash: &str, output_format: &str) -> Result<(
     }
 
     let client = WitnessClient::new(gateway_url);
-    let attestation = client.get_timestamp(hash).await?;
+    let timestamp = client.get_timestamp(hash).await?;
+    timestamp
+        .verify_inclusion()
+        .context("Gateway returned an invalid inclusion proof")?;
+    let attestation = &timestamp.attestation;
+
+    // Older gateways do not expose federated verification
+    let verification_level = client
+        .verify_federated(hash)
+        .await
+        .ok()
+        .filter(|response| response.verified)
+        .map(|response| response.verification_level);
 
     // Output results
     match output_format {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&attestation)?);
+            println!("{}", serde_json::to_string_pretty(&timestamp)?);
         }
         "text" => {
             println!("✓ Found timestamp");
             println!();
-            println!("Hash:      {}", hex::encode(attestation.attestation.hash));
+            println!("Hash:      {}", hex::encode(timestamp.hash()));
+            if let Some(proof) = &timestamp.inclusion_proof {
+                println!("Root:      {} (leaf {} of a batch timestamp)", hex::encode(proof.root), proof.index);
+            }
+            let exact = match (attestation.attestation.timestamp_millis, attestation.attestation.timestamp_ms()) {
+                (Some(_), Some(timestamp_ms)) => witness_core::format_timestamp_ms(timestamp_ms),
+                _ => attestation.attestation.timestamp.to_string(),
+            };
             println!("Timestamp: {} ({})",
-                attestation.attestation.timestamp,
+                exact,
                 format_timestamp(attestation.attestation.timestamp)
             );
+            if let Some((earliest, latest)) = attestation.time_interval() {
+                println!("Accuracy:  between {} and {} ({} witness clocks)",
+                    witness_core::format_timestamp_ms(earliest),
+                    witness_core::format_timestamp_ms(latest),
+                    attestation.time_readings.len()
+                );
+            }
             println!("Network:   {}", attestation.attestation.network_id);
             println!("Sequence:  {}", attestation.attestation.sequence);
             println!();
 
             // Display signature information based on type
-            if attestation.is_aggregated() {
+            if attestation.is_threshold() {
+                println!("Signatures: threshold BLS group signature (signers not revealed)");
+            } else if attestation.is_aggregated() {
                 println!("Signatures: BLS aggregated signature from {} witnesses", attestation.signature_count());
                 match &attestation.signatures {
                     witness_core::signature_scheme::AttestationSignatures::Aggregated { signers, .. } => {
@@ -58,6 +86,11 @@ pub async fn run(gateway_url: &str, hash: &str, output_format: &str) -> Result<(
                     _ => {}
                 }
             }
+
+            if let Some(level) = verification_level {
+                println!();
+                println!("Verification level: {}", level);
+            }
         }
         _ => {
             anyhow::bail!("Invalid output format: {}", output_format);
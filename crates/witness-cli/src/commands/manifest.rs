@@ -0,0 +1,206 @@
+//! Manifest commands: create, verify and pin signed network manifests
+//!
+//! `fetch` pins the first manifest it sees for a network (trust on first
+//! use) and afterwards only accepts updates that chain from the pinned one
+//! and are signed by a quorum of its witnesses.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use witness_core::{NetworkConfig, NetworkManifest, NetworkSnapshot, SignedManifest};
+
+use crate::client::WitnessClient;
+
+/// Create an unsigned manifest for witnesses to co-sign with `witness-node --sign-manifest`
+pub fn create(network_config_path: &str, previous_path: Option<String>, output_path: &str) -> Result<()> {
+    let content = fs::read_to_string(network_config_path)
+        .with_context(|| format!("Failed to read network configuration: {}", network_config_path))?;
+    let config: NetworkConfig =
+        serde_json::from_str(&content).context("Failed to parse network configuration JSON")?;
+    config.validate().context("Invalid network configuration")?;
+
+    let previous = previous_path.map(|path| load_manifest(&path)).transpose()?;
+
+    let manifest = NetworkManifest::new(
+        NetworkSnapshot::from_config(&config),
+        previous.as_ref().map(|p| &p.manifest),
+    );
+    let signed = SignedManifest::new(manifest);
+
+    fs::write(output_path, serde_json::to_string_pretty(&signed)?)
+        .with_context(|| format!("Failed to write manifest to: {}", output_path))?;
+
+    println!("Created manifest version {} for network {}", signed.manifest.version, config.id);
+    println!("Manifest saved to: {}", output_path);
+    println!();
+    match &previous {
+        Some(previous) => println!(
+            "Needs signatures from {} witnesses of version {} and {} of the new set.",
+            previous.manifest.network.threshold,
+            previous.manifest.version,
+            config.threshold
+        ),
+        None => println!("Needs signatures from {} witnesses.", config.threshold),
+    }
+    println!("Each witness operator runs: witness-node --config <witness.json> --sign-manifest {}", output_path);
+
+    Ok(())
+}
+
+/// Verify a manifest file, optionally as the successor of another
+pub fn verify(path: &str, previous_path: Option<String>) -> Result<()> {
+    let manifest = load_manifest(path)?;
+
+    let signers = match previous_path {
+        Some(previous_path) => {
+            let previous = load_manifest(&previous_path)?;
+            previous.verify_self_signed().context("Invalid previous manifest")?;
+            previous.verify_update(&manifest).context("Invalid manifest update")?
+        }
+        None => manifest.verify_self_signed().context("Invalid manifest")?,
+    };
+
+    println!("✓ VALID");
+    println!();
+    print_summary(&manifest);
+    println!("Signatures: {} valid", signers);
+
+    Ok(())
+}
+
+/// Fetch the gateway's manifest, pinning it on first use and verifying updates
+pub async fn fetch(gateway_url: &str, pin_path: Option<String>) -> Result<()> {
+    let client = WitnessClient::new(gateway_url);
+    let latest = client.get_manifest(None).await?;
+    latest
+        .verify_self_signed()
+        .context("Gateway served a manifest without a valid witness quorum")?;
+
+    let pin_path = match pin_path {
+        Some(path) => PathBuf::from(path),
+        None => default_pin_path(&latest.manifest.network.id)?,
+    };
+
+    let Some(pinned) = load_pin(&pin_path)? else {
+        save_pin(&pin_path, &latest)?;
+        println!("⚠ First manifest seen for network {}; trusting it", latest.manifest.network.id);
+        println!("  Compare this hash with one published by the network operators:");
+        println!("  {}", hex::encode(latest.manifest.hash()));
+        println!();
+        print_summary(&latest);
+        println!("Pinned to: {}", pin_path.display());
+        return Ok(());
+    };
+
+    let pinned_version = pinned.manifest.version;
+    let latest_version = latest.manifest.version;
+
+    if latest_version < pinned_version {
+        anyhow::bail!(
+            "Gateway served manifest version {}, older than pinned version {}",
+            latest_version,
+            pinned_version
+        );
+    }
+
+    if latest_version == pinned_version {
+        if latest.manifest.hash() != pinned.manifest.hash() {
+            anyhow::bail!(
+                "Gateway served a different manifest for pinned version {}; the network may be compromised",
+                pinned_version
+            );
+        }
+
+        println!("✓ Manifest up to date (version {})", pinned_version);
+        println!();
+        print_summary(&pinned);
+        return Ok(());
+    }
+
+    // Walk the chain from the pinned manifest to the latest one
+    let mut current = pinned;
+    for version in pinned_version + 1..=latest_version {
+        let next = if version == latest_version {
+            latest.clone()
+        } else {
+            client.get_manifest(Some(version)).await?
+        };
+
+        current
+            .verify_update(&next)
+            .with_context(|| format!("Manifest version {} is not a valid update", version))?;
+        current = next;
+    }
+
+    save_pin(&pin_path, &current)?;
+
+    println!("✓ Manifest updated from version {} to {}", pinned_version, latest_version);
+    println!();
+    print_summary(&current);
+    println!("Pinned to: {}", pin_path.display());
+
+    Ok(())
+}
+
+fn print_summary(signed: &SignedManifest) {
+    let manifest = &signed.manifest;
+    println!("Network:   {}", manifest.network.id);
+    println!("Version:   {}", manifest.version);
+    println!("Hash:      {}", hex::encode(manifest.hash()));
+    println!(
+        "Threshold: {} of {} ({})",
+        manifest.network.threshold,
+        manifest.network.witnesses.len(),
+        manifest.network.signature_scheme
+    );
+    for witness in &manifest.network.witnesses {
+        println!("  {} {}", witness.id, witness.pubkey);
+    }
+}
+
+fn load_manifest(path: &str) -> Result<SignedManifest> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest: {}", path))?;
+    serde_json::from_str(&content).context("Failed to parse manifest JSON")
+}
+
+fn default_pin_path(network_id: &str) -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
+        .context("Could not determine config directory")?;
+
+    // Network IDs come from the gateway; keep them from escaping the pin directory
+    let file_name: String = network_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+
+    Ok(config_dir.join("witness").join("manifests").join(format!("{}.json", file_name)))
+}
+
+/// The network configuration from the manifest pinned for this network, if any
+pub fn pinned_network_config(network_id: &str) -> Result<Option<(NetworkConfig, PathBuf)>> {
+    let pin_path = default_pin_path(network_id)?;
+    Ok(load_pin(&pin_path)?.map(|pinned| (pinned.to_network_config(), pin_path)))
+}
+
+fn load_pin(path: &Path) -> Result<Option<SignedManifest>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let pinned = load_manifest(&path.to_string_lossy())?;
+    pinned
+        .verify_self_signed()
+        .context("Pinned manifest is invalid")?;
+    Ok(Some(pinned))
+}
+
+fn save_pin(path: &Path, manifest: &SignedManifest) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create manifest pin directory")?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(manifest)?)
+        .with_context(|| format!("Failed to write pinned manifest: {}", path.display()))
+}
@@ -1,5 +1,8 @@
 pub mod anchors;
+pub mod dkg;
 pub mod get;
+pub mod manifest;
+pub mod receipt;
 pub mod timestamp;
 pub mod token;
 pub mod verify;
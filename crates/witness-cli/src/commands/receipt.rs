@@ -0,0 +1,185 @@
+//! Receipt commands: fetch and verify self-contained proof bundles
+
+use anyhow::{Context, Result};
+use std::fs;
+use witness_core::{format_timestamp_ms, WitnessReceipt};
+
+use crate::client::WitnessClient;
+use crate::commands::verify::load_network_config;
+
+/// Fetch a receipt for a hash and optionally save it to a file
+pub async fn fetch(
+    gateway_url: &str,
+    hash: &str,
+    output_format: &str,
+    save_path: Option<String>,
+) -> Result<()> {
+    // Validate hash
+    hex::decode(hash)
+        .context("Invalid hash format: must be hex encoded SHA-256")?;
+
+    if hash.len() != 64 {
+        anyhow::bail!("Invalid hash length: must be 64 hex characters (32 bytes)");
+    }
+
+    let client = WitnessClient::new(gateway_url);
+    let receipt = client.get_receipt(hash).await?;
+
+    match output_format {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&receipt)?);
+        }
+        "text" => {
+            println!("✓ Receipt retrieved");
+            println!();
+            print_summary(&receipt);
+        }
+        _ => {
+            anyhow::bail!("Invalid output format: {}", output_format);
+        }
+    }
+
+    if let Some(save_path) = save_path {
+        let json = serde_json::to_string_pretty(&receipt)?;
+        fs::write(&save_path, json)
+            .with_context(|| format!("Failed to write receipt to: {}", save_path))?;
+
+        if output_format == "text" {
+            println!();
+            println!("Receipt saved to: {}", save_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify a saved receipt without contacting the gateway
+pub async fn verify(
+    file_path: &str,
+    output_format: &str,
+    network_config_path: Option<String>,
+    legacy_cutoff: Option<u64>,
+) -> Result<()> {
+    let content = fs::read_to_string(file_path)
+        .with_context(|| format!("Failed to read receipt file: {}", file_path))?;
+
+    let receipt: WitnessReceipt = serde_json::from_str(&content)
+        .context("Failed to parse receipt JSON")?;
+
+    // Prefer a pinned configuration over the keys embedded in the receipt
+    let pinned = network_config_path.is_some();
+    let mut config = match network_config_path {
+        Some(path) => load_network_config(&path)?,
+        None => receipt.network.to_network_config(),
+    };
+    // Only the verifier decides whether v0 attestations are still acceptable
+    if legacy_cutoff.is_some() {
+        config.legacy_encoding_until = legacy_cutoff;
+    }
+
+    let result = witness_core::verify_receipt(&receipt, &config);
+
+    match output_format {
+        "json" => {
+            let json = match &result {
+                Ok(verification) => serde_json::json!({
+                    "valid": true,
+                    "pinned_config": pinned,
+                    "verification": verification,
+                    "verification_level": verification.level(),
+                }),
+                Err(e) => serde_json::json!({
+                    "valid": false,
+                    "pinned_config": pinned,
+                    "message": format!("Invalid: {}", e),
+                }),
+            };
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        "text" => {
+            print_summary(&receipt);
+            println!();
+
+            if !pinned {
+                println!("⚠ No --network-config given: trusting witness keys embedded in the receipt");
+                println!();
+            }
+
+            match &result {
+                Ok(verification) => {
+                    println!("✓ VALID");
+                    println!();
+                    println!(
+                        "Signatures:       {} verified ({} required)",
+                        verification.verified_signatures, config.threshold
+                    );
+                    println!(
+                        "Merkle inclusion: {}",
+                        if verification.batched { "verified" } else { "not batched yet" }
+                    );
+                    println!(
+                        "Cross-anchors:    {} ({} verified against peer keys)",
+                        verification.cross_anchor_count, verification.verified_cross_anchors
+                    );
+                    for invalid in &verification.invalid_cross_anchors {
+                        println!("                  ⚠ invalid cross-anchor from {}", invalid);
+                    }
+                    if verification.legacy_cross_anchors > 0 {
+                        println!(
+                            "                  {} legacy, made before signed statements and not verifiable",
+                            verification.legacy_cross_anchors
+                        );
+                    }
+                    println!(
+                        "External anchors: {} (unverified; check them with the anchor services)",
+                        verification.unverified_external_anchors
+                    );
+                    println!();
+                    println!("Verification level: {}", verification.level());
+                }
+                Err(e) => {
+                    println!("✗ INVALID");
+                    println!();
+                    println!("Invalid: {}", e);
+                }
+            }
+        }
+        _ => {
+            anyhow::bail!("Invalid output format: {}", output_format);
+        }
+    }
+
+    if result.is_err() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn print_summary(receipt: &WitnessReceipt) {
+    let attestation = &receipt.attestation.attestation;
+
+    println!("Receipt version: {}", receipt.version);
+    println!("Hash:      {}", hex::encode(receipt.hash()));
+    if let Some(proof) = &receipt.inclusion_proof {
+        println!("Root:      {} (leaf {} of a batch timestamp)", hex::encode(proof.root), proof.index);
+    }
+    match (attestation.timestamp_millis, attestation.timestamp_ms()) {
+        (Some(_), Some(timestamp_ms)) => println!("Timestamp: {}", format_timestamp_ms(timestamp_ms)),
+        _ => println!("Timestamp: {}", attestation.timestamp),
+    }
+    if let Some((earliest, latest)) = receipt.attestation.time_interval() {
+        println!(
+            "Accuracy:  between {} and {} ({} witness clocks)",
+            format_timestamp_ms(earliest),
+            format_timestamp_ms(latest),
+            receipt.attestation.time_readings.len()
+        );
+    }
+    println!("Network:   {}", attestation.network_id);
+    println!("Sequence:  {}", attestation.sequence);
+
+    if let Some(batch) = &receipt.batch {
+        println!("Batch:     {} (root: {})", batch.id, hex::encode(batch.merkle_root));
+    }
+}
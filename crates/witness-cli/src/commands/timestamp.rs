@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::fs;
-use witness_core::FreebirdToken;
+use std::path::{Path, PathBuf};
+use witness_core::{FreebirdToken, SignedAttestation, TimestampResponse};
 
 use crate::client::WitnessClient;
 use crate::freebird_client::FreebirdIssuerClient;
@@ -9,7 +10,7 @@ use crate::token_wallet::TokenWallet;
 
 pub async fn run(
     gateway_url: &str,
-    file_path: Option<String>,
+    file_paths: Vec<String>,
     hash_hex: Option<String>,
     output_format: &str,
     save_path: Option<String>,
@@ -21,25 +22,26 @@ pub async fn run(
     freebird_acquire: Option<String>,
     freebird_wallet: bool,
 ) -> Result<()> {
-    // Determine the hash to timestamp
-    let hash = if let Some(path) = file_path {
-        // Read file and compute hash
-        let content = fs::read(&path)
-            .with_context(|| format!("Failed to read file: {}", path))?;
+    // Determine the hashes to timestamp
+    let files = collect_files(&file_paths)?;
+    let mut hashes = Vec::new();
 
-        let mut hasher = Sha256::new();
-        hasher.update(&content);
-        let hash_bytes = hasher.finalize();
+    if !file_paths.is_empty() {
+        if files.is_empty() {
+            anyhow::bail!("No files found to timestamp");
+        }
 
-        let hash_hex = hex::encode(hash_bytes);
+        for path in &files {
+            let hash_hex = hash_file(path)?;
 
-        if output_format == "text" {
-            println!("File: {}", path);
-            println!("SHA-256: {}", hash_hex);
-            println!();
-        }
+            if output_format == "text" && files.len() == 1 {
+                println!("File: {}", path.display());
+                println!("SHA-256: {}", hash_hex);
+                println!();
+            }
 
-        hash_hex
+            hashes.push(hash_hex);
+        }
     } else if let Some(hash) = hash_hex {
         // Validate hash format
         hex::decode(&hash)
@@ -49,10 +51,10 @@ pub async fn run(
             anyhow::bail!("Invalid hash length: must be 64 hex characters (32 bytes)");
         }
 
-        hash
+        hashes.push(hash);
     } else {
         anyhow::bail!("Must provide either --file or --hash");
-    };
+    }
 
     // Build Freebird token from various sources
     let freebird_token = if let Some(token_path) = freebird_token_path {
@@ -110,55 +112,60 @@ pub async fn run(
 
     // Request timestamp
     if output_format == "text" {
+        let target = match hashes.len() {
+            1 => "timestamp".to_string(),
+            count => format!("timestamps for {} files", count),
+        };
         if freebird_token.is_some() {
-            println!("Requesting timestamp with Freebird token...");
+            println!("Requesting {} with Freebird token...", target);
         } else {
-            println!("Requesting timestamp from gateway...");
+            println!("Requesting {} from gateway...", target);
         }
     }
 
     let client = WitnessClient::new(gateway_url);
-    let attestation = client.timestamp(&hash, freebird_token).await?;
+
+    if hashes.len() > 1 {
+        let timestamps = client.timestamp_batch(hashes.clone(), freebird_token).await?;
+        return print_batch(&files, &hashes, &timestamps, output_format, save_path);
+    }
+
+    let timestamp = client.timestamp(&hashes[0], freebird_token).await?;
+    check_timestamp(&timestamp, &hashes[0])?;
+    let attestation = &timestamp.attestation;
 
     // Output results
     match output_format {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&attestation)?);
+            println!("{}", serde_json::to_string_pretty(&timestamp)?);
         }
         "text" => {
             println!("✓ Timestamp successful!");
             println!();
-            println!("Hash:      {}", hex::encode(attestation.attestation.hash));
+            println!("Hash:      {}", hex::encode(timestamp.hash()));
+            if let Some(proof) = &timestamp.inclusion_proof {
+                println!("Root:      {} (leaf {} of a batch timestamp)", hex::encode(proof.root), proof.index);
+            }
+            let exact = match (attestation.attestation.timestamp_millis, attestation.attestation.timestamp_ms()) {
+                (Some(_), Some(timestamp_ms)) => witness_core::format_timestamp_ms(timestamp_ms),
+                _ => attestation.attestation.timestamp.to_string(),
+            };
             println!("Timestamp: {} ({})",
-                attestation.attestation.timestamp,
+                exact,
                 format_timestamp(attestation.attestation.timestamp)
             );
+            if let Some((earliest, latest)) = attestation.time_interval() {
+                println!("Accuracy:  between {} and {} ({} witness clocks)",
+                    witness_core::format_timestamp_ms(earliest),
+                    witness_core::format_timestamp_ms(latest),
+                    attestation.time_readings.len()
+                );
+            }
             println!("Network:   {}", attestation.attestation.network_id);
             println!("Sequence:  {}", attestation.attestation.sequence);
             println!();
 
-            // Display signature information based on type
-            if attestation.is_aggregated() {
-                println!("Signatures: BLS aggregated signature from {} witnesses", attestation.signature_count());
-                match &attestation.signatures {
-                    witness_core::signature_scheme::AttestationSignatures::Aggregated { signers, .. } => {
-                        for signer in signers {
-                            println!("  - {}", signer);
-                        }
-                    }
-                    _ => {}
-                }
-            } else {
-                println!("Signatures: {} witnesses signed", attestation.signature_count());
-                match &attestation.signatures {
-                    witness_core::signature_scheme::AttestationSignatures::MultiSig { signatures } => {
-                        for sig in signatures {
-                            println!("  - {}", sig.witness_id);
-                        }
-                    }
-                    _ => {}
-                }
-            }
+            print_signatures(attestation);
         }
         _ => {
             anyhow::bail!("Invalid output format: {}", output_format);
@@ -167,7 +174,11 @@ pub async fn run(
 
     // Save attestation if requested
     if let Some(save_path) = save_path {
-        let json = serde_json::to_string_pretty(&attestation)?;
+        // A batch timestamp is only meaningful with its inclusion proof
+        let json = match timestamp.inclusion_proof {
+            Some(_) => serde_json::to_string_pretty(&timestamp)?,
+            None => serde_json::to_string_pretty(&timestamp.attestation)?,
+        };
         fs::write(&save_path, json)
             .with_context(|| format!("Failed to write attestation to: {}", save_path))?;
 
@@ -180,6 +191,181 @@ pub async fn run(
     Ok(())
 }
 
+/// Print and save the results of a batch timestamp, one per file
+fn print_batch(
+    files: &[PathBuf],
+    hashes: &[String],
+    timestamps: &[TimestampResponse],
+    output_format: &str,
+    save_dir: Option<String>,
+) -> Result<()> {
+    if timestamps.len() != hashes.len() {
+        anyhow::bail!(
+            "Gateway returned {} timestamps for {} hashes",
+            timestamps.len(),
+            hashes.len()
+        );
+    }
+    for (timestamp, hash) in timestamps.iter().zip(hashes) {
+        check_timestamp(timestamp, hash)?;
+    }
+
+    match output_format {
+        "json" => {
+            let results: Vec<_> = files
+                .iter()
+                .zip(hashes)
+                .zip(timestamps)
+                .map(|((file, hash), timestamp)| {
+                    serde_json::json!({
+                        "file": file,
+                        "hash": hash,
+                        "timestamp": timestamp,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        "text" => {
+            println!("✓ Timestamped {} files", files.len());
+            println!();
+            for (file, hash) in files.iter().zip(hashes) {
+                println!("  {}  {}", hash, file.display());
+            }
+
+            // Hashes timestamped earlier keep their own attestations
+            let mut attestations: Vec<(&SignedAttestation, usize)> = Vec::new();
+            for timestamp in timestamps {
+                match attestations
+                    .iter_mut()
+                    .find(|(attestation, _)| attestation.attestation == timestamp.attestation.attestation)
+                {
+                    Some((_, count)) => *count += 1,
+                    None => attestations.push((&timestamp.attestation, 1)),
+                }
+            }
+
+            for (attestation, count) in attestations {
+                println!();
+                println!("Root:      {} ({} files)", hex::encode(attestation.attestation.hash), count);
+                println!(
+                    "Timestamp: {} ({})",
+                    attestation
+                        .attestation
+                        .timestamp_ms()
+                        .map_or_else(|| attestation.attestation.timestamp.to_string(), witness_core::format_timestamp_ms),
+                    format_timestamp(attestation.attestation.timestamp)
+                );
+                println!("Network:   {}", attestation.attestation.network_id);
+                println!("Sequence:  {}", attestation.attestation.sequence);
+                print_signatures(attestation);
+            }
+        }
+        _ => {
+            anyhow::bail!("Invalid output format: {}", output_format);
+        }
+    }
+
+    // Save one timestamp per hash, named after the hash
+    if let Some(save_dir) = save_dir {
+        fs::create_dir_all(&save_dir)
+            .with_context(|| format!("Failed to create directory: {}", save_dir))?;
+
+        for (hash, timestamp) in hashes.iter().zip(timestamps) {
+            let path = Path::new(&save_dir).join(format!("{}.json", hash));
+            fs::write(&path, serde_json::to_string_pretty(timestamp)?)
+                .with_context(|| format!("Failed to write attestation to: {}", path.display()))?;
+        }
+
+        if output_format == "text" {
+            println!();
+            println!("Attestations saved to: {}", save_dir);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a timestamp is for the requested hash and its inclusion proof holds
+///
+/// Signatures are checked with `witness verify`.
+fn check_timestamp(timestamp: &TimestampResponse, hash: &str) -> Result<()> {
+    if hex::encode(timestamp.hash()) != hash.to_lowercase() {
+        anyhow::bail!("Gateway returned a timestamp for a different hash than {}", hash);
+    }
+    timestamp
+        .verify_inclusion()
+        .with_context(|| format!("Gateway returned an invalid inclusion proof for {}", hash))
+}
+
+fn print_signatures(attestation: &SignedAttestation) {
+    // Display signature information based on type
+    if attestation.is_threshold() {
+        println!("Signatures: threshold BLS group signature (signers not revealed)");
+    } else if attestation.is_aggregated() {
+        println!("Signatures: BLS aggregated signature from {} witnesses", attestation.signature_count());
+        match &attestation.signatures {
+            witness_core::signature_scheme::AttestationSignatures::Aggregated { signers, .. } => {
+                for signer in signers {
+                    println!("  - {}", signer);
+                }
+            }
+            _ => {}
+        }
+    } else {
+        println!("Signatures: {} witnesses signed", attestation.signature_count());
+        match &attestation.signatures {
+            witness_core::signature_scheme::AttestationSignatures::MultiSig { signatures } => {
+                for sig in signatures {
+                    println!("  - {}", sig.witness_id);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Expand the given paths into files, walking directories in name order
+fn collect_files(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_path(Path::new(path), &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_path(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+    if !metadata.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)
+        .with_context(|| format!("Failed to read directory: {}", path.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+    entries.sort();
+
+    for entry in entries {
+        collect_path(&entry, files)?;
+    }
+    Ok(())
+}
+
+/// SHA-256 of a file's contents, hex encoded
+fn hash_file(path: &Path) -> Result<String> {
+    let content = fs::read(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    Ok(hex::encode(hasher.finalize()))
+}
+
 fn format_timestamp(timestamp: u64) -> String {
     use std::time::{Duration, UNIX_EPOCH};
 
@@ -202,3 +388,36 @@ fn format_timestamp(timestamp: u64) -> String {
         Err(_) => format!("Unix timestamp: {}", timestamp),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_files_walks_directories() {
+        let dir = std::env::temp_dir().join(format!("witness-timestamp-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("b.txt"), b"b").unwrap();
+        fs::write(dir.join("a.txt"), b"a").unwrap();
+        fs::write(dir.join("sub").join("c.txt"), b"c").unwrap();
+
+        let single = dir.join("a.txt").to_string_lossy().into_owned();
+        let files = collect_files(&[dir.to_string_lossy().into_owned(), single]).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                dir.join("a.txt"),
+                dir.join("b.txt"),
+                dir.join("sub").join("c.txt"),
+                dir.join("a.txt"),
+            ]
+        );
+        assert_eq!(
+            hash_file(&dir.join("a.txt")).unwrap(),
+            hex::encode(Sha256::digest(b"a"))
+        );
+
+        assert!(collect_files(&[dir.join("missing").to_string_lossy().into_owned()]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,37 +1,123 @@
 use anyhow::{Context, Result};
 use std::fs;
-use witness_core::SignedAttestation;
+use witness_core::{
+    NetworkConfig, SignedAttestation, SignedManifest, TimestampResponse, VerificationLevel,
+    VerifyResponse,
+};
 
 use crate::client::WitnessClient;
+use crate::commands::manifest;
 
-pub async fn run(gateway_url: &str, file_path: &str, output_format: &str) -> Result<()> {
+pub async fn run(
+    gateway_url: &str,
+    file_path: &str,
+    output_format: &str,
+    offline: bool,
+    network_config_path: Option<String>,
+    legacy_cutoff: Option<u64>,
+) -> Result<()> {
     // Load attestation from file
     let content = fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read attestation file: {}", file_path))?;
 
-    let attestation: SignedAttestation = serde_json::from_str(&content)
-        .context("Failed to parse attestation JSON")?;
+    // Batch timestamps are saved together with their inclusion proof
+    let timestamp = match serde_json::from_str::<TimestampResponse>(&content) {
+        Ok(timestamp) => timestamp,
+        Err(_) => TimestampResponse::new(
+            serde_json::from_str(&content).context("Failed to parse attestation JSON")?,
+        ),
+    };
+    let attestation = &timestamp.attestation;
 
     if output_format == "text" {
         println!("Verifying attestation...");
-        println!("Hash: {}", hex::encode(attestation.attestation.hash));
+        println!("Hash: {}", hex::encode(timestamp.hash()));
+        if let Some(proof) = &timestamp.inclusion_proof {
+            println!("Root: {}", hex::encode(proof.root));
+        }
         println!();
     }
 
-    // Verify with gateway
-    let client = WitnessClient::new(gateway_url);
-    let result = client.verify(&attestation).await?;
+    let (result, verification_level) = match (timestamp.verify_inclusion(), offline) {
+        // The signatures are worthless if the hash is not under the signed root
+        (Err(e), _) => {
+            let result = VerifyResponse {
+                valid: false,
+                verified_signatures: 0,
+                required_signatures: 0,
+                message: format!("Invalid: {}", e),
+            };
+            (result, VerificationLevel::None)
+        }
+        // Verify locally against a pinned network configuration
+        (Ok(()), true) => {
+            let (mut config, source) = match network_config_path {
+                Some(config_path) => (load_network_config(&config_path)?, config_path),
+                None => {
+                    let network_id = &attestation.attestation.network_id;
+                    let Some((config, pin_path)) = manifest::pinned_network_config(network_id)? else {
+                        anyhow::bail!(
+                            "No manifest pinned for network '{}'; pass --network-config or run `witness manifest fetch`",
+                            network_id
+                        );
+                    };
+                    (config, pin_path.display().to_string())
+                }
+            };
+            if legacy_cutoff.is_some() {
+                config.legacy_encoding_until = legacy_cutoff;
+            }
+
+            if output_format == "text" {
+                println!("Offline mode: using network configuration from {}", source);
+                println!();
+            }
+
+            // A bare attestation proves no more than its threshold signatures
+            let result = verify_offline(attestation, &config);
+            let level = if result.valid {
+                VerificationLevel::Basic
+            } else {
+                VerificationLevel::None
+            };
+
+            (result, level)
+        }
+        // Verify with gateway
+        (Ok(()), false) => {
+            let client = WitnessClient::new(gateway_url);
+            let result = client.verify(attestation).await?;
+
+            // Batching and anchoring are looked up by hash on the gateway
+            let level = if result.valid {
+                client
+                    .verify_federated(&hex::encode(timestamp.hash()))
+                    .await
+                    .ok()
+                    .filter(|response| response.verified)
+                    .map(|response| response.verification_level)
+                    .unwrap_or(VerificationLevel::Basic)
+            } else {
+                VerificationLevel::None
+            };
+
+            (result, level)
+        }
+    };
 
     // Output results
     match output_format {
         "json" => {
-            println!("{}", serde_json::to_string_pretty(&result)?);
+            let mut json = serde_json::to_value(&result)?;
+            json["verification_level"] = serde_json::to_value(&verification_level)?;
+            println!("{}", serde_json::to_string_pretty(&json)?);
         }
         "text" => {
             if result.valid {
                 println!("✓ VALID");
                 println!();
                 println!("{}", result.message);
+                println!("Verification level: {}", verification_level);
             } else {
                 println!("✗ INVALID");
                 println!();
@@ -46,3 +132,157 @@ pub async fn run(gateway_url: &str, file_path: &str, output_format: &str) -> Res
 
     Ok(())
 }
+
+/// Load a pinned network configuration (e.g. saved with `witness config --save`)
+///
+/// Also accepts a signed manifest (e.g. pinned with `witness manifest fetch`),
+/// which must carry a quorum of its witnesses' signatures.
+pub fn load_network_config(path: &str) -> Result<NetworkConfig> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read network configuration: {}", path))?;
+
+    if let Ok(manifest) = serde_json::from_str::<SignedManifest>(&content) {
+        manifest
+            .verify_self_signed()
+            .context("Invalid signed manifest")?;
+        return Ok(manifest.to_network_config());
+    }
+
+    let config: NetworkConfig = serde_json::from_str(&content)
+        .context("Failed to parse network configuration JSON")?;
+
+    config
+        .validate()
+        .context("Invalid network configuration")?;
+
+    Ok(config)
+}
+
+/// Verify a signed attestation locally without contacting any gateway
+pub fn verify_offline(attestation: &SignedAttestation, config: &NetworkConfig) -> VerifyResponse {
+    if attestation.attestation.network_id != config.id {
+        return VerifyResponse {
+            valid: false,
+            verified_signatures: 0,
+            required_signatures: config.threshold,
+            message: format!(
+                "Invalid: attestation is from network '{}', configuration is for '{}'",
+                attestation.attestation.network_id, config.id
+            ),
+        };
+    }
+
+    match witness_core::verify_signed_attestation(attestation, config) {
+        Ok(verified_count) => VerifyResponse {
+            valid: true,
+            verified_signatures: verified_count,
+            required_signatures: config.threshold,
+            message: format!(
+                "Valid: {} of {} signatures verified, {} required",
+                verified_count,
+                config.witnesses.len(),
+                config.threshold
+            ),
+        },
+        Err(e) => VerifyResponse {
+            valid: false,
+            verified_signatures: 0,
+            required_signatures: config.threshold,
+            message: format!("Invalid: {}", e),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use witness_core::{Attestation, SignatureScheme, WitnessInfo};
+
+    fn test_config(scheme: SignatureScheme, pubkeys: Vec<String>) -> NetworkConfig {
+        NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses: pubkeys
+                .into_iter()
+                .enumerate()
+                .map(|(i, pubkey)| WitnessInfo {
+                    id: format!("w{}", i + 1),
+                    pubkey,
+                    endpoint: format!("http://localhost:300{}", i + 1),
+                    share_index: None,
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+                .collect(),
+            threshold: 2,
+            signature_scheme: scheme,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_offline_ed25519() {
+        let keys: Vec<_> = (0..3).map(|_| witness_core::generate_keypair()).collect();
+        let config = test_config(
+            SignatureScheme::Ed25519,
+            keys.iter().map(|(_, vk)| witness_core::encode_public_key(vk)).collect(),
+        );
+
+        let attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        let mut signed = SignedAttestation::new(attestation.clone());
+        for (i, (sk, _)) in keys.iter().take(2).enumerate() {
+            signed.add_signature(
+                format!("w{}", i + 1),
+                witness_core::sign_attestation(&attestation, sk),
+            );
+        }
+
+        let result = verify_offline(&signed, &config);
+        assert!(result.valid);
+        assert_eq!(result.verified_signatures, 2);
+
+        // Same attestation under a different network ID must not verify
+        let mut other = config.clone();
+        other.id = "other-net".to_string();
+        assert!(!verify_offline(&signed, &other).valid);
+    }
+
+    #[test]
+    fn test_verify_offline_bls() {
+        let keys: Vec<_> = (0..3).map(|_| witness_core::generate_bls_keypair()).collect();
+        let config = test_config(
+            SignatureScheme::BLS,
+            keys.iter().map(|(_, pk)| witness_core::encode_bls_public_key(pk)).collect(),
+        );
+
+        let attestation = Attestation::new([2; 32], "test-net".to_string(), 1);
+        let signatures: Vec<Vec<u8>> = keys
+            .iter()
+            .take(2)
+            .map(|(sk, _)| witness_core::sign_attestation_bls(&attestation, sk))
+            .collect();
+        let aggregated = witness_core::aggregate_signatures_bls(&signatures).unwrap();
+
+        let signed = SignedAttestation::new_with_aggregated(
+            attestation,
+            aggregated,
+            vec!["w1".to_string(), "w2".to_string()],
+        );
+        assert!(verify_offline(&signed, &config).valid);
+
+        // Claiming the wrong signer set must fail
+        let forged = SignedAttestation::new_with_aggregated(
+            signed.attestation.clone(),
+            match &signed.signatures {
+                witness_core::AttestationSignatures::Aggregated { signature, .. } => signature.clone(),
+                _ => unreachable!(),
+            },
+            vec!["w1".to_string(), "w3".to_string()],
+        );
+        assert!(!verify_offline(&forged, &config).valid);
+    }
+}
@@ -3,10 +3,10 @@ mod commands;
 mod freebird_client;
 mod token_wallet;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 
-use commands::{anchors, get, timestamp, token, verify};
+use commands::{anchors, dkg, get, manifest, receipt, timestamp, token, verify};
 
 #[derive(Parser)]
 #[command(name = "witness")]
@@ -23,11 +23,12 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Timestamp a file or hash
+    /// Timestamp files or a hash
     Timestamp {
-        /// File path to timestamp (will compute SHA-256)
+        /// File or directory to timestamp (will compute SHA-256); repeat to
+        /// timestamp several files under one signed merkle root
         #[arg(short, long, conflicts_with = "hash")]
-        file: Option<String>,
+        file: Vec<String>,
 
         /// Hash to timestamp (hex encoded SHA-256)
         #[arg(long, conflicts_with = "file")]
@@ -37,7 +38,7 @@ enum Commands {
         #[arg(short, long, default_value = "text")]
         output: String,
 
-        /// Save attestation to file
+        /// Save attestation to file (to a directory, one file per hash, for several files)
         #[arg(short, long)]
         save: Option<String>,
 
@@ -88,10 +89,27 @@ enum Commands {
         /// Output format: json or text
         #[arg(short, long, default_value = "text")]
         output: String,
+
+        /// Verify locally without contacting the gateway, against --network-config
+        /// or the manifest pinned with `witness manifest fetch`
+        #[arg(long)]
+        offline: bool,
+
+        /// Pinned network configuration JSON (e.g. saved with `witness config --save`)
+        #[arg(long, requires = "offline")]
+        network_config: Option<String>,
+
+        /// Accept legacy v0 attestations made before this Unix time
+        #[arg(long, requires = "offline")]
+        legacy_cutoff: Option<u64>,
     },
 
     /// Show gateway configuration
-    Config {},
+    Config {
+        /// Save configuration to file for offline verification
+        #[arg(short, long)]
+        save: Option<String>,
+    },
 
     /// Show external anchor proofs for an attestation
     Anchors {
@@ -103,6 +121,45 @@ enum Commands {
         output: String,
     },
 
+    /// Fetch or verify self-contained timestamp receipts
+    Receipt {
+        #[command(subcommand)]
+        action: ReceiptAction,
+    },
+
+    /// Create, verify and pin signed network manifests
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestAction,
+    },
+
+    /// Run a distributed key generation ceremony for a threshold BLS witness set
+    Dkg {
+        /// JSON file listing the participating witnesses (id, endpoint, pubkey, signature_scheme)
+        #[arg(long)]
+        participants: String,
+
+        /// Signatures required to produce a group signature
+        #[arg(long)]
+        threshold: usize,
+
+        /// Network ID for the resulting network config
+        #[arg(long)]
+        network_id: String,
+
+        /// Ceremony ID (defaults to <network-id>-<unix time>)
+        #[arg(long)]
+        ceremony_id: Option<String>,
+
+        /// File holding the Ed25519 private key (hex) listed in each witness's dkg_coordinators
+        #[arg(long, env = "WITNESS_DKG_COORDINATOR_KEY")]
+        coordinator_key: String,
+
+        /// Where to write the resulting network config
+        #[arg(short, long, default_value = "network.json")]
+        output: String,
+    },
+
     /// Manage Freebird token wallet
     Token {
         #[command(subcommand)]
@@ -110,6 +167,76 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum ReceiptAction {
+    /// Fetch the receipt for a hash from the gateway
+    Fetch {
+        /// Hash to look up (hex encoded SHA-256)
+        hash: String,
+
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        output: String,
+
+        /// Save receipt to file
+        #[arg(short, long)]
+        save: Option<String>,
+    },
+
+    /// Verify a saved receipt offline
+    Verify {
+        /// Path to receipt JSON file
+        file: String,
+
+        /// Output format: json or text
+        #[arg(short, long, default_value = "text")]
+        output: String,
+
+        /// Pinned network configuration JSON (defaults to keys embedded in the receipt)
+        #[arg(long)]
+        network_config: Option<String>,
+
+        /// Accept legacy v0 attestations made before this Unix time
+        #[arg(long)]
+        legacy_cutoff: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ManifestAction {
+    /// Fetch the gateway's manifest, pinning it on first use and verifying updates
+    Fetch {
+        /// Pin file (defaults to <config dir>/witness/manifests/<network-id>.json)
+        #[arg(long)]
+        pin: Option<String>,
+    },
+
+    /// Create an unsigned manifest from a network configuration
+    Create {
+        /// Network configuration JSON
+        #[arg(long)]
+        network_config: String,
+
+        /// Signed manifest this one succeeds
+        #[arg(long)]
+        previous: Option<String>,
+
+        /// Where to write the manifest
+        #[arg(short, long, default_value = "manifest.json")]
+        output: String,
+    },
+
+    /// Verify a signed manifest file
+    Verify {
+        /// Path to manifest JSON file
+        file: String,
+
+        /// Signed manifest this one should succeed
+        #[arg(long)]
+        previous: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 enum TokenAction {
     /// Fetch tokens from an issuer and store in wallet
@@ -170,17 +297,76 @@ async fn main() -> Result<()> {
         Commands::Get { hash, output } => {
             get::run(&cli.gateway, &hash, &output).await?;
         }
-        Commands::Verify { file, output } => {
-            verify::run(&cli.gateway, &file, &output).await?;
+        Commands::Verify {
+            file,
+            output,
+            offline,
+            network_config,
+            legacy_cutoff,
+        } => {
+            verify::run(&cli.gateway, &file, &output, offline, network_config, legacy_cutoff).await?;
         }
-        Commands::Config {} => {
+        Commands::Config { save } => {
             let client = client::WitnessClient::new(&cli.gateway);
             let config = client.get_config().await?;
-            println!("{}", serde_json::to_string_pretty(&config)?);
+            let json = serde_json::to_string_pretty(&config)?;
+            println!("{}", json);
+
+            if let Some(save_path) = save {
+                std::fs::write(&save_path, json)
+                    .with_context(|| format!("Failed to write configuration to: {}", save_path))?;
+                eprintln!("Configuration saved to: {}", save_path);
+            }
         }
         Commands::Anchors { hash, output } => {
             anchors::run(&cli.gateway, &hash, &output).await?;
         }
+        Commands::Receipt { action } => match action {
+            ReceiptAction::Fetch { hash, output, save } => {
+                receipt::fetch(&cli.gateway, &hash, &output, save).await?;
+            }
+            ReceiptAction::Verify {
+                file,
+                output,
+                network_config,
+                legacy_cutoff,
+            } => {
+                receipt::verify(&file, &output, network_config, legacy_cutoff).await?;
+            }
+        },
+        Commands::Manifest { action } => match action {
+            ManifestAction::Fetch { pin } => {
+                manifest::fetch(&cli.gateway, pin).await?;
+            }
+            ManifestAction::Create {
+                network_config,
+                previous,
+                output,
+            } => {
+                manifest::create(&network_config, previous, &output)?;
+            }
+            ManifestAction::Verify { file, previous } => {
+                manifest::verify(&file, previous)?;
+            }
+        },
+        Commands::Dkg {
+            participants,
+            threshold,
+            network_id,
+            ceremony_id,
+            coordinator_key,
+            output,
+        } => {
+            dkg::run(
+                &participants,
+                threshold,
+                &network_id,
+                ceremony_id,
+                &coordinator_key,
+                &output,
+            )
+            .await?;
+        }
         Commands::Token { action } => match action {
             TokenAction::Fetch { issuer, count } => {
                 token::fetch(&issuer, count).await?;
@@ -174,6 +174,7 @@ mod tests {
             token_b64: "test_token".to_string(),
             issuer_id: "test:issuer:v1".to_string(),
             exp: u64::MAX, // Far future
+            epoch: 0,
         };
 
         wallet.add_token(token.clone());
@@ -6,6 +6,9 @@ use crate::{Attestation, Result, WitnessError};
 
 const DST: &[u8] = b"WITNESS_BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_";
 
+/// Separate tag so a proof of possession can never double as an attestation signature
+const POP_DST: &[u8] = b"WITNESS_BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
 /// Generate a new BLS keypair
 pub fn generate_bls_keypair() -> (SecretKey, PublicKey) {
     let mut ikm = [0u8; 32];
@@ -19,9 +22,7 @@ pub fn generate_bls_keypair() -> (SecretKey, PublicKey) {
 
 /// Sign an attestation using BLS
 pub fn sign_attestation_bls(attestation: &Attestation, secret_key: &SecretKey) -> Vec<u8> {
-    let message = attestation.to_bytes();
-    let signature = secret_key.sign(&message, DST, &[]);
-    signature.to_bytes().to_vec()
+    sign_message_bls(&attestation.to_bytes(), secret_key)
 }
 
 /// Verify a BLS signature on an attestation
@@ -30,14 +31,53 @@ pub fn verify_signature_bls(
     signature_bytes: &[u8],
     public_key: &PublicKey,
 ) -> Result<()> {
-    let message = attestation.to_bytes();
+    verify_message_bls(&attestation.to_bytes(), signature_bytes, public_key)
+}
 
+/// Sign canonical message bytes with BLS (message already domain-separated)
+pub fn sign_message_bls(message: &[u8], secret_key: &SecretKey) -> Vec<u8> {
+    secret_key.sign(message, DST, &[]).to_bytes().to_vec()
+}
+
+/// Verify a BLS signature over canonical message bytes
+pub(crate) fn verify_message_bls(
+    message: &[u8],
+    signature_bytes: &[u8],
+    public_key: &PublicKey,
+) -> Result<()> {
     // Parse signature
     let signature = Signature::from_bytes(signature_bytes)
         .map_err(|_| WitnessError::InvalidSignature)?;
 
     // Verify
-    let result = signature.verify(true, &message, DST, &[], public_key, true);
+    let result = signature.verify(true, message, DST, &[], public_key, true);
+
+    if result == BLST_ERROR::BLST_SUCCESS {
+        Ok(())
+    } else {
+        Err(WitnessError::InvalidSignature)
+    }
+}
+
+/// Prove possession of a BLS secret key by signing its own public key
+///
+/// Same-message aggregation is only safe when every aggregated key comes
+/// with a valid proof; otherwise a rogue key chosen as a function of the
+/// others could forge aggregates.
+pub fn generate_bls_pop(secret_key: &SecretKey) -> Vec<u8> {
+    let public_key = secret_key.sk_to_pk();
+    secret_key
+        .sign(&public_key.to_bytes(), POP_DST, &[])
+        .to_bytes()
+        .to_vec()
+}
+
+/// Verify a proof of possession for a BLS public key
+pub fn verify_bls_pop(public_key: &PublicKey, pop: &[u8]) -> Result<()> {
+    let signature = Signature::from_bytes(pop)
+        .map_err(|_| WitnessError::InvalidSignature)?;
+
+    let result = signature.verify(true, &public_key.to_bytes(), POP_DST, &[], public_key, true);
 
     if result == BLST_ERROR::BLST_SUCCESS {
         Ok(())
@@ -213,6 +253,21 @@ mod tests {
         assert!(verify_aggregated_signature_bls(&attestation, &aggregated, &wrong_keys).is_err());
     }
 
+    #[test]
+    fn test_bls_proof_of_possession() {
+        let (secret_key, public_key) = generate_bls_keypair();
+        let pop = generate_bls_pop(&secret_key);
+        assert!(verify_bls_pop(&public_key, &pop).is_ok());
+
+        // A proof for one key does not cover another
+        let (_, other_key) = generate_bls_keypair();
+        assert!(verify_bls_pop(&other_key, &pop).is_err());
+
+        // An ordinary signature over the key bytes is not a proof
+        let signature = sign_message_bls(&public_key.to_bytes(), &secret_key);
+        assert!(verify_bls_pop(&public_key, &signature).is_err());
+    }
+
     #[test]
     fn test_bls_key_encoding() {
         let (secret_key, public_key) = generate_bls_keypair();
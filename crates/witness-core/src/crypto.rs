@@ -4,7 +4,7 @@ use sha2::{Digest, Sha256};
 
 use crate::{
     signature_scheme::AttestationSignatures, Attestation, NetworkConfig, Result,
-    SignedAttestation, SignatureScheme, WitnessError,
+    SignedAttestation, SignatureScheme, TimeReading, WitnessError,
 };
 
 /// Generate a new Ed25519 keypair
@@ -16,9 +16,7 @@ pub fn generate_keypair() -> (SigningKey, VerifyingKey) {
 
 /// Sign an attestation
 pub fn sign_attestation(attestation: &Attestation, signing_key: &SigningKey) -> Vec<u8> {
-    let message = attestation.to_bytes();
-    let signature = signing_key.sign(&message);
-    signature.to_bytes().to_vec()
+    sign_message(&attestation.to_bytes(), signing_key)
 }
 
 /// Verify a single signature on an attestation
@@ -27,21 +25,98 @@ pub fn verify_signature(
     signature: &[u8],
     verifying_key: &VerifyingKey,
 ) -> Result<()> {
-    let message = attestation.to_bytes();
+    verify_message(&attestation.to_bytes(), signature, verifying_key)
+}
+
+/// Sign canonical message bytes with Ed25519
+///
+/// The message must already carry its domain-separation tag (for example
+/// `Attestation::to_bytes`); this is the hook for external signers.
+pub fn sign_message(message: &[u8], signing_key: &SigningKey) -> Vec<u8> {
+    signing_key.sign(message).to_bytes().to_vec()
+}
 
+/// Verify an Ed25519 signature over canonical message bytes
+pub(crate) fn verify_message(
+    message: &[u8],
+    signature: &[u8],
+    verifying_key: &VerifyingKey,
+) -> Result<()> {
     let sig = Signature::from_slice(signature)
         .map_err(|_| WitnessError::InvalidSignature)?;
 
     verifying_key
-        .verify(&message, &sig)
+        .verify(message, &sig)
         .map_err(|_| WitnessError::InvalidSignature)
 }
 
+/// Sign a witness's clock reading for an attestation with Ed25519
+pub fn sign_time_reading(attestation: &Attestation, time_ms: u64, signing_key: &SigningKey) -> Vec<u8> {
+    sign_message(&TimeReading::message(attestation, time_ms), signing_key)
+}
+
+/// Sign a witness's clock reading for an attestation with BLS
+pub fn sign_time_reading_bls(
+    attestation: &Attestation,
+    time_ms: u64,
+    secret_key: &blst::min_sig::SecretKey,
+) -> Vec<u8> {
+    crate::bls::sign_message_bls(&TimeReading::message(attestation, time_ms), secret_key)
+}
+
+/// Verify a witness's clock reading with the key it held at the attestation time
+pub fn verify_time_reading(
+    reading: &TimeReading,
+    attestation: &Attestation,
+    config: &NetworkConfig,
+) -> Result<()> {
+    let witness = config
+        .find_witness(&reading.witness_id)
+        .ok_or_else(|| WitnessError::WitnessNotFound(reading.witness_id.clone()))?;
+
+    let pubkey = witness.pubkey_at(attestation.timestamp).ok_or_else(|| {
+        WitnessError::InvalidPublicKey(format!(
+            "Witness {} had no valid key at {}",
+            reading.witness_id, attestation.timestamp
+        ))
+    })?;
+
+    let message = TimeReading::message(attestation, reading.time_ms);
+    match config.signature_scheme {
+        SignatureScheme::Ed25519 => {
+            verify_message(&message, &reading.signature, &crate::decode_public_key(pubkey)?)
+        }
+        SignatureScheme::BLS | SignatureScheme::ThresholdBLS => crate::bls::verify_message_bls(
+            &message,
+            &reading.signature,
+            &crate::decode_bls_public_key(pubkey)?,
+        ),
+    }
+}
+
 /// Verify a complete signed attestation against network config
 pub fn verify_signed_attestation(
     signed: &SignedAttestation,
     config: &NetworkConfig,
 ) -> Result<usize> {
+    if !signed.attestation.is_supported_version() {
+        return Err(WitnessError::UnsupportedVersion(signed.attestation.version as u32));
+    }
+
+    // v0 signatures lack domain separation, so they are only accepted up to a cutoff
+    if signed.attestation.is_legacy() && !config.accepts_legacy_at(signed.attestation.timestamp) {
+        return Err(WitnessError::UnsupportedVersion(signed.attestation.version as u32));
+    }
+
+    if !signed.attestation.has_valid_millis() {
+        return Err(WitnessError::InvalidTimestamp);
+    }
+
+    // Clock readings widen or narrow the claimed time interval, so each must be genuine
+    for reading in &signed.time_readings {
+        verify_time_reading(reading, &signed.attestation, config)?;
+    }
+
     match (&signed.signatures, &config.signature_scheme) {
         // Ed25519 multi-sig verification
         (AttestationSignatures::MultiSig { signatures }, SignatureScheme::Ed25519) => {
@@ -62,8 +137,14 @@ pub fn verify_signed_attestation(
                         WitnessError::WitnessNotFound(witness_sig.witness_id.clone())
                     })?;
 
+                // A signature made outside every validity window of the witness's keys
+                // does not count
+                let Some(pubkey) = witness_info.pubkey_at(signed.attestation.timestamp) else {
+                    continue;
+                };
+
                 // Decode public key
-                let pubkey_bytes = hex::decode(&witness_info.pubkey)
+                let pubkey_bytes = hex::decode(pubkey)
                     .map_err(|e| WitnessError::InvalidPublicKey(e.to_string()))?;
 
                 let verifying_key = VerifyingKey::from_bytes(
@@ -115,8 +196,15 @@ pub fn verify_signed_attestation(
                     .find_witness(signer_id)
                     .ok_or_else(|| WitnessError::WitnessNotFound(signer_id.clone()))?;
 
-                let pubkey = crate::decode_bls_public_key(&witness_info.pubkey)?;
-                public_keys.push(pubkey);
+                let pubkey = witness_info
+                    .pubkey_at(signed.attestation.timestamp)
+                    .ok_or_else(|| {
+                        WitnessError::InvalidPublicKey(format!(
+                            "Witness {} had no valid key at {}",
+                            signer_id, signed.attestation.timestamp
+                        ))
+                    })?;
+                public_keys.push(crate::decode_bls_public_key(pubkey)?);
             }
 
             // Verify aggregated signature
@@ -129,6 +217,19 @@ pub fn verify_signed_attestation(
             Ok(signers.len())
         }
 
+        // Threshold BLS group signature verification
+        (AttestationSignatures::Threshold { signature }, SignatureScheme::ThresholdBLS) => {
+            let group_public_key = config.group_public_key.as_deref().ok_or_else(|| {
+                WitnessError::InvalidPublicKey("No group public key configured".to_string())
+            })?;
+            let group_public_key = crate::decode_bls_public_key(group_public_key)?;
+
+            crate::verify_threshold_signature(&signed.attestation, signature, &group_public_key)?;
+
+            // A valid group signature proves at least `threshold` shares signed
+            Ok(config.threshold)
+        }
+
         // Mismatch between signature type and network configuration
         _ => Err(WitnessError::InvalidSignature),
     }
@@ -182,6 +283,160 @@ mod tests {
         assert!(verify_signature(&attestation, &bad_signature, &verifying_key).is_err());
     }
 
+    #[test]
+    fn test_legacy_attestation_still_verifies() {
+        let (signing_key, verifying_key) = generate_keypair();
+        let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        attestation.version = crate::ATTESTATION_VERSION_LEGACY;
+
+        let signature = sign_attestation(&attestation, &signing_key);
+        assert!(verify_signature(&attestation, &signature, &verifying_key).is_ok());
+
+        // The same signature must not verify under the current encoding
+        attestation.version = crate::ATTESTATION_VERSION;
+        assert!(verify_signature(&attestation, &signature, &verifying_key).is_err());
+    }
+
+    #[test]
+    fn test_legacy_attestation_needs_cutoff() {
+        let (signing_key, verifying_key) = generate_keypair();
+        let mut config = NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses: vec![crate::WitnessInfo {
+                id: "w1".to_string(),
+                pubkey: encode_public_key(&verifying_key),
+                endpoint: String::new(),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
+            }],
+            threshold: 1,
+            signature_scheme: SignatureScheme::Ed25519,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+
+        let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        attestation.version = crate::ATTESTATION_VERSION_LEGACY;
+        attestation.timestamp_millis = None;
+        let mut signed = SignedAttestation::new(attestation.clone());
+        signed.add_signature("w1".to_string(), sign_attestation(&attestation, &signing_key));
+
+        // v0 is rejected unless the verifier opts in
+        assert!(matches!(
+            verify_signed_attestation(&signed, &config),
+            Err(WitnessError::UnsupportedVersion(0))
+        ));
+
+        // and then only for attestations made before the cutoff
+        config.legacy_encoding_until = Some(attestation.timestamp + 1);
+        assert_eq!(verify_signed_attestation(&signed, &config).unwrap(), 1);
+
+        config.legacy_encoding_until = Some(attestation.timestamp);
+        assert!(verify_signed_attestation(&signed, &config).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_rotated_key() {
+        let (old_sk, old_vk) = generate_keypair();
+        let (new_sk, new_vk) = generate_keypair();
+        let rotated_at = 1_700_000_000;
+
+        let mut config = NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses: vec![crate::WitnessInfo {
+                id: "w1".to_string(),
+                pubkey: encode_public_key(&new_vk),
+                endpoint: String::new(),
+                share_index: None,
+                pop: None,
+                key_activated_at: Some(rotated_at),
+                previous_keys: vec![crate::RetiredKey {
+                    pubkey: encode_public_key(&old_vk),
+                    activated_at: None,
+                    retired_at: rotated_at,
+                    pop: None,
+                }],
+            }],
+            threshold: 1,
+            signature_scheme: SignatureScheme::Ed25519,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+        assert!(config.validate().is_ok());
+
+        let signed_at = |timestamp: u64, key: &SigningKey| {
+            let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+            attestation.timestamp = timestamp;
+            let mut signed = SignedAttestation::new(attestation.clone());
+            signed.add_signature("w1".to_string(), sign_attestation(&attestation, key));
+            signed
+        };
+
+        // Each key verifies only inside its own validity window
+        assert!(verify_signed_attestation(&signed_at(rotated_at - 10, &old_sk), &config).is_ok());
+        assert!(verify_signed_attestation(&signed_at(rotated_at, &new_sk), &config).is_ok());
+        assert!(verify_signed_attestation(&signed_at(rotated_at + 10, &old_sk), &config).is_err());
+        assert!(verify_signed_attestation(&signed_at(rotated_at - 10, &new_sk), &config).is_err());
+
+        // Overlapping windows are rejected
+        config.witnesses[0].previous_keys[0].retired_at = rotated_at + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_time_readings_bound_attestation_time() {
+        let (signing_key, verifying_key) = generate_keypair();
+        let config = NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses: vec![crate::WitnessInfo {
+                id: "w1".to_string(),
+                pubkey: encode_public_key(&verifying_key),
+                endpoint: String::new(),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
+            }],
+            threshold: 1,
+            signature_scheme: SignatureScheme::Ed25519,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+
+        let attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        let mut signed = SignedAttestation::new(attestation.clone());
+        signed.add_signature("w1".to_string(), sign_attestation(&attestation, &signing_key));
+
+        let time_ms = attestation.timestamp_ms().unwrap() + 40;
+        signed.time_readings.push(TimeReading {
+            witness_id: "w1".to_string(),
+            time_ms,
+            signature: sign_time_reading(&attestation, time_ms, &signing_key),
+        });
+        assert!(verify_signed_attestation(&signed, &config).is_ok());
+        assert_eq!(signed.time_interval(), Some((time_ms, time_ms)));
+
+        // A reading moved after signing no longer verifies
+        signed.time_readings[0].time_ms += 1000;
+        assert!(verify_signed_attestation(&signed, &config).is_err());
+
+        // Milliseconds are covered by the attestation signature
+        signed.time_readings.clear();
+        signed.attestation.timestamp_millis = Some((attestation.timestamp_millis.unwrap() + 1) % 1000);
+        assert!(verify_signed_attestation(&signed, &config).is_err());
+    }
+
     #[test]
     fn test_hash_content() {
         let data = b"hello world";
@@ -0,0 +1,1001 @@
+//! Distributed key generation for threshold BLS witness sets
+//!
+//! Joint-Feldman DKG: every participant deals a random degree `t-1`
+//! polynomial, publishes Feldman commitments to its coefficients, and sends
+//! each peer its share encrypted to the peer's ephemeral transport key. A
+//! coordinator relays messages but never sees a plaintext share. Recipients
+//! sign complaints about dealers whose share does not match the commitments;
+//! the dealer must then reveal that share publicly or be disqualified. A
+//! dealer only reveals a share in answer to a signed complaint from its
+//! recipient. Each node's final secret share is the sum of the shares from
+//! qualified dealers.
+
+use blst::min_sig::{PublicKey as BlsPublicKey, SecretKey as BlsSecretKey};
+use blst::{
+    blst_fr, blst_fr_add, blst_p2, blst_p2_add_or_double, blst_p2_affine, blst_p2_from_affine,
+    blst_p2_mult, blst_p2_to_affine, blst_scalar, blst_scalar_from_fr,
+};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::signature_scheme::SignatureScheme;
+use crate::threshold::{
+    evaluate_polynomial, fr_from_u64, fr_to_secret_key, random_fr, secret_key_to_fr, KeyShare,
+};
+use crate::{Result, WitnessError};
+
+/// Domain-separation tag for signed DKG deals
+pub const DKG_DEAL_DOMAIN_TAG: &[u8] = b"WITNESS_DKG_DEAL";
+
+/// Domain-separation tag for signed DKG complaints
+pub const DKG_COMPLAINT_DOMAIN_TAG: &[u8] = b"WITNESS_DKG_COMPLAINT";
+
+/// Domain-separation tag for share encryption keys
+const DKG_SHARE_DOMAIN_TAG: &[u8] = b"WITNESS_DKG_SHARE";
+
+/// A ceremony participant, identified by its long-term witness key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgParticipantInfo {
+    /// Share index (starting at 1)
+    pub index: u32,
+
+    /// Witness ID
+    pub id: String,
+
+    /// HTTP endpoint of the witness node
+    pub endpoint: String,
+
+    /// Long-term public key that signs this participant's deal (hex encoded)
+    pub pubkey: String,
+
+    /// Scheme of the long-term key
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+}
+
+/// A dealer's public round-one message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgDeal {
+    pub ceremony_id: String,
+
+    /// Index of the dealing participant
+    pub dealer: u32,
+
+    /// Feldman commitments `g2^a_k` to the polynomial coefficients (hex encoded)
+    pub commitments: Vec<String>,
+
+    /// Ephemeral key that shares for this participant are encrypted to (hex encoded)
+    pub transport_key: String,
+
+    /// Signature by the dealer's long-term key over `to_bytes()`
+    #[serde(default)]
+    pub signature: Vec<u8>,
+}
+
+impl DkgDeal {
+    /// Canonical bytes covered by the deal signature
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(DKG_DEAL_DOMAIN_TAG);
+        bytes.extend_from_slice(&(self.ceremony_id.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.ceremony_id.as_bytes());
+        bytes.extend_from_slice(&self.dealer.to_le_bytes());
+        bytes.extend_from_slice(&(self.commitments.len() as u32).to_le_bytes());
+        for commitment in &self.commitments {
+            bytes.extend_from_slice(commitment.as_bytes());
+        }
+        bytes.extend_from_slice(self.transport_key.as_bytes());
+        bytes
+    }
+
+    fn decode_commitments(&self) -> Result<Vec<BlsPublicKey>> {
+        self.commitments
+            .iter()
+            .map(|c| crate::decode_bls_public_key(c))
+            .collect()
+    }
+}
+
+/// Sign a deal with an Ed25519 long-term key
+pub fn sign_dkg_deal(deal: &mut DkgDeal, signing_key: &SigningKey) {
+    deal.signature = crate::crypto::sign_message(&deal.to_bytes(), signing_key);
+}
+
+/// Sign a deal with a BLS long-term key
+pub fn sign_dkg_deal_bls(deal: &mut DkgDeal, secret_key: &BlsSecretKey) {
+    deal.signature = crate::bls::sign_message_bls(&deal.to_bytes(), secret_key);
+}
+
+/// Verify a deal's signature against the dealer's long-term key
+pub fn verify_dkg_deal(deal: &DkgDeal, dealer: &DkgParticipantInfo) -> Result<()> {
+    if deal.dealer != dealer.index {
+        return Err(WitnessError::InvalidProof(format!(
+            "deal from index {} attributed to participant {}",
+            deal.dealer, dealer.index
+        )));
+    }
+
+    verify_participant_signature(&deal.to_bytes(), &deal.signature, dealer)
+}
+
+/// A recipient's signed statement that a dealer's share did not verify
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgComplaint {
+    pub ceremony_id: String,
+
+    /// Index of the complaining recipient
+    pub complainer: u32,
+
+    /// Index of the accused dealer
+    pub dealer: u32,
+
+    /// Signature by the complainer's long-term key over `to_bytes()`
+    #[serde(default)]
+    pub signature: Vec<u8>,
+}
+
+impl DkgComplaint {
+    /// Canonical bytes covered by the complaint signature
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(DKG_COMPLAINT_DOMAIN_TAG);
+        bytes.extend_from_slice(&(self.ceremony_id.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.ceremony_id.as_bytes());
+        bytes.extend_from_slice(&self.complainer.to_le_bytes());
+        bytes.extend_from_slice(&self.dealer.to_le_bytes());
+        bytes
+    }
+}
+
+/// Sign a complaint with an Ed25519 long-term key
+pub fn sign_dkg_complaint(complaint: &mut DkgComplaint, signing_key: &SigningKey) {
+    complaint.signature = crate::crypto::sign_message(&complaint.to_bytes(), signing_key);
+}
+
+/// Verify a complaint's signature against the complainer's long-term key
+pub fn verify_dkg_complaint(complaint: &DkgComplaint, complainer: &DkgParticipantInfo) -> Result<()> {
+    if complaint.complainer != complainer.index {
+        return Err(WitnessError::InvalidProof(format!(
+            "complaint from index {} attributed to participant {}",
+            complaint.complainer, complainer.index
+        )));
+    }
+
+    if complaint.complainer == complaint.dealer {
+        return Err(WitnessError::InvalidProof(format!(
+            "participant {} complained about itself",
+            complaint.complainer
+        )));
+    }
+
+    verify_participant_signature(&complaint.to_bytes(), &complaint.signature, complainer)
+}
+
+fn verify_participant_signature(
+    message: &[u8],
+    signature: &[u8],
+    participant: &DkgParticipantInfo,
+) -> Result<()> {
+    match participant.signature_scheme {
+        SignatureScheme::Ed25519 => {
+            let key: VerifyingKey = crate::decode_public_key(&participant.pubkey)?;
+            crate::crypto::verify_message(message, signature, &key)
+        }
+        SignatureScheme::BLS | SignatureScheme::ThresholdBLS => {
+            let key = crate::decode_bls_public_key(&participant.pubkey)?;
+            crate::bls::verify_message_bls(message, signature, &key)
+        }
+    }
+}
+
+/// A share encrypted from one dealer to one recipient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedShare {
+    pub dealer: u32,
+    pub recipient: u32,
+
+    /// Ephemeral key `g2^r` (hex encoded)
+    pub ephemeral_key: String,
+
+    /// Share XOR H(transport_key^r) (hex encoded)
+    pub ciphertext: String,
+}
+
+/// Start a ceremony on a witness node
+///
+/// The node finds its own index by matching its long-term public key
+/// against `participants`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgStartRequest {
+    pub ceremony_id: String,
+    pub threshold: usize,
+    pub participants: Vec<DkgParticipantInfo>,
+}
+
+/// All participants' signed deals, relayed to each node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgDealsRequest {
+    pub ceremony_id: String,
+    pub deals: Vec<DkgDeal>,
+}
+
+/// Shares a node deals to the other participants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgSharesResponse {
+    pub shares: Vec<EncryptedShare>,
+}
+
+/// Encrypted shares addressed to one node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgReceiveRequest {
+    pub ceremony_id: String,
+    pub shares: Vec<EncryptedShare>,
+}
+
+/// Signed complaints about dealers a node has not received a valid share from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgComplaintsResponse {
+    pub complaints: Vec<DkgComplaint>,
+}
+
+/// Ask a dealer to reveal the share it dealt to a complaining participant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgRevealRequest {
+    pub ceremony_id: String,
+
+    /// The recipient's signed complaint against the dealer
+    pub complaint: DkgComplaint,
+}
+
+/// A share revealed publicly, either by its dealer or forwarded to its recipient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgRevealedShare {
+    pub ceremony_id: String,
+    pub dealer: u32,
+    pub recipient: u32,
+
+    /// Share secret (hex encoded)
+    pub share: String,
+
+    /// The complaint the share was revealed in answer to
+    pub complaint: DkgComplaint,
+}
+
+/// Finish the ceremony with the set of dealers that survived complaints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgFinalizeRequest {
+    pub ceremony_id: String,
+    pub qualified: Vec<u32>,
+
+    /// Unresolved signed complaints against every dealer left out of `qualified`
+    #[serde(default)]
+    pub complaints: Vec<DkgComplaint>,
+
+    /// Every share revealed in answer to a complaint; a dealer whose revealed
+    /// share verifies cannot be left out
+    #[serde(default)]
+    pub reveals: Vec<DkgRevealedShare>,
+}
+
+/// Public outcome of a ceremony; the secret share never leaves the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DkgFinalizeResponse {
+    pub index: u32,
+
+    /// Group public key (hex encoded)
+    pub group_public_key: String,
+
+    /// Share public key of every participant, by index (hex encoded)
+    pub verification_keys: BTreeMap<u32, String>,
+}
+
+/// Result of a completed ceremony for one participant
+pub struct DkgOutput {
+    /// This participant's secret share of the group key
+    pub share: KeyShare,
+
+    /// Group public key
+    pub group_public_key: BlsPublicKey,
+
+    /// Public key of every participant's share, for verifying partial signatures
+    pub verification_keys: BTreeMap<u32, BlsPublicKey>,
+}
+
+impl DkgOutput {
+    pub fn to_response(&self) -> DkgFinalizeResponse {
+        DkgFinalizeResponse {
+            index: self.share.index,
+            group_public_key: crate::encode_bls_public_key(&self.group_public_key),
+            verification_keys: self
+                .verification_keys
+                .iter()
+                .map(|(index, key)| (*index, crate::encode_bls_public_key(key)))
+                .collect(),
+        }
+    }
+}
+
+/// One participant's state during a ceremony
+pub struct DkgSession {
+    ceremony_id: String,
+    index: u32,
+    threshold: usize,
+    participants: Vec<DkgParticipantInfo>,
+    polynomial: Vec<blst_fr>,
+    transport_secret: BlsSecretKey,
+    deals: BTreeMap<u32, DkgDeal>,
+    received: BTreeMap<u32, blst_fr>,
+
+    /// Complaints this participant revealed a share in answer to, by complainer
+    answered: BTreeMap<u32, DkgComplaint>,
+}
+
+impl DkgSession {
+    /// Start a ceremony as participant `index`
+    pub fn new(
+        ceremony_id: String,
+        index: u32,
+        threshold: usize,
+        participants: Vec<DkgParticipantInfo>,
+    ) -> Result<Self> {
+        if threshold == 0 || threshold > participants.len() {
+            return Err(WitnessError::InsufficientSignatures {
+                got: participants.len(),
+                required: threshold,
+            });
+        }
+
+        let mut indices = BTreeSet::new();
+        for participant in &participants {
+            if participant.index == 0 || !indices.insert(participant.index) {
+                return Err(WitnessError::InvalidProof(format!(
+                    "participant {} needs a unique, non-zero index",
+                    participant.id
+                )));
+            }
+        }
+
+        if !indices.contains(&index) {
+            return Err(WitnessError::InvalidProof(format!(
+                "index {} is not a ceremony participant",
+                index
+            )));
+        }
+
+        Ok(Self {
+            ceremony_id,
+            index,
+            threshold,
+            participants,
+            polynomial: (0..threshold).map(|_| random_fr()).collect(),
+            transport_secret: fr_to_secret_key(&random_fr())?,
+            deals: BTreeMap::new(),
+            received: BTreeMap::new(),
+            answered: BTreeMap::new(),
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn participants(&self) -> &[DkgParticipantInfo] {
+        &self.participants
+    }
+
+    pub fn participant(&self, index: u32) -> Option<&DkgParticipantInfo> {
+        self.participants.iter().find(|p| p.index == index)
+    }
+
+    /// This participant's unsigned deal
+    pub fn deal(&self) -> Result<DkgDeal> {
+        let commitments = self
+            .polynomial
+            .iter()
+            .map(|coefficient| {
+                Ok(crate::encode_bls_public_key(&fr_to_secret_key(coefficient)?.sk_to_pk()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DkgDeal {
+            ceremony_id: self.ceremony_id.clone(),
+            dealer: self.index,
+            commitments,
+            transport_key: crate::encode_bls_public_key(&self.transport_secret.sk_to_pk()),
+            signature: Vec::new(),
+        })
+    }
+
+    /// Record every participant's signed deal
+    pub fn receive_deals(&mut self, deals: Vec<DkgDeal>) -> Result<()> {
+        for deal in deals {
+            let dealer = self.participant(deal.dealer).ok_or_else(|| {
+                WitnessError::InvalidProof(format!("deal from unknown index {}", deal.dealer))
+            })?;
+
+            if deal.ceremony_id != self.ceremony_id {
+                return Err(WitnessError::InvalidProof("deal for another ceremony".to_string()));
+            }
+
+            if deal.commitments.len() != self.threshold {
+                return Err(WitnessError::InvalidProof(format!(
+                    "deal from {} has {} commitments, expected {}",
+                    deal.dealer,
+                    deal.commitments.len(),
+                    self.threshold
+                )));
+            }
+
+            verify_dkg_deal(&deal, dealer)?;
+            deal.decode_commitments()?;
+            crate::decode_bls_public_key(&deal.transport_key)?;
+
+            self.deals.insert(deal.dealer, deal);
+        }
+
+        // Our own share is never sent over the wire
+        self.received
+            .insert(self.index, evaluate_polynomial(&self.polynomial, self.index));
+
+        Ok(())
+    }
+
+    /// Encrypt this participant's shares for every other participant
+    pub fn encrypted_shares(&self) -> Result<Vec<EncryptedShare>> {
+        self.participants
+            .iter()
+            .filter(|p| p.index != self.index)
+            .map(|recipient| {
+                let deal = self.deals.get(&recipient.index).ok_or_else(|| {
+                    WitnessError::InvalidProof(format!("no deal from {}", recipient.index))
+                })?;
+                let transport_key = crate::decode_bls_public_key(&deal.transport_key)?;
+                let share = evaluate_polynomial(&self.polynomial, recipient.index);
+
+                encrypt_share(&self.ceremony_id, self.index, recipient.index, &share, &transport_key)
+            })
+            .collect()
+    }
+
+    /// Decrypt and check a share; returns false if it does not match the dealer's commitments
+    pub fn receive_share(&mut self, share: &EncryptedShare) -> Result<bool> {
+        if share.recipient != self.index {
+            return Err(WitnessError::InvalidProof(format!(
+                "share for {} delivered to {}",
+                share.recipient, self.index
+            )));
+        }
+
+        let Some(value) = decrypt_share(&self.ceremony_id, share, &self.transport_secret) else {
+            return Ok(false);
+        };
+
+        self.accept_share(share.dealer, value)
+    }
+
+    /// Accept a share the dealer revealed publicly in answer to a complaint
+    pub fn accept_revealed_share(&mut self, dealer: u32, share: &BlsSecretKey) -> Result<bool> {
+        self.accept_share(dealer, secret_key_to_fr(share))
+    }
+
+    fn accept_share(&mut self, dealer: u32, value: blst_fr) -> Result<bool> {
+        let deal = self.deals.get(&dealer).ok_or_else(|| {
+            WitnessError::InvalidProof(format!("share from {} without a deal", dealer))
+        })?;
+
+        if !share_matches_commitments(&deal.decode_commitments()?, self.index, &value) {
+            return Ok(false);
+        }
+
+        self.received.insert(dealer, value);
+        Ok(true)
+    }
+
+    /// Dealers whose share is missing or did not verify
+    pub fn complaints(&self) -> Vec<u32> {
+        self.deals
+            .keys()
+            .filter(|dealer| !self.received.contains_key(dealer))
+            .copied()
+            .collect()
+    }
+
+    /// This participant's unsigned complaint against `dealer`
+    pub fn complaint(&self, dealer: u32) -> DkgComplaint {
+        DkgComplaint {
+            ceremony_id: self.ceremony_id.clone(),
+            complainer: self.index,
+            dealer,
+            signature: Vec::new(),
+        }
+    }
+
+    /// Complaints this participant has revealed shares in answer to
+    pub fn answered_complaints(&self) -> impl Iterator<Item = &DkgComplaint> {
+        self.answered.values()
+    }
+
+    /// Check a signed complaint against this ceremony's participants
+    fn check_complaint(&self, complaint: &DkgComplaint) -> Result<()> {
+        if complaint.ceremony_id != self.ceremony_id {
+            return Err(WitnessError::InvalidProof("complaint for another ceremony".to_string()));
+        }
+
+        let complainer = self.participant(complaint.complainer).ok_or_else(|| {
+            WitnessError::InvalidProof(format!(
+                "complaint from unknown index {}",
+                complaint.complainer
+            ))
+        })?;
+
+        verify_dkg_complaint(complaint, complainer)
+    }
+
+    /// Reveal the share dealt to a recipient in answer to its signed complaint
+    ///
+    /// Each recipient's share is revealed at most once, and never this
+    /// participant's own.
+    pub fn reveal_share(&mut self, complaint: &DkgComplaint) -> Result<BlsSecretKey> {
+        if complaint.dealer != self.index {
+            return Err(WitnessError::InvalidProof(format!(
+                "complaint against dealer {} sent to {}",
+                complaint.dealer, self.index
+            )));
+        }
+
+        self.check_complaint(complaint)?;
+
+        if self.answered.contains_key(&complaint.complainer) {
+            return Err(WitnessError::InvalidProof(format!(
+                "share for {} was already revealed",
+                complaint.complainer
+            )));
+        }
+
+        let share = fr_to_secret_key(&evaluate_polynomial(&self.polynomial, complaint.complainer))?;
+        self.answered.insert(complaint.complainer, complaint.clone());
+        Ok(share)
+    }
+
+    /// Whether `dealer` answered `complainer`'s complaint with a share that
+    /// matches its commitments, publicly or to this participant
+    fn complaint_answered(
+        &self,
+        dealer: u32,
+        complainer: u32,
+        commitments: &[BlsPublicKey],
+        reveals: &[DkgRevealedShare],
+    ) -> bool {
+        if complainer == self.index && self.received.contains_key(&dealer) {
+            return true;
+        }
+
+        reveals
+            .iter()
+            .filter(|reveal| {
+                reveal.ceremony_id == self.ceremony_id && reveal.dealer == dealer && reveal.recipient == complainer
+            })
+            .filter_map(|reveal| crate::decode_bls_secret_key(&reveal.share).ok())
+            .any(|share| share_matches_commitments(commitments, complainer, &secret_key_to_fr(&share)))
+    }
+
+    /// Combine shares from the qualified dealers into this participant's key share
+    ///
+    /// As in Joint-Feldman, a dealer is only left out of `qualified` for a
+    /// signed complaint in `complaints` that it did not answer with a share
+    /// matching its commitments (in `reveals`). This participant never
+    /// disqualifies itself, so neither a coordinator nor a single complainer
+    /// can drop honest dealers.
+    pub fn finalize(
+        &self,
+        qualified: &[u32],
+        complaints: &[DkgComplaint],
+        reveals: &[DkgRevealedShare],
+    ) -> Result<DkgOutput> {
+        if qualified.len() < self.threshold {
+            return Err(WitnessError::InsufficientSignatures {
+                got: qualified.len(),
+                required: self.threshold,
+            });
+        }
+
+        let qualified_set: BTreeSet<u32> = qualified.iter().copied().collect();
+        if qualified_set.len() != qualified.len() {
+            return Err(WitnessError::InvalidProof("qualified dealers listed twice".to_string()));
+        }
+
+        if !qualified_set.contains(&self.index) {
+            return Err(WitnessError::InvalidProof(format!(
+                "participant {} dealt honestly but is not qualified",
+                self.index
+            )));
+        }
+
+        for (&dealer, deal) in self.deals.iter().filter(|(dealer, _)| !qualified_set.contains(dealer)) {
+            let commitments = deal.decode_commitments()?;
+            let unresolved = complaints.iter().any(|complaint| {
+                complaint.dealer == dealer
+                    && self.check_complaint(complaint).is_ok()
+                    && !self.complaint_answered(dealer, complaint.complainer, &commitments, reveals)
+            });
+            if !unresolved {
+                return Err(WitnessError::InvalidProof(format!(
+                    "dealer {} excluded without an unresolved signed complaint",
+                    dealer
+                )));
+            }
+        }
+
+        let mut secret = blst_fr::default();
+        let mut dealer_commitments = Vec::new();
+
+        for dealer in qualified {
+            let deal = self.deals.get(dealer).ok_or_else(|| {
+                WitnessError::InvalidProof(format!("no deal from qualified dealer {}", dealer))
+            })?;
+            let value = self.received.get(dealer).ok_or_else(|| {
+                WitnessError::InvalidProof(format!("no valid share from qualified dealer {}", dealer))
+            })?;
+
+            unsafe {
+                blst_fr_add(&mut secret, &secret, value);
+            }
+            dealer_commitments.push(deal.decode_commitments()?);
+        }
+
+        let group_public_key = sum_points(dealer_commitments.iter().map(|c| c[0]));
+
+        let verification_keys = self
+            .participants
+            .iter()
+            .map(|p| {
+                let key = sum_points(
+                    dealer_commitments
+                        .iter()
+                        .map(|c| evaluate_commitments(c, p.index)),
+                );
+                (p.index, key)
+            })
+            .collect();
+
+        Ok(DkgOutput {
+            share: KeyShare {
+                index: self.index,
+                secret_key: fr_to_secret_key(&secret)?,
+            },
+            group_public_key,
+            verification_keys,
+        })
+    }
+}
+
+/// Check a publicly revealed share against a dealer's commitments
+pub fn verify_dkg_share(deal: &DkgDeal, recipient: u32, share: &BlsSecretKey) -> Result<bool> {
+    Ok(share_matches_commitments(
+        &deal.decode_commitments()?,
+        recipient,
+        &secret_key_to_fr(share),
+    ))
+}
+
+fn share_matches_commitments(commitments: &[BlsPublicKey], recipient: u32, value: &blst_fr) -> bool {
+    match fr_to_secret_key(value) {
+        Ok(secret_key) => secret_key.sk_to_pk() == evaluate_commitments(commitments, recipient),
+        Err(_) => false,
+    }
+}
+
+/// Evaluate `prod_k C_k^(x^k)`, the public key of `f(x)`
+fn evaluate_commitments(commitments: &[BlsPublicKey], x: u32) -> BlsPublicKey {
+    let x = fr_from_u64(x as u64);
+    let mut result = blst_p2::default();
+
+    for commitment in commitments.iter().rev() {
+        result = p2_mult(&result, &x);
+        result = p2_add(&result, &p2_from_public_key(commitment));
+    }
+
+    p2_to_public_key(&result)
+}
+
+fn sum_points(points: impl Iterator<Item = BlsPublicKey>) -> BlsPublicKey {
+    let sum = points.fold(blst_p2::default(), |acc, point| {
+        p2_add(&acc, &p2_from_public_key(&point))
+    });
+    p2_to_public_key(&sum)
+}
+
+fn encrypt_share(
+    ceremony_id: &str,
+    dealer: u32,
+    recipient: u32,
+    share: &blst_fr,
+    transport_key: &BlsPublicKey,
+) -> Result<EncryptedShare> {
+    let ephemeral = random_fr();
+    let ephemeral_key = fr_to_secret_key(&ephemeral)?.sk_to_pk();
+    let shared = p2_to_public_key(&p2_mult(&p2_from_public_key(transport_key), &ephemeral));
+
+    let pad = share_pad(ceremony_id, dealer, recipient, &shared);
+    let plaintext = fr_to_secret_key(share)?.to_bytes();
+    let ciphertext: Vec<u8> = plaintext.iter().zip(pad.iter()).map(|(a, b)| a ^ b).collect();
+
+    Ok(EncryptedShare {
+        dealer,
+        recipient,
+        ephemeral_key: crate::encode_bls_public_key(&ephemeral_key),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn decrypt_share(ceremony_id: &str, share: &EncryptedShare, transport_secret: &BlsSecretKey) -> Option<blst_fr> {
+    let ephemeral_key = crate::decode_bls_public_key(&share.ephemeral_key).ok()?;
+    let ciphertext = hex::decode(&share.ciphertext).ok()?;
+    if ciphertext.len() != 32 {
+        return None;
+    }
+
+    let shared = p2_to_public_key(&p2_mult(
+        &p2_from_public_key(&ephemeral_key),
+        &secret_key_to_fr(transport_secret),
+    ));
+
+    let pad = share_pad(ceremony_id, share.dealer, share.recipient, &shared);
+    let plaintext: Vec<u8> = ciphertext.iter().zip(pad.iter()).map(|(a, b)| a ^ b).collect();
+
+    BlsSecretKey::from_bytes(&plaintext)
+        .ok()
+        .map(|secret_key| secret_key_to_fr(&secret_key))
+}
+
+fn share_pad(ceremony_id: &str, dealer: u32, recipient: u32, shared: &BlsPublicKey) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DKG_SHARE_DOMAIN_TAG);
+    hasher.update((ceremony_id.len() as u32).to_le_bytes());
+    hasher.update(ceremony_id.as_bytes());
+    hasher.update(dealer.to_le_bytes());
+    hasher.update(recipient.to_le_bytes());
+    hasher.update(shared.to_bytes());
+    hasher.finalize().into()
+}
+
+fn p2_from_public_key(key: &BlsPublicKey) -> blst_p2 {
+    let affine: blst_p2_affine = (*key).into();
+    let mut point = blst_p2::default();
+    unsafe {
+        blst_p2_from_affine(&mut point, &affine);
+    }
+    point
+}
+
+fn p2_to_public_key(point: &blst_p2) -> BlsPublicKey {
+    let mut affine = blst_p2_affine::default();
+    unsafe {
+        blst_p2_to_affine(&mut affine, point);
+    }
+    BlsPublicKey::from(affine)
+}
+
+fn p2_add(a: &blst_p2, b: &blst_p2) -> blst_p2 {
+    let mut out = blst_p2::default();
+    unsafe {
+        blst_p2_add_or_double(&mut out, a, b);
+    }
+    out
+}
+
+fn p2_mult(point: &blst_p2, scalar: &blst_fr) -> blst_p2 {
+    let mut bytes = blst_scalar::default();
+    let mut out = blst_p2::default();
+    unsafe {
+        blst_scalar_from_fr(&mut bytes, scalar);
+        blst_p2_mult(&mut out, point, bytes.b.as_ptr(), 255);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{combine_partial_signatures, generate_keypair, sign_attestation_bls, Attestation};
+
+    struct Node {
+        signing_key: SigningKey,
+        session: DkgSession,
+    }
+
+    fn start(count: u32, threshold: usize) -> Vec<Node> {
+        let keys: Vec<_> = (0..count).map(|_| generate_keypair()).collect();
+        let participants: Vec<DkgParticipantInfo> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, (_, vk))| DkgParticipantInfo {
+                index: i as u32 + 1,
+                id: format!("w{}", i + 1),
+                endpoint: String::new(),
+                pubkey: crate::encode_public_key(vk),
+                signature_scheme: SignatureScheme::Ed25519,
+            })
+            .collect();
+
+        keys.into_iter()
+            .enumerate()
+            .map(|(i, (signing_key, _))| Node {
+                signing_key,
+                session: DkgSession::new(
+                    "ceremony-1".to_string(),
+                    i as u32 + 1,
+                    threshold,
+                    participants.clone(),
+                )
+                .unwrap(),
+            })
+            .collect()
+    }
+
+    fn exchange_deals(nodes: &mut [Node]) -> Vec<DkgDeal> {
+        let deals: Vec<DkgDeal> = nodes
+            .iter()
+            .map(|node| {
+                let mut deal = node.session.deal().unwrap();
+                sign_dkg_deal(&mut deal, &node.signing_key);
+                deal
+            })
+            .collect();
+
+        for node in nodes.iter_mut() {
+            node.session.receive_deals(deals.clone()).unwrap();
+        }
+
+        deals
+    }
+
+    fn deliver(nodes: &mut [Node], shares: Vec<EncryptedShare>) {
+        for share in shares {
+            let recipient = &mut nodes[share.recipient as usize - 1];
+            recipient.session.receive_share(&share).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dkg_produces_working_threshold_keys() {
+        let mut nodes = start(4, 3);
+        exchange_deals(&mut nodes);
+
+        let shares: Vec<_> = nodes
+            .iter()
+            .flat_map(|node| node.session.encrypted_shares().unwrap())
+            .collect();
+        deliver(&mut nodes, shares);
+
+        let qualified = vec![1, 2, 3, 4];
+        let outputs: Vec<DkgOutput> = nodes
+            .iter()
+            .map(|node| {
+                assert!(node.session.complaints().is_empty());
+                node.session.finalize(&qualified, &[], &[]).unwrap()
+            })
+            .collect();
+
+        // Everyone agrees on the group key and verification keys
+        for output in &outputs[1..] {
+            assert_eq!(output.group_public_key, outputs[0].group_public_key);
+            assert_eq!(output.verification_keys, outputs[0].verification_keys);
+        }
+        for output in &outputs {
+            assert_eq!(
+                output.share.public_key(),
+                outputs[0].verification_keys[&output.share.index]
+            );
+        }
+
+        // Any 3 shares produce a signature under the group key
+        let attestation = Attestation::new([4; 32], "test-net".to_string(), 1);
+        let partials: Vec<(u32, Vec<u8>)> = outputs[1..]
+            .iter()
+            .map(|o| (o.share.index, sign_attestation_bls(&attestation, &o.share.secret_key)))
+            .collect();
+        let signature = combine_partial_signatures(&partials, 3).unwrap();
+        assert!(crate::verify_threshold_signature(&attestation, &signature, &outputs[0].group_public_key).is_ok());
+    }
+
+    #[test]
+    fn test_dkg_complaint_resolution() {
+        let mut nodes = start(3, 2);
+        let deals = exchange_deals(&mut nodes);
+
+        let mut shares: Vec<_> = nodes
+            .iter()
+            .flat_map(|node| node.session.encrypted_shares().unwrap())
+            .collect();
+
+        // Dealer 1 sends garbage to participant 2
+        for share in shares.iter_mut() {
+            if share.dealer == 1 && share.recipient == 2 {
+                share.ciphertext = hex::encode([7u8; 32]);
+            }
+        }
+        deliver(&mut nodes, shares);
+
+        assert_eq!(nodes[1].session.complaints(), vec![1]);
+        let mut complaint = nodes[1].session.complaint(1);
+        sign_dkg_complaint(&mut complaint, &nodes[1].signing_key);
+
+        // While dealer 1 has not answered, excluding it needs a signed complaint
+        assert!(nodes[1].session.finalize(&[2, 3], &[], &[]).is_err());
+        let mut forged = nodes[0].session.complaint(1);
+        forged.complainer = 3;
+        sign_dkg_complaint(&mut forged, &nodes[1].signing_key);
+        assert!(nodes[1].session.finalize(&[2, 3], &[forged.clone()], &[]).is_err());
+
+        // and yields consistent keys
+        let a = nodes[1].session.finalize(&[2, 3], &[complaint.clone()], &[]).unwrap();
+        let b = nodes[2].session.finalize(&[2, 3], &[complaint.clone()], &[]).unwrap();
+        assert_eq!(a.group_public_key, b.group_public_key);
+
+        // Dealer 1 reveals the share publicly; it checks out against its commitments
+        let share = nodes[0].session.reveal_share(&complaint).unwrap();
+        assert!(verify_dkg_share(&deals[0], 2, &share).unwrap());
+        let revealed = DkgRevealedShare {
+            ceremony_id: "ceremony-1".to_string(),
+            dealer: 1,
+            recipient: 2,
+            share: crate::encode_bls_secret_key(&share),
+            complaint: complaint.clone(),
+        };
+        assert_eq!(nodes[0].session.answered_complaints().collect::<Vec<_>>(), vec![&complaint]);
+
+        // An answered complaint no longer disqualifies the dealer
+        assert!(nodes[2]
+            .session
+            .finalize(&[2, 3], &[complaint.clone()], std::slice::from_ref(&revealed))
+            .is_err());
+
+        // The complainer accepts the share, and knows the dealer answered even without the reveal
+        assert!(nodes[1].session.accept_revealed_share(1, &share).unwrap());
+        assert!(nodes[1].session.complaints().is_empty());
+        assert!(nodes[1].session.finalize(&[2, 3], &[complaint.clone()], &[]).is_err());
+
+        // Each share is revealed once, and only for a complaint its recipient signed
+        assert!(nodes[0].session.reveal_share(&complaint).is_err());
+        assert!(nodes[0].session.reveal_share(&forged).is_err());
+
+        // Never for the dealer's own index, nor for a complaint against another dealer
+        let mut own = nodes[0].session.complaint(1);
+        sign_dkg_complaint(&mut own, &nodes[0].signing_key);
+        assert!(nodes[0].session.reveal_share(&own).is_err());
+        assert!(nodes[1].session.reveal_share(&complaint).is_err());
+
+        // A wrong revealed share does not answer the complaint
+        let mut other = nodes[2].session.complaint(1);
+        sign_dkg_complaint(&mut other, &nodes[2].signing_key);
+        let wrong = nodes[0].session.reveal_share(&other).unwrap();
+        assert!(!verify_dkg_share(&deals[0], 2, &wrong).unwrap());
+        let wrong_reveal = DkgRevealedShare {
+            share: crate::encode_bls_secret_key(&wrong),
+            ..revealed
+        };
+        assert!(nodes[2]
+            .session
+            .finalize(&[2, 3], &[complaint.clone()], &[wrong_reveal])
+            .is_ok());
+
+        // Nobody drops itself, lists a dealer twice or qualifies an unknown dealer
+        assert!(nodes[0].session.finalize(&[2, 3], &[complaint.clone()], &[]).is_err());
+        assert!(nodes[2].session.finalize(&[2, 3, 3], &[complaint.clone()], &[]).is_err());
+        assert!(nodes[2].session.finalize(&[2, 3, 9], &[complaint], &[]).is_err());
+    }
+
+    #[test]
+    fn test_dkg_rejects_forged_deal() {
+        let mut nodes = start(3, 2);
+
+        let mut deal = nodes[0].session.deal().unwrap();
+        // Signed by the wrong long-term key
+        sign_dkg_deal(&mut deal, &nodes[1].signing_key);
+
+        assert!(nodes[2].session.receive_deals(vec![deal]).is_err());
+    }
+}
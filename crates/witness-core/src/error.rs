@@ -28,6 +28,12 @@ pub enum WitnessError {
 
     #[error("Duplicate attestation")]
     DuplicateAttestation,
+
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
+
+    #[error("Unsupported format version: {0}")]
+    UnsupportedVersion(u32),
 }
 
 pub type Result<T> = std::result::Result<T, WitnessError>;
@@ -1,10 +1,27 @@
+use blst::min_sig::{PublicKey as BlsPublicKey, SecretKey as BlsSecretKey};
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
+use crate::external_anchors::ExternalAnchorProof;
 use crate::merkle::MerkleProof;
-use crate::WitnessSignature;
+use crate::signature_scheme::SignatureScheme;
+use crate::{Result, WitnessError, WitnessInfo, WitnessSignature};
+
+/// Domain-separation tag for cross-anchor statements
+pub const CROSS_ANCHOR_DOMAIN_TAG: &[u8] = b"WITNESS_CROSS_ANCHOR";
+
+/// Current cross-anchor statement encoding version
+pub const CROSS_ANCHOR_VERSION: u8 = 1;
+
+/// Cross-anchors made before statements existed
+///
+/// Their witnesses signed a plain attestation over the batch root under a
+/// gateway sequence number that was never recorded, so the signatures
+/// cannot be checked.
+pub const CROSS_ANCHOR_VERSION_LEGACY: u8 = 0;
 
 /// A batch of attestations with their merkle root
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AttestationBatch {
     /// Unique batch ID
     pub id: u64,
@@ -25,22 +42,123 @@ pub struct AttestationBatch {
     pub attestation_count: u64,
 }
 
-/// Cross-anchor attestation from a peer network
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CrossAnchor {
+/// Statement signed by a peer network's witnesses when cross-anchoring a batch
+///
+/// Commits to the whole batch (origin network, batch id, merkle root, period
+/// and count) and to the witnessing network, under its own domain tag so it
+/// can never be confused with a user attestation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CrossAnchorStatement {
+    /// Statement encoding version
+    #[serde(default = "default_cross_anchor_version")]
+    pub version: u8,
+
     /// The batch being witnessed
     pub batch: AttestationBatch,
 
     /// ID of the network that witnessed this batch
     pub witnessing_network: String,
 
-    /// Signatures from the witnessing network's witnesses
-    pub signatures: Vec<WitnessSignature>,
-
     /// When this cross-anchor was created
     pub timestamp: u64,
 }
 
+// Cross-anchors serialized without a version predate statements
+fn default_cross_anchor_version() -> u8 {
+    CROSS_ANCHOR_VERSION_LEGACY
+}
+
+impl CrossAnchorStatement {
+    pub fn new(batch: AttestationBatch, witnessing_network: String) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            version: CROSS_ANCHOR_VERSION,
+            batch,
+            witnessing_network,
+            timestamp,
+        }
+    }
+
+    /// Whether this was signed as a plain attestation, before statements existed
+    pub fn is_legacy(&self) -> bool {
+        self.version == CROSS_ANCHOR_VERSION_LEGACY
+    }
+
+    /// Get canonical bytes for signing
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(CROSS_ANCHOR_DOMAIN_TAG);
+        bytes.push(self.version);
+        put_str(&mut bytes, &self.witnessing_network);
+        put_str(&mut bytes, &self.batch.network_id);
+        bytes.extend_from_slice(&self.batch.id.to_le_bytes());
+        bytes.extend_from_slice(&self.batch.merkle_root);
+        bytes.extend_from_slice(&self.batch.period_start.to_le_bytes());
+        bytes.extend_from_slice(&self.batch.period_end.to_le_bytes());
+        bytes.extend_from_slice(&self.batch.attestation_count.to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes
+    }
+}
+
+/// Append a u32 length-prefixed string
+pub(crate) fn put_str(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+/// Sign a cross-anchor statement with Ed25519
+pub fn sign_cross_anchor(statement: &CrossAnchorStatement, signing_key: &SigningKey) -> Vec<u8> {
+    crate::crypto::sign_message(&statement.to_bytes(), signing_key)
+}
+
+/// Verify an Ed25519 signature on a cross-anchor statement
+pub fn verify_cross_anchor_signature(
+    statement: &CrossAnchorStatement,
+    signature: &[u8],
+    verifying_key: &VerifyingKey,
+) -> Result<()> {
+    check_cross_anchor_version(statement)?;
+    crate::crypto::verify_message(&statement.to_bytes(), signature, verifying_key)
+}
+
+/// Sign a cross-anchor statement with BLS
+pub fn sign_cross_anchor_bls(statement: &CrossAnchorStatement, secret_key: &BlsSecretKey) -> Vec<u8> {
+    crate::bls::sign_message_bls(&statement.to_bytes(), secret_key)
+}
+
+/// Verify a BLS signature on a cross-anchor statement
+pub fn verify_cross_anchor_signature_bls(
+    statement: &CrossAnchorStatement,
+    signature: &[u8],
+    public_key: &BlsPublicKey,
+) -> Result<()> {
+    check_cross_anchor_version(statement)?;
+    crate::bls::verify_message_bls(&statement.to_bytes(), signature, public_key)
+}
+
+fn check_cross_anchor_version(statement: &CrossAnchorStatement) -> Result<()> {
+    if statement.version != CROSS_ANCHOR_VERSION {
+        return Err(WitnessError::UnsupportedVersion(statement.version as u32));
+    }
+    Ok(())
+}
+
+/// Cross-anchor attestation from a peer network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossAnchor {
+    /// The statement signed by the witnessing network
+    #[serde(flatten)]
+    pub statement: CrossAnchorStatement,
+
+    /// Signatures from the witnessing network's witnesses
+    pub signatures: Vec<WitnessSignature>,
+}
+
 /// Configuration for federation
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FederationConfig {
@@ -59,12 +177,35 @@ pub struct FederationConfig {
     /// Minimum number of peer networks that must cross-anchor
     #[serde(default)]
     pub cross_anchor_threshold: usize,
+
+    /// How often to retry batches below the cross-anchor threshold (seconds, 0 disables)
+    #[serde(default = "default_cross_anchor_retry_interval")]
+    pub cross_anchor_retry_interval: u64,
+
+    /// How far back to retry cross-anchoring (seconds)
+    #[serde(default = "default_cross_anchor_retry_window")]
+    pub cross_anchor_retry_window: u64,
+}
+
+impl FederationConfig {
+    /// Distinct peer networks that must cross-anchor a batch
+    pub fn required_cross_anchors(&self) -> usize {
+        self.cross_anchor_threshold.max(1)
+    }
 }
 
 fn default_batch_period() -> u64 {
     3600 // 1 hour
 }
 
+fn default_cross_anchor_retry_interval() -> u64 {
+    300 // 5 minutes
+}
+
+fn default_cross_anchor_retry_window() -> u64 {
+    86400 // 24 hours
+}
+
 /// Information about a peer network
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerNetworkInfo {
@@ -77,18 +218,126 @@ pub struct PeerNetworkInfo {
     /// Minimum number of witnesses required from this peer
     #[serde(default = "default_min_witnesses")]
     pub min_witnesses: usize,
+
+    /// Peer witness public keys, pinned here rather than trusted from the peer gateway
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub witnesses: Vec<WitnessInfo>,
+
+    /// Peer signature scheme (ed25519 or bls)
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+
+    /// Peer network's own signature threshold (0 if unknown)
+    #[serde(default)]
+    pub threshold: usize,
 }
 
 fn default_min_witnesses() -> usize {
     2
 }
 
+impl PeerNetworkInfo {
+    /// Whether witness keys are known for this peer
+    pub fn has_keys(&self) -> bool {
+        !self.witnesses.is_empty()
+    }
+
+    /// Signatures required on a cross-anchor from this peer
+    pub fn required_signatures(&self) -> usize {
+        self.min_witnesses.max(self.threshold).max(1)
+    }
+
+    pub fn find_witness(&self, id: &str) -> Option<&WitnessInfo> {
+        self.witnesses.iter().find(|w| w.id == id)
+    }
+}
+
+/// Verify a cross-anchor's signatures against the peer network's witness keys
+///
+/// Returns the number of distinct peer witnesses with a valid signature, and
+/// fails if that is below the peer's `required_signatures()`.
+pub fn verify_cross_anchor(cross_anchor: &CrossAnchor, peer: &PeerNetworkInfo) -> Result<usize> {
+    let statement = &cross_anchor.statement;
+
+    if statement.witnessing_network != peer.id {
+        return Err(WitnessError::InvalidProof(format!(
+            "cross-anchor is from network '{}', expected '{}'",
+            statement.witnessing_network, peer.id
+        )));
+    }
+
+    if !peer.has_keys() {
+        return Err(WitnessError::InvalidPublicKey(format!(
+            "No witness keys known for peer network '{}'",
+            peer.id
+        )));
+    }
+
+    if statement.is_legacy() {
+        return Err(WitnessError::InvalidProof(format!(
+            "legacy cross-anchor from '{}' has no verifiable signatures",
+            statement.witnessing_network
+        )));
+    }
+
+    check_cross_anchor_version(statement)?;
+
+    let mut verified: Vec<&str> = Vec::new();
+
+    for witness_sig in &cross_anchor.signatures {
+        if verified.contains(&witness_sig.witness_id.as_str()) {
+            continue;
+        }
+
+        // Signatures from unknown witnesses are ignored rather than trusted
+        let Some(witness_info) = peer.find_witness(&witness_sig.witness_id) else {
+            continue;
+        };
+
+        let Some(pubkey) = witness_info.pubkey_at(statement.timestamp) else {
+            continue;
+        };
+
+        let valid = match peer.signature_scheme {
+            SignatureScheme::Ed25519 => {
+                let key = crate::decode_public_key(pubkey)?;
+                verify_cross_anchor_signature(statement, &witness_sig.signature, &key).is_ok()
+            }
+            // Threshold witnesses sign cross-anchors individually with their share keys
+            SignatureScheme::BLS | SignatureScheme::ThresholdBLS => {
+                let key = crate::decode_bls_public_key(pubkey)?;
+                verify_cross_anchor_signature_bls(statement, &witness_sig.signature, &key).is_ok()
+            }
+        };
+
+        if valid {
+            verified.push(&witness_sig.witness_id);
+        }
+    }
+
+    let required = peer.required_signatures();
+    if verified.len() < required {
+        return Err(WitnessError::InsufficientSignatures {
+            got: verified.len(),
+            required,
+        });
+    }
+
+    Ok(verified.len())
+}
+
 /// Request to cross-anchor a batch (sent to peer network)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossAnchorRequest {
     pub batch: AttestationBatch,
 }
 
+/// Internal request from gateway to witness for signing a cross-anchor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossAnchorSignRequest {
+    pub statement: CrossAnchorStatement,
+}
+
 /// Response from peer network after cross-anchoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossAnchorResponse {
@@ -106,13 +355,21 @@ pub struct FederatedAttestation {
 
     /// Cross-anchors from peer networks (if available)
     pub cross_anchors: Vec<CrossAnchor>,
+
+    /// Batch containing the attestation (if batched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<AttestationBatch>,
+
+    /// External anchor proofs for the batch (if available)
+    #[serde(default)]
+    pub external_anchors: Vec<ExternalAnchorProof>,
 }
 
 /// Request to get federated verification info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FederatedVerifyRequest {
-    /// Hash to verify
-    pub hash: [u8; 32],
+    /// SHA-256 hash to verify (hex encoded)
+    pub hash: String,
 }
 
 /// Response with federated verification info
@@ -138,6 +395,9 @@ pub enum VerificationLevel {
 
     /// Federated: Cross-anchored by peer networks (Phase 2)
     Federated { peer_count: usize },
+
+    /// Externally anchored: Batch recorded in external services (Phase 3)
+    ExternallyAnchored { peer_count: usize, anchor_count: usize },
 }
 
 impl std::fmt::Display for VerificationLevel {
@@ -149,6 +409,146 @@ impl std::fmt::Display for VerificationLevel {
             VerificationLevel::Federated { peer_count } => {
                 write!(f, "Federated ({} peer networks)", peer_count)
             }
+            VerificationLevel::ExternallyAnchored { peer_count, anchor_count } => {
+                write!(
+                    f,
+                    "Externally anchored ({} external anchors, {} peer networks)",
+                    anchor_count, peer_count
+                )
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_bls_keypair, generate_keypair, sign_attestation, Attestation};
+
+    fn test_batch() -> AttestationBatch {
+        AttestationBatch {
+            id: 3,
+            network_id: "origin-net".to_string(),
+            merkle_root: [7u8; 32],
+            period_start: 1700000000,
+            period_end: 1700003600,
+            attestation_count: 10,
+        }
+    }
+
+    #[test]
+    fn test_cross_anchor_sign_and_verify() {
+        let (signing_key, verifying_key) = generate_keypair();
+        let statement = CrossAnchorStatement::new(test_batch(), "peer-net".to_string());
+
+        let signature = sign_cross_anchor(&statement, &signing_key);
+        assert!(verify_cross_anchor_signature(&statement, &signature, &verifying_key).is_ok());
+
+        // Signature commits to the batch id
+        let mut other = statement.clone();
+        other.batch.id = 4;
+        assert!(verify_cross_anchor_signature(&other, &signature, &verifying_key).is_err());
+    }
+
+    #[test]
+    fn test_cross_anchor_sign_and_verify_bls() {
+        let (secret_key, public_key) = generate_bls_keypair();
+        let statement = CrossAnchorStatement::new(test_batch(), "peer-net".to_string());
+
+        let signature = sign_cross_anchor_bls(&statement, &secret_key);
+        assert!(verify_cross_anchor_signature_bls(&statement, &signature, &public_key).is_ok());
+
+        let mut other = statement.clone();
+        other.witnessing_network = "other-net".to_string();
+        assert!(verify_cross_anchor_signature_bls(&other, &signature, &public_key).is_err());
+    }
+
+    #[test]
+    fn test_attestation_signature_not_valid_for_cross_anchor() {
+        let (signing_key, verifying_key) = generate_keypair();
+        let batch = test_batch();
+        let statement = CrossAnchorStatement::new(batch.clone(), "peer-net".to_string());
+
+        let attestation = Attestation::new(batch.merkle_root, "peer-net".to_string(), 1);
+        let signature = sign_attestation(&attestation, &signing_key);
+
+        assert!(verify_cross_anchor_signature(&statement, &signature, &verifying_key).is_err());
+    }
+
+    fn peer_with_keys(keys: &[(SigningKey, VerifyingKey)], min_witnesses: usize) -> PeerNetworkInfo {
+        PeerNetworkInfo {
+            id: "peer-net".to_string(),
+            gateway: "http://localhost:9002".to_string(),
+            min_witnesses,
+            witnesses: keys
+                .iter()
+                .enumerate()
+                .map(|(i, (_, vk))| WitnessInfo {
+                    id: format!("p{}", i + 1),
+                    pubkey: crate::encode_public_key(vk),
+                    endpoint: String::new(),
+                    share_index: None,
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+                .collect(),
+            signature_scheme: SignatureScheme::Ed25519,
+            threshold: 2,
+        }
+    }
+
+    #[test]
+    fn test_verify_cross_anchor() {
+        let keys: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let peer = peer_with_keys(&keys, 2);
+        let statement = CrossAnchorStatement::new(test_batch(), "peer-net".to_string());
+
+        let sign = |i: usize| WitnessSignature {
+            witness_id: format!("p{}", i + 1),
+            signature: sign_cross_anchor(&statement, &keys[i].0),
+        };
+
+        let mut cross_anchor = CrossAnchor {
+            statement: statement.clone(),
+            signatures: vec![sign(0), sign(1)],
+        };
+        assert_eq!(verify_cross_anchor(&cross_anchor, &peer).unwrap(), 2);
+
+        // Duplicate signatures from one witness do not count twice
+        cross_anchor.signatures = vec![sign(0), sign(0)];
+        assert!(verify_cross_anchor(&cross_anchor, &peer).is_err());
+
+        // min_witnesses above the peer threshold is enforced
+        cross_anchor.signatures = vec![sign(0), sign(1)];
+        let strict = peer_with_keys(&keys, 3);
+        assert!(verify_cross_anchor(&cross_anchor, &strict).is_err());
+
+        // Wrong witnessing network is rejected
+        let mut other = peer.clone();
+        other.id = "other-net".to_string();
+        assert!(verify_cross_anchor(&cross_anchor, &other).is_err());
+    }
+
+    #[test]
+    fn test_cross_anchor_json_shape() {
+        let cross_anchor = CrossAnchor {
+            statement: CrossAnchorStatement::new(test_batch(), "peer-net".to_string()),
+            signatures: vec![],
+        };
+
+        let json = serde_json::to_value(&cross_anchor).unwrap();
+        assert!(json.get("batch").is_some());
+        assert_eq!(json["witnessing_network"], "peer-net");
+
+        let back: CrossAnchor = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(back.statement, cross_anchor.statement);
+
+        // Cross-anchors from before statements carry no version
+        let mut legacy = json;
+        legacy.as_object_mut().unwrap().remove("version");
+        let legacy: CrossAnchor = serde_json::from_value(legacy).unwrap();
+        assert!(legacy.statement.is_legacy());
+        assert!(verify_cross_anchor(&legacy, &peer_with_keys(&[generate_keypair()], 1)).is_err());
+    }
+}
@@ -6,6 +6,11 @@ pub mod federation;
 pub mod bls;
 pub mod signature_scheme;
 pub mod external_anchors;
+pub mod receipt;
+pub mod threshold;
+pub mod dkg;
+pub mod manifest;
+pub mod auth;
 
 pub use types::*;
 pub use crypto::*;
@@ -15,3 +20,8 @@ pub use federation::*;
 pub use bls::*;
 pub use signature_scheme::*;
 pub use external_anchors::*;
+pub use receipt::*;
+pub use threshold::*;
+pub use dkg::*;
+pub use manifest::*;
+pub use auth::*;
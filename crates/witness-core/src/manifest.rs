@@ -0,0 +1,396 @@
+//! Signed, versioned network manifests
+//!
+//! A manifest pins a network's witness set (keys, threshold, scheme) and is
+//! co-signed by a quorum of witnesses, so clients do not have to trust the
+//! gateway's `/v1/config`. Each manifest names its predecessor by hash, and an
+//! update is only valid if a quorum of the *previous* witness set signed it.
+
+use blst::min_sig::SecretKey as BlsSecretKey;
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::federation::{put_str, PeerNetworkInfo};
+use crate::receipt::NetworkSnapshot;
+use crate::signature_scheme::SignatureScheme;
+use crate::{NetworkConfig, Result, WitnessError, WitnessInfo, WitnessSignature};
+
+/// Domain-separation tag for manifest signatures
+///
+/// V1 signed the manifest's JSON serialization; V2 is an explicit
+/// length-prefixed encoding that does not change when struct fields do.
+pub const MANIFEST_DOMAIN_TAG: &[u8] = b"WITNESS_MANIFEST_V2";
+
+/// A version of a network's witness set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkManifest {
+    /// Monotonic version counter, starting at 1
+    pub version: u64,
+
+    /// Hash of the previous manifest (hex encoded, absent for version 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_hash: Option<String>,
+
+    /// Unix timestamp the manifest was issued; selects witness keys for signing
+    pub issued_at: u64,
+
+    /// Witness keys and threshold
+    pub network: NetworkSnapshot,
+}
+
+impl NetworkManifest {
+    /// Create the first manifest, or the successor of `previous`
+    pub fn new(network: NetworkSnapshot, previous: Option<&NetworkManifest>) -> Self {
+        let issued_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            version: previous.map_or(1, |p| p.version + 1),
+            previous_hash: previous.map(|p| hex::encode(p.hash())),
+            issued_at,
+            network,
+        }
+    }
+
+    /// Canonical bytes covered by witness signatures
+    ///
+    /// Every field is encoded explicitly, so a field added to these structs
+    /// later is not signed until it is added here.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let network = &self.network;
+
+        let mut bytes = MANIFEST_DOMAIN_TAG.to_vec();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        put_opt_str(&mut bytes, self.previous_hash.as_deref());
+        bytes.extend_from_slice(&self.issued_at.to_le_bytes());
+
+        put_str(&mut bytes, &network.id);
+        put_str(&mut bytes, &network.signature_scheme.to_string());
+        bytes.extend_from_slice(&(network.threshold as u64).to_le_bytes());
+        put_witnesses(&mut bytes, &network.witnesses);
+        put_opt_str(&mut bytes, network.group_public_key.as_deref());
+
+        bytes.extend_from_slice(&(network.peer_networks.len() as u32).to_le_bytes());
+        for peer in &network.peer_networks {
+            put_peer(&mut bytes, peer);
+        }
+        bytes.extend_from_slice(&(network.cross_anchor_threshold as u64).to_le_bytes());
+        bytes
+    }
+
+    /// Hash identifying this manifest in its successor's `previous_hash`
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
+}
+
+fn put_opt_str(bytes: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            bytes.push(1);
+            put_str(bytes, s);
+        }
+        None => bytes.push(0),
+    }
+}
+
+fn put_opt_u64(bytes: &mut Vec<u8>, n: Option<u64>) {
+    match n {
+        Some(n) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+        None => bytes.push(0),
+    }
+}
+
+fn put_witnesses(bytes: &mut Vec<u8>, witnesses: &[WitnessInfo]) {
+    bytes.extend_from_slice(&(witnesses.len() as u32).to_le_bytes());
+    for witness in witnesses {
+        put_str(bytes, &witness.id);
+        put_str(bytes, &witness.pubkey);
+        put_str(bytes, &witness.endpoint);
+        put_opt_u64(bytes, witness.share_index.map(u64::from));
+        put_opt_str(bytes, witness.pop.as_deref());
+        put_opt_u64(bytes, witness.key_activated_at);
+
+        bytes.extend_from_slice(&(witness.previous_keys.len() as u32).to_le_bytes());
+        for key in &witness.previous_keys {
+            put_str(bytes, &key.pubkey);
+            put_opt_u64(bytes, key.activated_at);
+            bytes.extend_from_slice(&key.retired_at.to_le_bytes());
+            put_opt_str(bytes, key.pop.as_deref());
+        }
+    }
+}
+
+fn put_peer(bytes: &mut Vec<u8>, peer: &PeerNetworkInfo) {
+    put_str(bytes, &peer.id);
+    put_str(bytes, &peer.gateway);
+    bytes.extend_from_slice(&(peer.min_witnesses as u64).to_le_bytes());
+    put_witnesses(bytes, &peer.witnesses);
+    put_str(bytes, &peer.signature_scheme.to_string());
+    bytes.extend_from_slice(&(peer.threshold as u64).to_le_bytes());
+}
+
+/// A manifest with witness co-signatures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub manifest: NetworkManifest,
+    pub signatures: Vec<WitnessSignature>,
+}
+
+impl SignedManifest {
+    pub fn new(manifest: NetworkManifest) -> Self {
+        Self {
+            manifest,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Add or replace a witness's signature
+    pub fn add_signature(&mut self, witness_id: String, signature: Vec<u8>) {
+        self.signatures.retain(|s| s.witness_id != witness_id);
+        self.signatures.push(WitnessSignature {
+            witness_id,
+            signature,
+        });
+    }
+
+    /// Network configuration for verifying attestations under this manifest
+    pub fn to_network_config(&self) -> NetworkConfig {
+        self.manifest.network.to_network_config()
+    }
+
+    /// Verify that a quorum of the manifest's own witnesses signed it
+    ///
+    /// This is all a client can check for the first manifest it sees; later
+    /// manifests must be checked with `verify_update`.
+    pub fn verify_self_signed(&self) -> Result<usize> {
+        self.to_network_config().validate()?;
+        verify_manifest_signatures(self, &self.manifest.network)
+    }
+
+    /// Verify that `next` is a valid successor of this manifest
+    pub fn verify_update(&self, next: &SignedManifest) -> Result<usize> {
+        let current = &self.manifest;
+        let candidate = &next.manifest;
+
+        if candidate.network.id != current.network.id {
+            return Err(WitnessError::InvalidProof(format!(
+                "manifest is for network '{}', expected '{}'",
+                candidate.network.id, current.network.id
+            )));
+        }
+
+        if candidate.version != current.version + 1 {
+            return Err(WitnessError::InvalidProof(format!(
+                "manifest version {} does not follow {}",
+                candidate.version, current.version
+            )));
+        }
+
+        if candidate.previous_hash.as_deref() != Some(hex::encode(current.hash()).as_str()) {
+            return Err(WitnessError::InvalidProof(
+                "manifest does not chain to the previous manifest".to_string(),
+            ));
+        }
+
+        // The outgoing witness set authorizes the change; the incoming one must agree
+        verify_manifest_signatures(next, &current.network)?;
+        next.verify_self_signed()
+    }
+}
+
+/// Verify a chain of manifests, oldest first; the first is trusted if self-signed
+pub fn verify_manifest_chain(chain: &[SignedManifest]) -> Result<()> {
+    let first = chain.first().ok_or_else(|| {
+        WitnessError::InvalidProof("empty manifest chain".to_string())
+    })?;
+    first.verify_self_signed()?;
+
+    for pair in chain.windows(2) {
+        pair[0].verify_update(&pair[1])?;
+    }
+
+    Ok(())
+}
+
+/// Sign a manifest with Ed25519
+pub fn sign_manifest(manifest: &NetworkManifest, signing_key: &SigningKey) -> Vec<u8> {
+    crate::crypto::sign_message(&manifest.to_bytes(), signing_key)
+}
+
+/// Sign a manifest with BLS (including threshold key shares)
+pub fn sign_manifest_bls(manifest: &NetworkManifest, secret_key: &BlsSecretKey) -> Vec<u8> {
+    crate::bls::sign_message_bls(&manifest.to_bytes(), secret_key)
+}
+
+/// Count distinct witnesses of `signers` with a valid signature, requiring its threshold
+fn verify_manifest_signatures(signed: &SignedManifest, signers: &NetworkSnapshot) -> Result<usize> {
+    let manifest = &signed.manifest;
+    let message = manifest.to_bytes();
+    let mut verified: Vec<&str> = Vec::new();
+
+    for witness_sig in &signed.signatures {
+        if verified.contains(&witness_sig.witness_id.as_str()) {
+            continue;
+        }
+
+        let Some(witness) = signers.witnesses.iter().find(|w| w.id == witness_sig.witness_id) else {
+            continue;
+        };
+
+        let Some(pubkey) = witness.pubkey_at(manifest.issued_at) else {
+            continue;
+        };
+
+        // Threshold witnesses sign individually with their share keys
+        let valid = match signers.signature_scheme {
+            SignatureScheme::Ed25519 => {
+                let key = crate::decode_public_key(pubkey)?;
+                crate::crypto::verify_message(&message, &witness_sig.signature, &key).is_ok()
+            }
+            SignatureScheme::BLS | SignatureScheme::ThresholdBLS => {
+                let key = crate::decode_bls_public_key(pubkey)?;
+                crate::bls::verify_message_bls(&message, &witness_sig.signature, &key).is_ok()
+            }
+        };
+
+        if valid {
+            verified.push(&witness_sig.witness_id);
+        }
+    }
+
+    if verified.len() < signers.threshold {
+        return Err(WitnessError::InsufficientSignatures {
+            got: verified.len(),
+            required: signers.threshold,
+        });
+    }
+
+    Ok(verified.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_keypair;
+
+    fn network(keys: &[(SigningKey, ed25519_dalek::VerifyingKey)], threshold: usize) -> NetworkSnapshot {
+        NetworkSnapshot {
+            id: "test-net".to_string(),
+            signature_scheme: SignatureScheme::Ed25519,
+            threshold,
+            witnesses: keys
+                .iter()
+                .enumerate()
+                .map(|(i, (_, vk))| WitnessInfo {
+                    id: format!("w{}", i + 1),
+                    pubkey: crate::encode_public_key(vk),
+                    endpoint: String::new(),
+                    share_index: None,
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+                .collect(),
+            group_public_key: None,
+            peer_networks: vec![],
+            cross_anchor_threshold: 0,
+        }
+    }
+
+    fn sign(manifest: NetworkManifest, keys: &[(SigningKey, ed25519_dalek::VerifyingKey)], signers: &[usize]) -> SignedManifest {
+        let mut signed = SignedManifest::new(manifest);
+        for &i in signers {
+            let signature = sign_manifest(&signed.manifest, &keys[i].0);
+            signed.add_signature(format!("w{}", i + 1), signature);
+        }
+        signed
+    }
+
+    #[test]
+    fn test_manifest_chain() {
+        let keys: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let first = sign(NetworkManifest::new(network(&keys, 2), None), &keys, &[0, 1]);
+        assert_eq!(first.verify_self_signed().unwrap(), 2);
+
+        // Rotate witness 3's key: signed by the old set and the new set
+        let mut rotated = keys.clone();
+        rotated[2] = generate_keypair();
+        let second = sign(
+            NetworkManifest::new(network(&rotated, 2), Some(&first.manifest)),
+            &rotated,
+            &[0, 1, 2],
+        );
+        assert_eq!(second.manifest.version, 2);
+        assert!(first.verify_update(&second).is_ok());
+        assert!(verify_manifest_chain(&[first.clone(), second.clone()]).is_ok());
+
+        // Survives a JSON round trip
+        let json = serde_json::to_string(&second).unwrap();
+        let decoded: SignedManifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.manifest.hash(), second.manifest.hash());
+        assert!(first.verify_update(&decoded).is_ok());
+    }
+
+    #[test]
+    fn test_manifest_rejects_takeover() {
+        let keys: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let first = sign(NetworkManifest::new(network(&keys, 2), None), &keys, &[0, 1]);
+
+        // A gateway replacing every key can self-sign, but not chain from the old set
+        let attacker: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let forged = sign(
+            NetworkManifest::new(network(&attacker, 2), Some(&first.manifest)),
+            &attacker,
+            &[0, 1, 2],
+        );
+        assert!(forged.verify_self_signed().is_ok());
+        assert!(first.verify_update(&forged).is_err());
+
+        // Below threshold
+        let unsigned = sign(NetworkManifest::new(network(&keys, 2), None), &keys, &[0]);
+        assert!(unsigned.verify_self_signed().is_err());
+
+        // Skipping a version or breaking the hash chain fails
+        let mut skipped = NetworkManifest::new(network(&keys, 2), Some(&first.manifest));
+        skipped.version += 1;
+        assert!(first.verify_update(&sign(skipped, &keys, &[0, 1])).is_err());
+
+        let mut unchained = NetworkManifest::new(network(&keys, 2), Some(&first.manifest));
+        unchained.previous_hash = Some(hex::encode([0u8; 32]));
+        assert!(first.verify_update(&sign(unchained, &keys, &[0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_manifest_encoding_is_explicit() {
+        let keys: Vec<_> = (0..3).map(|_| generate_keypair()).collect();
+        let manifest = NetworkManifest::new(network(&keys, 2), None);
+
+        // Fields the encoding does not know about do not change what is signed
+        let mut json = serde_json::to_value(&manifest).unwrap();
+        json["network"]["future_field"] = "ignored".into();
+        let decoded: NetworkManifest = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.to_bytes(), manifest.to_bytes());
+
+        // Every field it does know about is covered
+        let mut changed = manifest.clone();
+        changed.network.cross_anchor_threshold = 2;
+        assert_ne!(changed.hash(), manifest.hash());
+
+        let mut changed = manifest.clone();
+        changed.network.witnesses[0].key_activated_at = Some(0);
+        assert_ne!(changed.hash(), manifest.hash());
+
+        // Length prefixes keep adjacent strings apart
+        let mut shifted = manifest.clone();
+        let pubkey = &manifest.network.witnesses[0].pubkey;
+        shifted.network.witnesses[0].id = format!("w1{}", &pubkey[..1]);
+        shifted.network.witnesses[0].pubkey = pubkey[1..].to_string();
+        assert_ne!(shifted.to_bytes(), manifest.to_bytes());
+    }
+}
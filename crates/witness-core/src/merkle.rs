@@ -17,6 +17,13 @@ pub struct MerkleProof {
     pub root: [u8; 32],
 }
 
+impl MerkleProof {
+    /// Check that the siblings lead from the leaf to the root
+    pub fn verify(&self) -> bool {
+        MerkleTree::verify_proof(self.leaf, &self.siblings, self.root)
+    }
+}
+
 mod hex_bytes {
     use serde::{Deserialize, Deserializer, Serializer};
 
@@ -125,6 +132,37 @@ impl MerkleTree {
         Some(proof)
     }
 
+    /// Get merkle proofs for every leaf, building each level only once
+    pub fn proofs(&self) -> Vec<MerkleProof> {
+        let mut levels = vec![self.leaves.clone()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next_level = Self::build_level(&levels[levels.len() - 1]);
+            levels.push(next_level);
+        }
+
+        (0..self.leaves.len())
+            .map(|index| {
+                let mut siblings = Vec::new();
+                let mut current_index = index;
+
+                for level in &levels[..levels.len() - 1] {
+                    let sibling_index = current_index ^ 1;
+                    if sibling_index < level.len() {
+                        siblings.push(level[sibling_index]);
+                    }
+                    current_index /= 2;
+                }
+
+                MerkleProof {
+                    leaf: self.leaves[index],
+                    siblings,
+                    index,
+                    root: self.root,
+                }
+            })
+            .collect()
+    }
+
     /// Verify a merkle proof
     pub fn verify_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: [u8; 32]) -> bool {
         let mut current = leaf;
@@ -237,4 +275,37 @@ mod tests {
             assert!(MerkleTree::verify_proof(leaves[i], &proof, tree.root()));
         }
     }
+
+    #[test]
+    fn test_all_proofs() {
+        let leaves: Vec<[u8; 32]> = (1..=5).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+
+        let proofs = tree.proofs();
+        assert_eq!(proofs.len(), leaves.len());
+        for (i, proof) in proofs.iter().enumerate() {
+            assert_eq!(proof.leaf, leaves[i]);
+            assert_eq!(proof.siblings, tree.proof(i).unwrap());
+            assert!(proof.verify());
+        }
+
+        assert!(MerkleTree::new(vec![]).proofs().is_empty());
+    }
+
+    #[test]
+    fn test_merkle_proof_verify() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let tree = MerkleTree::new(leaves.clone());
+
+        let mut proof = MerkleProof {
+            leaf: leaves[1],
+            siblings: tree.proof(1).unwrap(),
+            index: 1,
+            root: tree.root(),
+        };
+        assert!(proof.verify());
+
+        proof.leaf = [9u8; 32];
+        assert!(!proof.verify());
+    }
 }
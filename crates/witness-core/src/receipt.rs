@@ -0,0 +1,536 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::external_anchors::ExternalAnchorProof;
+use crate::federation::{
+    AttestationBatch, CrossAnchor, FederationConfig, PeerNetworkInfo, VerificationLevel,
+};
+use crate::merkle::{MerkleProof, MerkleTree};
+use crate::signature_scheme::SignatureScheme;
+use crate::{NetworkConfig, Result, SignedAttestation, WitnessError, WitnessInfo};
+
+/// Current receipt format version
+pub const RECEIPT_VERSION: u32 = 1;
+
+/// Snapshot of a network's witness set at the time a receipt was issued
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// Network identifier
+    pub id: String,
+
+    /// Signature scheme (ed25519 or bls)
+    pub signature_scheme: SignatureScheme,
+
+    /// Minimum number of signatures required
+    pub threshold: usize,
+
+    /// Witnesses and their public keys
+    pub witnesses: Vec<WitnessInfo>,
+
+    /// Group public key (threshold_bls only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_public_key: Option<String>,
+
+    /// Peer networks and their witness keys, for verifying cross-anchors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub peer_networks: Vec<PeerNetworkInfo>,
+
+    /// Peer networks that must cross-anchor a batch (0 means 1)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cross_anchor_threshold: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl NetworkSnapshot {
+    /// Snapshot the witness keys of a network configuration
+    pub fn from_config(config: &NetworkConfig) -> Self {
+        Self {
+            id: config.id.clone(),
+            signature_scheme: config.signature_scheme,
+            threshold: config.threshold,
+            witnesses: config.witnesses.clone(),
+            group_public_key: config.group_public_key.clone(),
+            peer_networks: config.federation.peer_networks.clone(),
+            cross_anchor_threshold: config.federation.cross_anchor_threshold,
+        }
+    }
+
+    /// Build a network configuration usable for signature verification
+    pub fn to_network_config(&self) -> NetworkConfig {
+        NetworkConfig {
+            id: self.id.clone(),
+            witnesses: self.witnesses.clone(),
+            threshold: self.threshold,
+            signature_scheme: self.signature_scheme,
+            group_public_key: self.group_public_key.clone(),
+            federation: FederationConfig {
+                peer_networks: self.peer_networks.clone(),
+                cross_anchor_threshold: self.cross_anchor_threshold,
+                ..Default::default()
+            },
+            external_anchors: Default::default(),
+            federation_peers: Vec::new(),
+            legacy_encoding_until: None,
+        }
+    }
+}
+
+/// Self-contained proof bundle for a single timestamped hash
+///
+/// Holds everything needed to verify a timestamp offline: the signed
+/// attestation, its batch and merkle inclusion proof, cross-anchors from
+/// peer networks, external anchor proofs and the witness keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessReceipt {
+    /// Receipt format version
+    pub version: u32,
+
+    /// The signed attestation
+    pub attestation: SignedAttestation,
+
+    /// Proof that the hash is a leaf of the attested merkle root (batch timestamps only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inclusion_proof: Option<MerkleProof>,
+
+    /// Batch containing the attestation (if batched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<AttestationBatch>,
+
+    /// Merkle proof of inclusion in the batch (if batched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merkle_proof: Option<MerkleProof>,
+
+    /// Cross-anchors from peer networks
+    #[serde(default)]
+    pub cross_anchors: Vec<CrossAnchor>,
+
+    /// External anchor proofs for the batch
+    #[serde(default)]
+    pub external_anchors: Vec<ExternalAnchorProof>,
+
+    /// Witness keys of the issuing network
+    pub network: NetworkSnapshot,
+}
+
+impl WitnessReceipt {
+    /// Create a receipt for an attestation that has not been batched yet
+    pub fn new(attestation: SignedAttestation, network: NetworkSnapshot) -> Self {
+        Self {
+            version: RECEIPT_VERSION,
+            attestation,
+            inclusion_proof: None,
+            batch: None,
+            merkle_proof: None,
+            cross_anchors: Vec::new(),
+            external_anchors: Vec::new(),
+            network,
+        }
+    }
+
+    /// The hash this receipt timestamps
+    pub fn hash(&self) -> [u8; 32] {
+        match &self.inclusion_proof {
+            Some(proof) => proof.leaf,
+            None => self.attestation.attestation.hash,
+        }
+    }
+}
+
+/// Summary of what a receipt proves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptVerification {
+    /// Number of valid witness signatures on the attestation
+    pub verified_signatures: usize,
+
+    /// Whether the attestation is proven to be part of a batch
+    pub batched: bool,
+
+    /// Number of cross-anchors that commit to the batch
+    pub cross_anchor_count: usize,
+
+    /// Number of distinct peer networks with a cross-anchor that verified
+    /// against their known keys
+    pub verified_cross_anchors: usize,
+
+    /// Cross-anchors that failed verification against known peer keys, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invalid_cross_anchors: Vec<String>,
+
+    /// Number of legacy cross-anchors, made before statements, which cannot be verified
+    pub legacy_cross_anchors: usize,
+
+    /// Distinct peer networks that must cross-anchor the batch
+    pub required_cross_anchors: usize,
+
+    /// Number of external anchor proofs for the batch; these are the issuing
+    /// gateway's claims and are not checked against the external services
+    pub unverified_external_anchors: usize,
+}
+
+impl ReceiptVerification {
+    /// Highest verification level the receipt reaches
+    ///
+    /// Only what was verified counts: cross-anchors from enough distinct peer
+    /// networks make a receipt federated. Unverified external anchors never
+    /// raise the level.
+    pub fn level(&self) -> VerificationLevel {
+        if !self.batched {
+            return VerificationLevel::Basic;
+        }
+
+        if self.verified_cross_anchors >= self.required_cross_anchors {
+            return VerificationLevel::Federated {
+                peer_count: self.verified_cross_anchors,
+            };
+        }
+
+        VerificationLevel::Batched
+    }
+}
+
+/// Verify a receipt against a network configuration
+///
+/// Pass the receipt's own snapshot (`receipt.network.to_network_config()`)
+/// only if the embedded keys are trusted; otherwise use a pinned config.
+pub fn verify_receipt(receipt: &WitnessReceipt, config: &NetworkConfig) -> Result<ReceiptVerification> {
+    if receipt.version != RECEIPT_VERSION {
+        return Err(WitnessError::UnsupportedVersion(receipt.version));
+    }
+
+    let attestation = &receipt.attestation.attestation;
+
+    if attestation.network_id != config.id {
+        return Err(WitnessError::InvalidProof(format!(
+            "attestation is from network '{}', expected '{}'",
+            attestation.network_id, config.id
+        )));
+    }
+
+    let verified_signatures = crate::verify_signed_attestation(&receipt.attestation, config)?;
+
+    crate::verify_inclusion_proof(receipt.inclusion_proof.as_ref(), attestation)?;
+
+    let batched = match (&receipt.batch, &receipt.merkle_proof) {
+        (Some(batch), Some(proof)) => {
+            if batch.network_id != attestation.network_id {
+                return Err(WitnessError::InvalidProof(
+                    "batch network does not match attestation".to_string(),
+                ));
+            }
+
+            if proof.leaf != attestation.hash {
+                return Err(WitnessError::InvalidProof(
+                    "merkle proof leaf does not match attestation hash".to_string(),
+                ));
+            }
+
+            if proof.root != batch.merkle_root
+                || !MerkleTree::verify_proof(proof.leaf, &proof.siblings, proof.root)
+            {
+                return Err(WitnessError::InvalidProof(
+                    "merkle proof does not lead to batch root".to_string(),
+                ));
+            }
+
+            true
+        }
+        (None, None) => false,
+        _ => {
+            return Err(WitnessError::InvalidProof(
+                "batch and merkle proof must be provided together".to_string(),
+            ))
+        }
+    };
+
+    let mut verified_peers = HashSet::new();
+    let mut invalid_cross_anchors = Vec::new();
+    let mut legacy_cross_anchors = 0;
+
+    if !receipt.cross_anchors.is_empty() || !receipt.external_anchors.is_empty() {
+        let Some(batch) = &receipt.batch else {
+            return Err(WitnessError::InvalidProof(
+                "anchors present without a batch".to_string(),
+            ));
+        };
+
+        for cross_anchor in &receipt.cross_anchors {
+            if cross_anchor.statement.batch != *batch {
+                return Err(WitnessError::InvalidProof(format!(
+                    "cross-anchor from '{}' is for a different batch",
+                    cross_anchor.statement.witnessing_network
+                )));
+            }
+
+            if cross_anchor.statement.is_legacy() {
+                legacy_cross_anchors += 1;
+                continue;
+            }
+
+            let peer = config
+                .federation
+                .peer_networks
+                .iter()
+                .find(|p| p.id == cross_anchor.statement.witnessing_network);
+
+            // Without the peer's keys a cross-anchor is counted but not verified
+            let Some(peer) = peer else {
+                continue;
+            };
+
+            match crate::verify_cross_anchor(cross_anchor, peer) {
+                Ok(_) => {
+                    verified_peers.insert(peer.id.as_str());
+                }
+                Err(e) => invalid_cross_anchors.push(format!("{}: {}", peer.id, e)),
+            }
+        }
+    }
+
+    Ok(ReceiptVerification {
+        verified_signatures,
+        batched,
+        cross_anchor_count: receipt.cross_anchors.len(),
+        verified_cross_anchors: verified_peers.len(),
+        invalid_cross_anchors,
+        legacy_cross_anchors,
+        required_cross_anchors: config.federation.required_cross_anchors(),
+        unverified_external_anchors: receipt.external_anchors.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_keypair, sign_attestation, Attestation};
+
+    fn signed_receipt() -> WitnessReceipt {
+        signed_receipt_for([1; 32])
+    }
+
+    fn signed_receipt_for(hash: [u8; 32]) -> WitnessReceipt {
+        let keys: Vec<_> = (0..2).map(|_| generate_keypair()).collect();
+        let witnesses = keys
+            .iter()
+            .enumerate()
+            .map(|(i, (_, vk))| WitnessInfo {
+                id: format!("w{}", i + 1),
+                pubkey: crate::encode_public_key(vk),
+                endpoint: format!("http://localhost:300{}", i + 1),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
+            })
+            .collect();
+
+        let attestation = Attestation::new(hash, "test-net".to_string(), 1);
+        let mut signed = SignedAttestation::new(attestation.clone());
+        for (i, (sk, _)) in keys.iter().enumerate() {
+            signed.add_signature(format!("w{}", i + 1), sign_attestation(&attestation, sk));
+        }
+
+        let network = NetworkSnapshot {
+            id: "test-net".to_string(),
+            signature_scheme: SignatureScheme::Ed25519,
+            threshold: 2,
+            witnesses,
+            group_public_key: None,
+            peer_networks: vec![],
+            cross_anchor_threshold: 0,
+        };
+
+        WitnessReceipt::new(signed, network)
+    }
+
+    fn add_batch(receipt: &mut WitnessReceipt) {
+        let leaves = vec![receipt.attestation.attestation.hash, [2; 32], [3; 32]];
+        let tree = MerkleTree::new(leaves.clone());
+
+        receipt.batch = Some(AttestationBatch {
+            id: 7,
+            network_id: "test-net".to_string(),
+            merkle_root: tree.root(),
+            period_start: 1700000000,
+            period_end: 1700003600,
+            attestation_count: 3,
+        });
+        receipt.merkle_proof = Some(MerkleProof {
+            leaf: leaves[0],
+            siblings: tree.proof(0).unwrap(),
+            index: 0,
+            root: tree.root(),
+        });
+    }
+
+    #[test]
+    fn test_receipt_roundtrip_and_verify() {
+        let mut receipt = signed_receipt();
+        add_batch(&mut receipt);
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        let receipt: WitnessReceipt = serde_json::from_str(&json).unwrap();
+
+        let config = receipt.network.to_network_config();
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.verified_signatures, 2);
+        assert!(result.batched);
+        assert_eq!(result.level(), VerificationLevel::Batched);
+    }
+
+    #[test]
+    fn test_receipt_unbatched() {
+        let receipt = signed_receipt();
+        let config = receipt.network.to_network_config();
+
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert!(!result.batched);
+        assert_eq!(result.level(), VerificationLevel::Basic);
+    }
+
+    #[test]
+    fn test_receipt_rejects_bad_merkle_proof() {
+        let mut receipt = signed_receipt();
+        add_batch(&mut receipt);
+        receipt.batch.as_mut().unwrap().merkle_root = [9; 32];
+
+        let config = receipt.network.to_network_config();
+        assert!(verify_receipt(&receipt, &config).is_err());
+    }
+
+    #[test]
+    fn test_receipt_inclusion_proof() {
+        let leaves = vec![[4; 32], [5; 32], [6; 32]];
+        let tree = MerkleTree::new(leaves.clone());
+
+        let mut receipt = signed_receipt_for(tree.root());
+        receipt.inclusion_proof = Some(MerkleProof {
+            leaf: leaves[2],
+            siblings: tree.proof(2).unwrap(),
+            index: 2,
+            root: tree.root(),
+        });
+        assert_eq!(receipt.hash(), leaves[2]);
+
+        let config = receipt.network.to_network_config();
+        assert!(verify_receipt(&receipt, &config).is_ok());
+
+        // A proof for a leaf that is not in the tree
+        receipt.inclusion_proof.as_mut().unwrap().leaf = [7; 32];
+        assert!(verify_receipt(&receipt, &config).is_err());
+
+        // A valid proof for a root the witnesses did not sign
+        let mut receipt = signed_receipt();
+        receipt.inclusion_proof = Some(MerkleProof {
+            leaf: leaves[0],
+            siblings: tree.proof(0).unwrap(),
+            index: 0,
+            root: tree.root(),
+        });
+        let config = receipt.network.to_network_config();
+        assert!(verify_receipt(&receipt, &config).is_err());
+    }
+
+    #[test]
+    fn test_receipt_rejects_unknown_version() {
+        let mut receipt = signed_receipt();
+        receipt.version = RECEIPT_VERSION + 1;
+
+        let config = receipt.network.to_network_config();
+        assert!(verify_receipt(&receipt, &config).is_err());
+    }
+
+    #[test]
+    fn test_receipt_federated_level() {
+        let mut receipt = signed_receipt();
+        add_batch(&mut receipt);
+
+        let (peer_sk, peer_vk) = generate_keypair();
+        let statement = crate::CrossAnchorStatement::new(
+            receipt.batch.clone().unwrap(),
+            "peer-net".to_string(),
+        );
+        receipt.cross_anchors.push(CrossAnchor {
+            signatures: vec![crate::WitnessSignature {
+                witness_id: "p1".to_string(),
+                signature: crate::sign_cross_anchor(&statement, &peer_sk),
+            }],
+            statement,
+        });
+
+        // Without the peer's keys the cross-anchor is counted but not verified
+        let config = receipt.network.to_network_config();
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.cross_anchor_count, 1);
+        assert_eq!(result.verified_cross_anchors, 0);
+        assert_eq!(result.level(), VerificationLevel::Batched);
+
+        receipt.network.peer_networks.push(PeerNetworkInfo {
+            id: "peer-net".to_string(),
+            gateway: "http://localhost:9002".to_string(),
+            min_witnesses: 1,
+            witnesses: vec![WitnessInfo {
+                id: "p1".to_string(),
+                pubkey: crate::encode_public_key(&peer_vk),
+                endpoint: String::new(),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
+            }],
+            signature_scheme: SignatureScheme::Ed25519,
+            threshold: 1,
+        });
+
+        let config = receipt.network.to_network_config();
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.verified_cross_anchors, 1);
+        assert_eq!(result.level(), VerificationLevel::Federated { peer_count: 1 });
+
+        // One peer network is not enough when the network requires two
+        receipt.network.cross_anchor_threshold = 2;
+        let strict = receipt.network.to_network_config();
+        let result = verify_receipt(&receipt, &strict).unwrap();
+        assert_eq!(result.required_cross_anchors, 2);
+        assert_eq!(result.level(), VerificationLevel::Batched);
+        receipt.network.cross_anchor_threshold = 0;
+
+        // An external anchor anyone could add does not raise the level
+        receipt.external_anchors.push(ExternalAnchorProof {
+            provider: crate::AnchorProviderType::DnsTxt,
+            timestamp: 1700003600,
+            proof: serde_json::json!({}),
+            anchored_data: None,
+        });
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.unverified_external_anchors, 1);
+        assert_eq!(result.level(), VerificationLevel::Federated { peer_count: 1 });
+        receipt.external_anchors.clear();
+
+        // A second cross-anchor from the same peer is still one peer network
+        receipt.cross_anchors.push(receipt.cross_anchors[0].clone());
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.verified_cross_anchors, 1);
+        assert!(result.invalid_cross_anchors.is_empty());
+
+        // A cross-anchor with a bad signature is reported
+        let mut forged = receipt.cross_anchors[0].clone();
+        forged.signatures[0].signature[0] ^= 1;
+        receipt.cross_anchors.push(forged);
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.verified_cross_anchors, 1);
+        assert_eq!(result.invalid_cross_anchors.len(), 1);
+        assert!(result.invalid_cross_anchors[0].starts_with("peer-net"));
+        receipt.cross_anchors.truncate(1);
+
+        // A legacy cross-anchor is reported, not verified
+        let mut legacy = receipt.cross_anchors[0].clone();
+        legacy.statement.version = crate::CROSS_ANCHOR_VERSION_LEGACY;
+        receipt.cross_anchors.push(legacy);
+        let result = verify_receipt(&receipt, &config).unwrap();
+        assert_eq!(result.verified_cross_anchors, 1);
+        assert_eq!(result.legacy_cross_anchors, 1);
+    }
+}
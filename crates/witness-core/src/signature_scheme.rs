@@ -10,6 +10,10 @@ pub enum SignatureScheme {
     /// BLS signatures (Phase 4, aggregated)
     #[serde(rename = "bls")]
     BLS,
+
+    /// Threshold BLS: witnesses hold Shamir shares of one group key
+    #[serde(rename = "threshold_bls")]
+    ThresholdBLS,
 }
 
 impl Default for SignatureScheme {
@@ -23,6 +27,7 @@ impl std::fmt::Display for SignatureScheme {
         match self {
             SignatureScheme::Ed25519 => write!(f, "ed25519"),
             SignatureScheme::BLS => write!(f, "bls"),
+            SignatureScheme::ThresholdBLS => write!(f, "threshold_bls"),
         }
     }
 }
@@ -44,6 +49,12 @@ pub enum AttestationSignatures {
         /// List of witness IDs that participated
         signers: Vec<String>,
     },
+
+    /// Threshold BLS group signature (does not reveal which witnesses signed)
+    Threshold {
+        /// Signature under the network's group public key
+        signature: Vec<u8>,
+    },
 }
 
 impl AttestationSignatures {
@@ -66,14 +77,24 @@ impl AttestationSignatures {
         }
     }
 
+    pub fn new_threshold(signature: Vec<u8>) -> Self {
+        AttestationSignatures::Threshold { signature }
+    }
+
+    /// Number of signers (0 for threshold signatures, which do not reveal signers)
     pub fn signer_count(&self) -> usize {
         match self {
             AttestationSignatures::MultiSig { signatures } => signatures.len(),
             AttestationSignatures::Aggregated { signers, .. } => signers.len(),
+            AttestationSignatures::Threshold { .. } => 0,
         }
     }
 
     pub fn is_aggregated(&self) -> bool {
         matches!(self, AttestationSignatures::Aggregated { .. })
     }
+
+    pub fn is_threshold(&self) -> bool {
+        matches!(self, AttestationSignatures::Threshold { .. })
+    }
 }
@@ -0,0 +1,333 @@
+//! Threshold BLS signatures (t-of-n) over Shamir-shared keys
+//!
+//! Each witness holds a Shamir share `s_i = f(i)` of a group secret `f(0)`.
+//! Partial signatures are ordinary BLS signatures under the share key; any
+//! `threshold` of them are Lagrange-interpolated at zero into a signature
+//! that verifies under the single group public key.
+
+use blst::min_sig::{PublicKey, SecretKey, Signature};
+use blst::{
+    blst_bendian_from_scalar, blst_fr, blst_fr_add, blst_fr_from_scalar, blst_fr_from_uint64,
+    blst_fr_inverse, blst_fr_mul, blst_fr_sub, blst_p1, blst_p1_add_or_double, blst_p1_affine,
+    blst_p1_from_affine, blst_p1_mult, blst_p1_to_affine, blst_scalar, blst_scalar_from_bendian,
+    blst_scalar_from_fr,
+};
+use rand::RngCore;
+
+use crate::{Attestation, Result, WitnessError};
+
+/// One witness's share of the group secret key
+#[derive(Clone)]
+pub struct KeyShare {
+    /// Share index (x coordinate, starting at 1)
+    pub index: u32,
+
+    /// Share secret key `f(index)`
+    pub secret_key: SecretKey,
+}
+
+impl KeyShare {
+    /// Public key of this share, used to verify partial signatures
+    pub fn public_key(&self) -> PublicKey {
+        self.secret_key.sk_to_pk()
+    }
+}
+
+/// Output of a trusted-dealer threshold key generation
+pub struct ThresholdKeys {
+    /// Group public key that combined signatures verify under
+    pub group_public_key: PublicKey,
+
+    /// Shares for witnesses 1..=n
+    pub shares: Vec<KeyShare>,
+}
+
+/// Generate a t-of-n threshold BLS key set with a trusted dealer
+///
+/// The dealer learns every share; see the DKG ceremony for a setup where no
+/// single party does.
+pub fn generate_threshold_keys(threshold: usize, total: usize) -> Result<ThresholdKeys> {
+    if threshold == 0 || threshold > total {
+        return Err(WitnessError::InsufficientSignatures {
+            got: total,
+            required: threshold,
+        });
+    }
+
+    let coefficients: Vec<blst_fr> = (0..threshold).map(|_| random_fr()).collect();
+
+    let shares = (1..=total as u32)
+        .map(|index| {
+            Ok(KeyShare {
+                index,
+                secret_key: fr_to_secret_key(&evaluate_polynomial(&coefficients, index))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let group_public_key = fr_to_secret_key(&coefficients[0])?.sk_to_pk();
+
+    Ok(ThresholdKeys {
+        group_public_key,
+        shares,
+    })
+}
+
+/// Combine `threshold` partial signatures into a group signature
+///
+/// `partials` are `(share index, partial signature)` pairs. Partial signatures
+/// should be verified against their share public keys first: a single bad
+/// partial yields a combined signature that does not verify.
+pub fn combine_partial_signatures(partials: &[(u32, Vec<u8>)], threshold: usize) -> Result<Vec<u8>> {
+    if threshold == 0 || partials.len() < threshold {
+        return Err(WitnessError::InsufficientSignatures {
+            got: partials.len(),
+            required: threshold,
+        });
+    }
+
+    let partials = &partials[..threshold];
+    let indices: Vec<u32> = partials.iter().map(|(index, _)| *index).collect();
+
+    for (i, index) in indices.iter().enumerate() {
+        if *index == 0 || indices[..i].contains(index) {
+            return Err(WitnessError::InvalidSignature);
+        }
+    }
+
+    let mut combined = blst_p1::default();
+
+    for (index, signature_bytes) in partials {
+        let signature = Signature::from_bytes(signature_bytes)
+            .map_err(|_| WitnessError::InvalidSignature)?;
+        let affine: blst_p1_affine = signature.into();
+
+        let coefficient = fr_to_scalar(&lagrange_coefficient_at_zero(*index, &indices));
+
+        let mut point = blst_p1::default();
+        let mut term = blst_p1::default();
+        unsafe {
+            blst_p1_from_affine(&mut point, &affine);
+            blst_p1_mult(&mut term, &point, coefficient.b.as_ptr(), 255);
+            blst_p1_add_or_double(&mut combined, &combined, &term);
+        }
+    }
+
+    let mut affine = blst_p1_affine::default();
+    unsafe {
+        blst_p1_to_affine(&mut affine, &combined);
+    }
+
+    Ok(Signature::from(affine).to_bytes().to_vec())
+}
+
+/// Verify a combined threshold signature on an attestation
+pub fn verify_threshold_signature(
+    attestation: &Attestation,
+    signature: &[u8],
+    group_public_key: &PublicKey,
+) -> Result<()> {
+    crate::verify_signature_bls(attestation, signature, group_public_key)
+}
+
+/// Lagrange basis polynomial for `index` over `indices`, evaluated at zero
+fn lagrange_coefficient_at_zero(index: u32, indices: &[u32]) -> blst_fr {
+    let x_i = fr_from_u64(index as u64);
+    let mut numerator = fr_from_u64(1);
+    let mut denominator = fr_from_u64(1);
+
+    for &other in indices.iter().filter(|&&other| other != index) {
+        let x_j = fr_from_u64(other as u64);
+        let mut difference = blst_fr::default();
+
+        unsafe {
+            blst_fr_mul(&mut numerator, &numerator, &x_j);
+            blst_fr_sub(&mut difference, &x_j, &x_i);
+            blst_fr_mul(&mut denominator, &denominator, &difference);
+        }
+    }
+
+    let mut result = blst_fr::default();
+    unsafe {
+        blst_fr_inverse(&mut denominator, &denominator);
+        blst_fr_mul(&mut result, &numerator, &denominator);
+    }
+
+    result
+}
+
+/// Evaluate `f(x) = c_0 + c_1 x + ... + c_{t-1} x^{t-1}` using Horner's rule
+pub(crate) fn evaluate_polynomial(coefficients: &[blst_fr], x: u32) -> blst_fr {
+    let x = fr_from_u64(x as u64);
+    let mut result = blst_fr::default();
+
+    for coefficient in coefficients.iter().rev() {
+        unsafe {
+            blst_fr_mul(&mut result, &result, &x);
+            blst_fr_add(&mut result, &result, coefficient);
+        }
+    }
+
+    result
+}
+
+/// Uniformly random non-zero field element
+pub(crate) fn random_fr() -> blst_fr {
+    let mut ikm = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut ikm);
+
+    // key_gen always yields a valid, non-zero scalar
+    let secret_key = SecretKey::key_gen(&ikm, &[]).unwrap();
+    secret_key_to_fr(&secret_key)
+}
+
+pub(crate) fn fr_from_u64(value: u64) -> blst_fr {
+    let limbs = [value, 0, 0, 0];
+    let mut fr = blst_fr::default();
+    unsafe {
+        blst_fr_from_uint64(&mut fr, limbs.as_ptr());
+    }
+    fr
+}
+
+pub(crate) fn secret_key_to_fr(secret_key: &SecretKey) -> blst_fr {
+    let mut scalar = blst_scalar::default();
+    let mut fr = blst_fr::default();
+    unsafe {
+        blst_scalar_from_bendian(&mut scalar, secret_key.to_bytes().as_ptr());
+        blst_fr_from_scalar(&mut fr, &scalar);
+    }
+    fr
+}
+
+pub(crate) fn fr_to_secret_key(fr: &blst_fr) -> Result<SecretKey> {
+    let scalar = fr_to_scalar(fr);
+    let mut bytes = [0u8; 32];
+    unsafe {
+        blst_bendian_from_scalar(bytes.as_mut_ptr(), &scalar);
+    }
+
+    SecretKey::from_bytes(&bytes)
+        .map_err(|_| WitnessError::InvalidPublicKey("Invalid BLS secret share".to_string()))
+}
+
+fn fr_to_scalar(fr: &blst_fr) -> blst_scalar {
+    let mut scalar = blst_scalar::default();
+    unsafe {
+        blst_scalar_from_fr(&mut scalar, fr);
+    }
+    scalar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partials(keys: &ThresholdKeys, attestation: &Attestation, indices: &[u32]) -> Vec<(u32, Vec<u8>)> {
+        indices
+            .iter()
+            .map(|&index| {
+                let share = &keys.shares[index as usize - 1];
+                (index, crate::sign_attestation_bls(attestation, &share.secret_key))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_threshold_sign_and_combine() {
+        let keys = generate_threshold_keys(3, 5).unwrap();
+        let attestation = Attestation::new([5; 32], "test-net".to_string(), 1);
+
+        // Any subset of 3 shares produces the same group signature
+        let a = combine_partial_signatures(&partials(&keys, &attestation, &[1, 2, 3]), 3).unwrap();
+        let b = combine_partial_signatures(&partials(&keys, &attestation, &[5, 2, 4]), 3).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 48);
+
+        assert!(verify_threshold_signature(&attestation, &a, &keys.group_public_key).is_ok());
+
+        // Partial signatures verify under their share keys, not the group key
+        let partial = &partials(&keys, &attestation, &[1])[0].1;
+        assert!(crate::verify_signature_bls(&attestation, partial, &keys.shares[0].public_key()).is_ok());
+        assert!(verify_threshold_signature(&attestation, partial, &keys.group_public_key).is_err());
+    }
+
+    #[test]
+    fn test_threshold_below_threshold_fails() {
+        let keys = generate_threshold_keys(3, 5).unwrap();
+        let attestation = Attestation::new([6; 32], "test-net".to_string(), 1);
+
+        let two = partials(&keys, &attestation, &[1, 2]);
+        assert!(combine_partial_signatures(&two, 3).is_err());
+
+        // Interpolating too few shares gives a signature that does not verify
+        let forged = combine_partial_signatures(&two, 2).unwrap();
+        assert!(verify_threshold_signature(&attestation, &forged, &keys.group_public_key).is_err());
+    }
+
+    #[test]
+    fn test_threshold_rejects_duplicate_indices() {
+        let keys = generate_threshold_keys(2, 3).unwrap();
+        let attestation = Attestation::new([7; 32], "test-net".to_string(), 1);
+
+        let mut duplicated = partials(&keys, &attestation, &[1]);
+        duplicated.push(duplicated[0].clone());
+        assert!(combine_partial_signatures(&duplicated, 2).is_err());
+    }
+
+    #[test]
+    fn test_generate_threshold_keys_rejects_bad_parameters() {
+        assert!(generate_threshold_keys(0, 3).is_err());
+        assert!(generate_threshold_keys(4, 3).is_err());
+    }
+
+    #[test]
+    fn test_verify_signed_attestation_threshold() {
+        let keys = generate_threshold_keys(2, 3).unwrap();
+        let config = crate::NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses: keys
+                .shares
+                .iter()
+                .map(|share| crate::WitnessInfo {
+                    id: format!("w{}", share.index),
+                    pubkey: crate::encode_bls_public_key(&share.public_key()),
+                    endpoint: String::new(),
+                    share_index: Some(share.index),
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+                .collect(),
+            threshold: 2,
+            signature_scheme: crate::SignatureScheme::ThresholdBLS,
+            group_public_key: Some(crate::encode_bls_public_key(&keys.group_public_key)),
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+        assert!(config.validate().is_ok());
+
+        let attestation = Attestation::new([8; 32], "test-net".to_string(), 1);
+        let signature = combine_partial_signatures(&partials(&keys, &attestation, &[3, 1]), 2).unwrap();
+        let signed = crate::SignedAttestation::new_with_threshold(attestation.clone(), signature);
+
+        assert_eq!(crate::verify_signed_attestation(&signed, &config).unwrap(), 2);
+
+        // The receipt form round-trips through JSON without a signer list
+        let json = serde_json::to_string(&signed).unwrap();
+        assert!(!json.contains("signers"));
+        let decoded: crate::SignedAttestation = serde_json::from_str(&json).unwrap();
+        assert!(decoded.is_threshold());
+
+        // Missing group key or share indices fail validation
+        let mut bad = config.clone();
+        bad.group_public_key = None;
+        assert!(bad.validate().is_err());
+
+        let mut bad = config;
+        bad.witnesses[1].share_index = bad.witnesses[0].share_index;
+        assert!(bad.validate().is_err());
+    }
+}
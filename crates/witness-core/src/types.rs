@@ -1,6 +1,26 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::merkle::MerkleProof;
+
+/// Domain-separation tag prefixed to every versioned attestation encoding
+pub const ATTESTATION_DOMAIN_TAG: &[u8] = b"WITNESS_ATTESTATION";
+
+/// Legacy (unversioned, unprefixed) attestation encoding
+pub const ATTESTATION_VERSION_LEGACY: u8 = 0;
+
+/// First attestation encoding version with sub-second milliseconds
+pub const ATTESTATION_VERSION_MILLIS: u8 = 2;
+
+/// Current attestation encoding version
+pub const ATTESTATION_VERSION: u8 = 2;
+
+/// Domain-separation tag for an attestation over a merkle root of many hashes
+pub const ROOT_ATTESTATION_DOMAIN_TAG: &[u8] = b"WITNESS_ROOT_ATTESTATION";
+
+/// Domain-separation tag for a witness's signed clock reading
+pub const TIME_READING_DOMAIN_TAG: &[u8] = b"WITNESS_TIME_READING_V1";
+
 /// Core attestation: what gets signed by witnesses
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Attestation {
@@ -15,26 +35,119 @@ pub struct Attestation {
 
     /// Monotonic sequence number for ordering
     pub sequence: u64,
+
+    /// Canonical encoding version (0 = legacy, missing in older attestations)
+    #[serde(default, skip_serializing_if = "is_legacy_version")]
+    pub version: u8,
+
+    /// Milliseconds past `timestamp` (0-999), for millisecond precision (v2 only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_millis: Option<u16>,
+
+    /// Number of leaves when `hash` is a merkle root over many hashes (root attestations only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_count: Option<u64>,
+}
+
+fn is_legacy_version(version: &u8) -> bool {
+    *version == ATTESTATION_VERSION_LEGACY
 }
 
 impl Attestation {
     pub fn new(hash: [u8; 32], network_id: String, sequence: u64) -> Self {
-        let timestamp = std::time::SystemTime::now()
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+            .unwrap();
 
         Self {
             hash,
-            timestamp,
+            timestamp: now.as_secs(),
             network_id,
             sequence,
+            version: ATTESTATION_VERSION,
+            timestamp_millis: Some(now.subsec_millis() as u16),
+            leaf_count: None,
+        }
+    }
+
+    /// Attestation over the merkle root of `leaf_count` hashes
+    pub fn new_root(root: [u8; 32], leaf_count: u64, network_id: String, sequence: u64) -> Self {
+        Self {
+            leaf_count: Some(leaf_count),
+            ..Self::new(root, network_id, sequence)
+        }
+    }
+
+    /// Whether this attests a merkle root rather than a single hash
+    pub fn is_root(&self) -> bool {
+        self.leaf_count.is_some()
+    }
+
+    /// Timestamp in Unix milliseconds (whole seconds if no millisecond part
+    /// was recorded), or `None` if it does not fit in a `u64`
+    pub fn timestamp_ms(&self) -> Option<u64> {
+        checked_timestamp_ms(self.timestamp, self.timestamp_millis)
+    }
+
+    /// Whether the millisecond part is well-formed for this encoding version
+    pub fn has_valid_millis(&self) -> bool {
+        match self.timestamp_millis {
+            None => true,
+            Some(millis) => self.version >= ATTESTATION_VERSION_MILLIS && millis < 1000,
         }
     }
 
+    /// Whether this attestation uses an encoding version this build understands
+    pub fn is_supported_version(&self) -> bool {
+        self.version <= ATTESTATION_VERSION
+    }
+
+    /// Whether this attestation uses the legacy v0 encoding
+    pub fn is_legacy(&self) -> bool {
+        self.version == ATTESTATION_VERSION_LEGACY
+    }
+
     /// Get canonical bytes for signing
+    ///
+    /// v1 layout: domain tag || version || hash || timestamp (u64 LE)
+    /// || network_id length (u32 LE) || network_id || sequence (u64 LE)
+    ///
+    /// v2 appends the milliseconds (u16 LE, 0xFFFF when absent)
+    ///
+    /// Root attestations use their own domain tag and append the leaf count
+    /// (u64 LE), so a root signature never verifies as a single-hash one.
     pub fn to_bytes(&self) -> Vec<u8> {
-        // Deterministic serialization for signing
+        // The legacy encoding has no room for a leaf count
+        if self.is_legacy() && !self.is_root() {
+            return self.to_legacy_bytes();
+        }
+
+        let network_id = self.network_id.as_bytes();
+        let domain_tag = match self.leaf_count {
+            Some(_) => ROOT_ATTESTATION_DOMAIN_TAG,
+            None => ATTESTATION_DOMAIN_TAG,
+        };
+        let mut bytes = Vec::with_capacity(
+            domain_tag.len() + 1 + 32 + 8 + 4 + network_id.len() + 8 + 2 + 8,
+        );
+        bytes.extend_from_slice(domain_tag);
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.hash);
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes.extend_from_slice(&(network_id.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(network_id);
+        bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        if self.version >= ATTESTATION_VERSION_MILLIS {
+            bytes.extend_from_slice(&self.timestamp_millis.unwrap_or(u16::MAX).to_le_bytes());
+        }
+        if let Some(leaf_count) = self.leaf_count {
+            bytes.extend_from_slice(&leaf_count.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Legacy v0 encoding, kept so that existing signatures still verify
+    fn to_legacy_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
         bytes.extend_from_slice(&self.hash);
         bytes.extend_from_slice(&self.timestamp.to_le_bytes());
@@ -48,12 +161,20 @@ impl fmt::Display for Attestation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Attestation(hash={}, ts={}, net={}, seq={})",
+            "Attestation(hash={}, ts={}, net={}, seq={}, v={}",
             hex::encode(self.hash),
-            self.timestamp,
+            match (self.timestamp_millis, self.timestamp_ms()) {
+                (Some(_), Some(timestamp_ms)) => format_timestamp_ms(timestamp_ms),
+                _ => self.timestamp.to_string(),
+            },
             self.network_id,
-            self.sequence
-        )
+            self.sequence,
+            self.version
+        )?;
+        match self.leaf_count {
+            Some(leaf_count) => write!(f, ", leaves={})", leaf_count),
+            None => write!(f, ")"),
+        }
     }
 }
 
@@ -74,6 +195,10 @@ pub struct SignedAttestation {
 
     /// Signatures (multi-sig or aggregated)
     pub signatures: crate::signature_scheme::AttestationSignatures,
+
+    /// Each signing witness's own clock reading, bounding the attestation time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_readings: Vec<TimeReading>,
 }
 
 impl SignedAttestation {
@@ -82,6 +207,7 @@ impl SignedAttestation {
         Self {
             attestation,
             signatures: crate::signature_scheme::AttestationSignatures::new_multisig(),
+            time_readings: Vec::new(),
         }
     }
 
@@ -96,6 +222,16 @@ impl SignedAttestation {
             signatures: crate::signature_scheme::AttestationSignatures::new_aggregated(
                 signature, signers,
             ),
+            time_readings: Vec::new(),
+        }
+    }
+
+    /// Create new attestation with a threshold BLS group signature
+    pub fn new_with_threshold(attestation: Attestation, signature: Vec<u8>) -> Self {
+        Self {
+            attestation,
+            signatures: crate::signature_scheme::AttestationSignatures::new_threshold(signature),
+            time_readings: Vec::new(),
         }
     }
 
@@ -113,6 +249,50 @@ impl SignedAttestation {
     pub fn is_aggregated(&self) -> bool {
         self.signatures.is_aggregated()
     }
+
+    /// Check if using a threshold group signature
+    pub fn is_threshold(&self) -> bool {
+        self.signatures.is_threshold()
+    }
+
+    /// Earliest and latest witness clock readings (Unix milliseconds)
+    ///
+    /// Like an RFC 3161 accuracy field: the attestation was made between these
+    /// times according to the witnesses' own clocks. Only meaningful once the
+    /// readings have been verified.
+    pub fn time_interval(&self) -> Option<(u64, u64)> {
+        let earliest = self.time_readings.iter().map(|r| r.time_ms).min()?;
+        let latest = self.time_readings.iter().map(|r| r.time_ms).max()?;
+        Some((earliest, latest))
+    }
+}
+
+/// A witness's clock reading when it signed an attestation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeReading {
+    pub witness_id: String,
+
+    /// The witness's clock when it signed (Unix milliseconds)
+    pub time_ms: u64,
+
+    /// Witness signature over the reading and the attestation
+    pub signature: Vec<u8>,
+}
+
+impl TimeReading {
+    /// Canonical bytes covered by the reading's signature:
+    /// domain tag || time_ms (u64 LE) || attestation bytes
+    pub fn message(attestation: &Attestation, time_ms: u64) -> Vec<u8> {
+        let mut bytes = TIME_READING_DOMAIN_TAG.to_vec();
+        bytes.extend_from_slice(&time_ms.to_le_bytes());
+        bytes.extend_from_slice(&attestation.to_bytes());
+        bytes
+    }
+}
+
+/// Format Unix milliseconds as seconds with a millisecond fraction
+pub fn format_timestamp_ms(time_ms: u64) -> String {
+    format!("{}.{:03}", time_ms / 1000, time_ms % 1000)
 }
 
 /// Information about a witness node
@@ -121,11 +301,126 @@ pub struct WitnessInfo {
     /// Unique identifier for this witness
     pub id: String,
 
-    /// Ed25519 public key (hex encoded)
+    /// Public key (hex encoded): Ed25519, BLS, or the BLS share public key for threshold_bls
     pub pubkey: String,
 
     /// HTTP endpoint for this witness
     pub endpoint: String,
+
+    /// Shamir share index (threshold_bls only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_index: Option<u32>,
+
+    /// Proof of possession of the BLS secret key (hex encoded, required for bls)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pop: Option<String>,
+
+    /// Unix time `pubkey` became active; earlier attestations use `previous_keys`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_activated_at: Option<u64>,
+
+    /// Keys this witness has rotated away from, kept to verify older attestations
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_keys: Vec<RetiredKey>,
+}
+
+/// A witness key that has been rotated out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredKey {
+    /// Public key (hex encoded)
+    pub pubkey: String,
+
+    /// Unix time the key became active (unbounded if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activated_at: Option<u64>,
+
+    /// Unix time the key stopped being valid (exclusive)
+    pub retired_at: u64,
+
+    /// Proof of possession (bls only, hex encoded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pop: Option<String>,
+}
+
+impl WitnessInfo {
+    /// Public key that was valid at `timestamp`, if any
+    pub fn pubkey_at(&self, timestamp: u64) -> Option<&str> {
+        if self.key_activated_at.is_none_or(|activated| timestamp >= activated) {
+            return Some(&self.pubkey);
+        }
+
+        self.previous_keys
+            .iter()
+            .find(|key| key.activated_at.unwrap_or(0) <= timestamp && timestamp < key.retired_at)
+            .map(|key| key.pubkey.as_str())
+    }
+
+    /// Check that key validity windows are well-formed and do not overlap
+    pub fn validate_key_history(&self) -> crate::Result<()> {
+        if self.previous_keys.is_empty() {
+            return Ok(());
+        }
+
+        let invalid = |reason: &str| {
+            crate::WitnessError::InvalidPublicKey(format!(
+                "Witness {} key history: {}",
+                self.id, reason
+            ))
+        };
+
+        let activated = self
+            .key_activated_at
+            .ok_or_else(|| invalid("key_activated_at is required with previous_keys"))?;
+
+        let mut windows: Vec<(u64, u64)> = self
+            .previous_keys
+            .iter()
+            .map(|key| (key.activated_at.unwrap_or(0), key.retired_at))
+            .collect();
+        windows.push((activated, u64::MAX));
+        windows.sort();
+
+        for window in &windows {
+            if window.0 >= window.1 {
+                return Err(invalid("a key retires before it activates"));
+            }
+        }
+
+        for pair in windows.windows(2) {
+            if pair[0].1 > pair[1].0 {
+                return Err(invalid("validity windows overlap"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the BLS proof of possession of every key in the witness's history
+    pub fn verify_pop(&self) -> crate::Result<()> {
+        let keys = std::iter::once((&self.pubkey, &self.pop))
+            .chain(self.previous_keys.iter().map(|key| (&key.pubkey, &key.pop)));
+
+        for (pubkey, pop) in keys {
+            let pop = pop.as_deref().ok_or_else(|| {
+                crate::WitnessError::InvalidPublicKey(format!(
+                    "Witness {} has no BLS proof of possession for key {}",
+                    self.id, pubkey
+                ))
+            })?;
+
+            let public_key = crate::decode_bls_public_key(pubkey)?;
+            let pop = hex::decode(pop).map_err(|_| crate::WitnessError::InvalidSignature)?;
+
+            crate::verify_bls_pop(&public_key, &pop).map_err(|_| {
+                crate::WitnessError::InvalidPublicKey(format!(
+                    "Invalid BLS proof of possession for witness {}",
+                    self.id
+                ))
+            })?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Network configuration
@@ -140,10 +435,14 @@ pub struct NetworkConfig {
     /// Minimum number of signatures required
     pub threshold: usize,
 
-    /// Signature scheme (ed25519 or bls)
+    /// Signature scheme (ed25519, bls or threshold_bls)
     #[serde(default)]
     pub signature_scheme: crate::signature_scheme::SignatureScheme,
 
+    /// Group public key for threshold_bls (hex encoded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_public_key: Option<String>,
+
     /// Federation configuration (Phase 2)
     #[serde(default)]
     pub federation: crate::federation::FederationConfig,
@@ -155,9 +454,19 @@ pub struct NetworkConfig {
     /// Deprecated: Use federation.peer_networks instead
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub federation_peers: Vec<String>,
+
+    /// Accept legacy v0 attestations (no domain separation) timestamped before
+    /// this Unix time. Unset, v0 attestations are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_encoding_until: Option<u64>,
 }
 
 impl NetworkConfig {
+    /// Whether a v0 attestation with this timestamp is accepted
+    pub fn accepts_legacy_at(&self, timestamp: u64) -> bool {
+        self.legacy_encoding_until.is_some_and(|cutoff| timestamp < cutoff)
+    }
+
     pub fn validate(&self) -> crate::Result<()> {
         if self.witnesses.is_empty() {
             return Err(crate::WitnessError::InvalidPublicKey(
@@ -172,6 +481,49 @@ impl NetworkConfig {
             });
         }
 
+        for witness in &self.witnesses {
+            witness.validate_key_history()?;
+        }
+
+        if self.signature_scheme == crate::signature_scheme::SignatureScheme::BLS {
+            for witness in &self.witnesses {
+                witness.verify_pop()?;
+            }
+        }
+
+        if self.signature_scheme == crate::signature_scheme::SignatureScheme::ThresholdBLS {
+            let group_public_key = self.group_public_key.as_deref().ok_or_else(|| {
+                crate::WitnessError::InvalidPublicKey(
+                    "threshold_bls requires a group_public_key".to_string(),
+                )
+            })?;
+            crate::decode_bls_public_key(group_public_key)?;
+
+            let mut indices = Vec::new();
+            for witness in &self.witnesses {
+                match witness.share_index {
+                    Some(index) if index > 0 && !indices.contains(&index) => indices.push(index),
+                    _ => {
+                        return Err(crate::WitnessError::InvalidPublicKey(format!(
+                            "Witness {} needs a unique, non-zero share_index",
+                            witness.id
+                        )))
+                    }
+                }
+            }
+        }
+
+        if self.federation.enabled {
+            for peer in &self.federation.peer_networks {
+                if !peer.has_keys() {
+                    return Err(crate::WitnessError::InvalidPublicKey(format!(
+                        "No witness keys configured for peer network {}",
+                        peer.id
+                    )));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -229,10 +581,83 @@ pub struct FreebirdConfig {
     pub consume_tokens: bool,
 }
 
+/// Request to timestamp many hashes under one signed merkle root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTimestampRequest {
+    /// SHA-256 hashes to timestamp (hex encoded)
+    pub hashes: Vec<String>,
+
+    /// Optional Freebird token for Sybil resistance (one per batch)
+    #[serde(default)]
+    pub freebird_token: Option<FreebirdToken>,
+}
+
 /// Response from successful timestamp request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampResponse {
     pub attestation: SignedAttestation,
+
+    /// Proof that the hash is a leaf of the attested merkle root (batch timestamps only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inclusion_proof: Option<MerkleProof>,
+}
+
+impl TimestampResponse {
+    /// Response for a hash the witnesses signed directly
+    pub fn new(attestation: SignedAttestation) -> Self {
+        Self {
+            attestation,
+            inclusion_proof: None,
+        }
+    }
+
+    /// The hash this response timestamps
+    pub fn hash(&self) -> [u8; 32] {
+        match &self.inclusion_proof {
+            Some(proof) => proof.leaf,
+            None => self.attestation.attestation.hash,
+        }
+    }
+
+    /// Check that the inclusion proof (if any) leads to the attested root
+    ///
+    /// This does not check the witness signatures on the attestation.
+    pub fn verify_inclusion(&self) -> crate::Result<()> {
+        verify_inclusion_proof(self.inclusion_proof.as_ref(), &self.attestation.attestation)
+    }
+}
+
+/// Response from a batch timestamp request, in request order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTimestampResponse {
+    pub timestamps: Vec<TimestampResponse>,
+}
+
+/// Check that a batch inclusion proof leads to an attestation's hash
+pub fn verify_inclusion_proof(proof: Option<&MerkleProof>, attestation: &Attestation) -> crate::Result<()> {
+    let Some(proof) = proof else {
+        return Ok(());
+    };
+
+    if proof.root != attestation.hash {
+        return Err(crate::WitnessError::InvalidProof(
+            "inclusion proof root does not match attestation hash".to_string(),
+        ));
+    }
+
+    if attestation.leaf_count.is_some_and(|leaf_count| proof.index as u64 >= leaf_count) {
+        return Err(crate::WitnessError::InvalidProof(
+            "inclusion proof index is outside the attested leaf count".to_string(),
+        ));
+    }
+
+    if !proof.verify() {
+        return Err(crate::WitnessError::InvalidProof(
+            "inclusion proof does not lead to attested root".to_string(),
+        ));
+    }
+
+    Ok(())
 }
 
 /// Request to verify an attestation
@@ -256,11 +681,85 @@ pub struct SignRequest {
     pub attestation: Attestation,
 }
 
+/// Internal request from gateway to witness to sign a merkle root over many hashes
+///
+/// The attestation's `hash` is the root and its `leaf_count` must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignRootRequest {
+    pub attestation: Attestation,
+}
+
 /// Response from witness with signature
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignResponse {
     pub witness_id: String,
     pub signature: Vec<u8>,
+
+    /// The witness's signed clock reading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_reading: Option<TimeReading>,
+}
+
+/// An attestation recorded in a witness's signing log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningLogEntry {
+    pub sequence: u64,
+
+    /// Attested hash (hex encoded)
+    pub hash: String,
+
+    pub timestamp: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_millis: Option<u16>,
+
+    /// Leaf count of a root attestation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_count: Option<u64>,
+}
+
+impl SigningLogEntry {
+    /// Timestamp in Unix milliseconds, or `None` if it does not fit in a `u64`
+    pub fn timestamp_ms(&self) -> Option<u64> {
+        checked_timestamp_ms(self.timestamp, self.timestamp_millis)
+    }
+}
+
+/// Unix seconds plus an optional millisecond part, in Unix milliseconds.
+/// Timestamps come from requests, so this must not overflow.
+pub fn checked_timestamp_ms(timestamp: u64, millis: Option<u16>) -> Option<u64> {
+    timestamp
+        .checked_mul(1000)
+        .and_then(|ms| ms.checked_add(millis.unwrap_or(0) as u64))
+}
+
+impl From<&Attestation> for SigningLogEntry {
+    fn from(attestation: &Attestation) -> Self {
+        Self {
+            sequence: attestation.sequence,
+            hash: hex::encode(attestation.hash),
+            timestamp: attestation.timestamp,
+            timestamp_millis: attestation.timestamp_millis,
+            leaf_count: attestation.leaf_count,
+        }
+    }
+}
+
+/// Head of a witness's signing log, published so others can detect a forked history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningLogHead {
+    pub witness_id: String,
+    pub network_id: String,
+
+    /// Number of attestations signed
+    pub entries: u64,
+
+    /// Entry with the highest sequence signed so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest: Option<SigningLogEntry>,
+
+    /// Hash chain over every entry in signing order (hex encoded)
+    pub log_hash: String,
 }
 
 #[cfg(test)]
@@ -274,6 +773,9 @@ mod tests {
             timestamp: 1700000000,
             network_id: "test-network".to_string(),
             sequence: 42,
+            version: ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
         };
 
         // Serialize to JSON
@@ -292,6 +794,9 @@ mod tests {
             timestamp: 1700000000,
             network_id: "test".to_string(),
             sequence: 1,
+            version: ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
         };
 
         let bytes1 = attestation.to_bytes();
@@ -301,6 +806,70 @@ mod tests {
         assert!(!bytes1.is_empty());
     }
 
+    #[test]
+    fn test_attestation_to_bytes_domain_separated() {
+        let attestation = Attestation::new([1u8; 32], "test".to_string(), 1);
+        let bytes = attestation.to_bytes();
+
+        assert!(bytes.starts_with(ATTESTATION_DOMAIN_TAG));
+        assert_eq!(bytes[ATTESTATION_DOMAIN_TAG.len()], ATTESTATION_VERSION);
+
+        // Network ID is length-prefixed
+        let offset = ATTESTATION_DOMAIN_TAG.len() + 1 + 32 + 8;
+        assert_eq!(&bytes[offset..offset + 4], &4u32.to_le_bytes());
+
+        let mut legacy = attestation.clone();
+        legacy.version = ATTESTATION_VERSION_LEGACY;
+        assert_ne!(legacy.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_millis_only_from_millis_version() {
+        let attestation = Attestation::new([1u8; 32], "test".to_string(), 1);
+        assert!(attestation.timestamp_millis.is_some());
+        assert!(attestation.has_valid_millis());
+
+        // Version 1 has no millisecond field, so it neither carries nor signs one
+        let mut v1 = attestation.clone();
+        v1.version = ATTESTATION_VERSION_MILLIS - 1;
+        assert!(!v1.has_valid_millis());
+        assert_eq!(v1.to_bytes().len(), attestation.to_bytes().len() - 2);
+
+        v1.timestamp_millis = None;
+        assert!(v1.has_valid_millis());
+    }
+
+    #[test]
+    fn test_root_attestation_binds_leaf_count() {
+        let attestation = Attestation::new([1u8; 32], "test".to_string(), 1);
+        let root = Attestation::new_root([1u8; 32], 3, "test".to_string(), 1);
+        let bytes = root.to_bytes();
+
+        assert!(bytes.starts_with(ROOT_ATTESTATION_DOMAIN_TAG));
+        assert_ne!(bytes, attestation.to_bytes());
+        assert_ne!(bytes, Attestation::new_root([1u8; 32], 4, "test".to_string(), 1).to_bytes());
+
+        let leaves: Vec<[u8; 32]> = (0..3).map(|i| [i; 32]).collect();
+        let tree = crate::MerkleTree::new(leaves);
+        let root = Attestation::new_root(tree.root(), 3, "test".to_string(), 1);
+        let mut proof = tree.proofs().pop().unwrap();
+        assert!(verify_inclusion_proof(Some(&proof), &root).is_ok());
+
+        // A proof for a leaf past the attested count is refused
+        proof.index = 3;
+        assert!(verify_inclusion_proof(Some(&proof), &root).is_err());
+    }
+
+    #[test]
+    fn test_attestation_without_version_is_legacy() {
+        let json = r#"{"hash":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],
+            "timestamp":1700000000,"network_id":"test","sequence":1}"#;
+        let attestation: Attestation = serde_json::from_str(json).unwrap();
+
+        assert!(attestation.is_legacy());
+        assert!(!serde_json::to_string(&attestation).unwrap().contains("version"));
+    }
+
     #[test]
     fn test_signed_attestation_with_multisig() {
         let attestation = Attestation {
@@ -308,6 +877,9 @@ mod tests {
             timestamp: 1700000000,
             network_id: "test".to_string(),
             sequence: 1,
+            version: ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
         };
 
         let mut signed = SignedAttestation::new(attestation);
@@ -332,6 +904,9 @@ mod tests {
             timestamp: 1700000000,
             network_id: "test".to_string(),
             sequence: 1,
+            version: ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
         };
 
         let signed = SignedAttestation::new_with_aggregated(
@@ -360,12 +935,18 @@ mod tests {
                 id: "w1".to_string(),
                 pubkey: "abc123".to_string(),
                 endpoint: "http://localhost:3001".to_string(),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
             }],
             threshold: 1,
             signature_scheme: Default::default(),
+            group_public_key: None,
             federation: Default::default(),
             external_anchors: Default::default(),
             federation_peers: vec![],
+            legacy_encoding_until: None,
         };
 
         assert!(config.validate().is_ok());
@@ -376,9 +957,11 @@ mod tests {
             witnesses: vec![],
             threshold: 1,
             signature_scheme: Default::default(),
+            group_public_key: None,
             federation: Default::default(),
             external_anchors: Default::default(),
             federation_peers: vec![],
+            legacy_encoding_until: None,
         };
 
         assert!(bad_config.validate().is_err());
@@ -390,17 +973,104 @@ mod tests {
                 id: "w1".to_string(),
                 pubkey: "abc123".to_string(),
                 endpoint: "http://localhost:3001".to_string(),
+                share_index: None,
+                pop: None,
+                key_activated_at: None,
+                previous_keys: vec![],
             }],
             threshold: 5, // Only 1 witness
             signature_scheme: Default::default(),
+            group_public_key: None,
             federation: Default::default(),
             external_anchors: Default::default(),
             federation_peers: vec![],
+            legacy_encoding_until: None,
         };
 
         assert!(bad_threshold.validate().is_err());
     }
 
+    #[test]
+    fn test_network_config_requires_peer_keys() {
+        let witness = WitnessInfo {
+            id: "w1".to_string(),
+            pubkey: "abc123".to_string(),
+            endpoint: "http://localhost:3001".to_string(),
+            share_index: None,
+            pop: None,
+            key_activated_at: None,
+            previous_keys: vec![],
+        };
+
+        let mut config = NetworkConfig {
+            id: "test".to_string(),
+            witnesses: vec![witness.clone()],
+            threshold: 1,
+            signature_scheme: Default::default(),
+            group_public_key: None,
+            federation: crate::federation::FederationConfig {
+                enabled: true,
+                peer_networks: vec![crate::federation::PeerNetworkInfo {
+                    id: "peer".to_string(),
+                    gateway: "http://localhost:8081".to_string(),
+                    min_witnesses: 1,
+                    witnesses: vec![],
+                    signature_scheme: Default::default(),
+                    threshold: 1,
+                }],
+                ..Default::default()
+            },
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+
+        // Peer keys are never fetched from the peer gateway, so they must be pinned
+        assert!(config.validate().is_err());
+
+        config.federation.peer_networks[0].witnesses = vec![witness];
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_network_config_requires_bls_pop() {
+        let keys: Vec<_> = (0..2).map(|_| crate::generate_bls_keypair()).collect();
+
+        let mut config = NetworkConfig {
+            id: "test".to_string(),
+            witnesses: keys
+                .iter()
+                .enumerate()
+                .map(|(i, (sk, pk))| WitnessInfo {
+                    id: format!("w{}", i + 1),
+                    pubkey: crate::encode_bls_public_key(pk),
+                    endpoint: String::new(),
+                    share_index: None,
+                    pop: Some(hex::encode(crate::generate_bls_pop(sk))),
+                    key_activated_at: None,
+                    previous_keys: vec![],
+                })
+                .collect(),
+            threshold: 2,
+            signature_scheme: crate::SignatureScheme::BLS,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: vec![],
+            legacy_encoding_until: None,
+        };
+
+        assert!(config.validate().is_ok());
+
+        // A proof copied from another witness does not verify
+        config.witnesses[1].pop = config.witnesses[0].pop.clone();
+        assert!(config.validate().is_err());
+
+        // Nor does a missing one
+        config.witnesses[1].pop = None;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_find_witness() {
         let config = NetworkConfig {
@@ -410,18 +1080,28 @@ mod tests {
                     id: "w1".to_string(),
                     pubkey: "key1".to_string(),
                     endpoint: "http://localhost:3001".to_string(),
+                    share_index: None,
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
                 },
                 WitnessInfo {
                     id: "w2".to_string(),
                     pubkey: "key2".to_string(),
                     endpoint: "http://localhost:3002".to_string(),
+                    share_index: None,
+                    pop: None,
+                    key_activated_at: None,
+                    previous_keys: vec![],
                 },
             ],
             threshold: 1,
             signature_scheme: Default::default(),
+            group_public_key: None,
             federation: Default::default(),
             external_anchors: Default::default(),
             federation_peers: vec![],
+            legacy_encoding_until: None,
         };
 
         assert!(config.find_witness("w1").is_some());
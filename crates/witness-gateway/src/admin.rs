@@ -13,7 +13,9 @@ use serde::Serialize;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::circuit_breaker::{CircuitState, WitnessHealth};
 use crate::metrics;
+use crate::signature_audit::SignatureAudit;
 use crate::storage::Storage;
 use witness_core::NetworkConfig;
 
@@ -22,14 +24,23 @@ use witness_core::NetworkConfig;
 pub struct AdminState {
     pub config: Arc<NetworkConfig>,
     pub storage: Arc<Storage>,
+    pub signature_audit: Arc<SignatureAudit>,
+    pub witness_health: Arc<WitnessHealth>,
     pub start_time: SystemTime,
 }
 
 impl AdminState {
-    pub fn new(config: Arc<NetworkConfig>, storage: Arc<Storage>) -> Self {
+    pub fn new(
+        config: Arc<NetworkConfig>,
+        storage: Arc<Storage>,
+        signature_audit: Arc<SignatureAudit>,
+        witness_health: Arc<WitnessHealth>,
+    ) -> Self {
         Self {
             config,
             storage,
+            signature_audit,
+            witness_health,
             start_time: SystemTime::now(),
         }
     }
@@ -112,6 +123,11 @@ struct WitnessStatus {
     endpoint: String,
     status: String,
     latency_ms: Option<u64>,
+    circuit: CircuitState,
+    valid_signatures: u64,
+    invalid_signatures: u64,
+    last_invalid_at: Option<u64>,
+    repeat_offender: bool,
 }
 
 async fn witnesses_handler(State(state): State<AdminState>) -> impl IntoResponse {
@@ -119,12 +135,18 @@ async fn witnesses_handler(State(state): State<AdminState>) -> impl IntoResponse
 
     for witness in &state.config.witnesses {
         let (status, latency_ms) = check_witness_health(&witness.endpoint).await;
+        let record = state.signature_audit.record(&witness.id);
 
         witnesses.push(WitnessStatus {
             id: witness.id.clone(),
             endpoint: witness.endpoint.clone(),
             status,
             latency_ms,
+            circuit: state.witness_health.state(&witness.id),
+            valid_signatures: record.valid_signatures,
+            invalid_signatures: record.invalid_signatures,
+            last_invalid_at: record.last_invalid_at,
+            repeat_offender: record.is_repeat_offender(now_secs()),
         });
     }
 
@@ -309,6 +331,7 @@ fn generate_dashboard_html(config: &NetworkConfig) -> String {
         .dot.loading {{ background: var(--warning); animation: pulse 1s infinite; }}
         @keyframes pulse {{ 0%, 100% {{ opacity: 1; }} 50% {{ opacity: 0.5; }} }}
         .latency {{ color: var(--text-dim); font-size: 0.75rem; }}
+        .offender {{ color: var(--error); font-size: 0.75rem; }}
         table {{
             width: 100%;
             border-collapse: collapse;
@@ -494,6 +517,8 @@ fn generate_dashboard_html(config: &NetworkConfig) -> String {
                         <span class="dot ${{w.status === 'online' ? 'online' : 'offline'}}"></span>
                         <span>${{w.id}}</span>
                         ${{w.latency_ms ? `<span class="latency">${{w.latency_ms}}ms</span>` : ''}}
+                        ${{w.circuit !== 'closed' ? `<span class="latency">circuit ${{w.circuit.replace('_', '-')}}</span>` : ''}}
+                        ${{w.repeat_offender ? `<span class="offender" title="Repeated invalid signatures">⚠ ${{w.invalid_signatures}} invalid</span>` : ''}}
                     </div>
                 `).join('');
             }} catch (e) {{
@@ -0,0 +1,68 @@
+//! Micro-batching of timestamp requests
+//!
+//! In aggregation mode, single-hash timestamp requests are held for a short
+//! window so witnesses sign one root attestation over the whole micro-batch
+//! instead of one attestation per hash. Each request then gets back the root
+//! attestation with its own inclusion proof.
+
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How long and how large a micro-batch may grow
+#[derive(Debug, Clone, Copy)]
+pub struct AggregationConfig {
+    /// How long the first request in a micro-batch waits for others
+    pub window: Duration,
+
+    /// Flush as soon as this many requests are waiting
+    pub max_size: usize,
+}
+
+/// Wait for a request, then gather more until the window closes or the
+/// micro-batch is full
+///
+/// Returns None once every sender is gone.
+pub async fn next_batch<T>(rx: &mut mpsc::Receiver<T>, config: &AggregationConfig) -> Option<Vec<T>> {
+    let first = rx.recv().await?;
+    let deadline = tokio::time::Instant::now() + config.window;
+
+    let mut batch = vec![first];
+    while batch.len() < config.max_size {
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(item)) => batch.push(item),
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    Some(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_next_batch_flushes_on_size_and_window() {
+        let config = AggregationConfig {
+            window: Duration::from_millis(50),
+            max_size: 3,
+        };
+        let (tx, mut rx) = mpsc::channel(16);
+
+        for i in 0..5 {
+            tx.send(i).await.unwrap();
+        }
+
+        // A full micro-batch goes out without waiting for the window
+        let start = std::time::Instant::now();
+        assert_eq!(next_batch(&mut rx, &config).await, Some(vec![0, 1, 2]));
+        assert!(start.elapsed() < config.window);
+
+        // The rest goes out when the window closes
+        assert_eq!(next_batch(&mut rx, &config).await, Some(vec![3, 4]));
+        assert!(start.elapsed() >= config.window);
+
+        drop(tx);
+        assert_eq!(next_batch(&mut rx, &config).await, None);
+    }
+}
@@ -4,6 +4,7 @@ use tokio::time;
 use witness_core::{AttestationBatch, MerkleTree, NetworkConfig};
 
 use crate::anchor_manager::AnchorManager;
+use crate::federation_client::FederationClient;
 use crate::metrics;
 use crate::storage::Storage;
 
@@ -13,6 +14,7 @@ pub struct BatchManager {
     storage: Arc<Storage>,
     last_batch_time: Arc<tokio::sync::Mutex<u64>>,
     anchor_manager: Option<Arc<AnchorManager>>,
+    federation_client: Option<Arc<FederationClient>>,
 }
 
 impl BatchManager {
@@ -27,6 +29,7 @@ impl BatchManager {
             storage,
             last_batch_time: Arc::new(tokio::sync::Mutex::new(now)),
             anchor_manager: None,
+            federation_client: None,
         }
     }
 
@@ -36,6 +39,12 @@ impl BatchManager {
         self
     }
 
+    /// Set the federation client used to cross-anchor closed batches (must be called before start)
+    pub fn with_federation_client(mut self, federation_client: Arc<FederationClient>) -> Self {
+        self.federation_client = Some(federation_client);
+        self
+    }
+
     /// Start the batch manager background task
     pub fn start(self: Arc<Self>) {
         let batch_period = self.config.federation.batch_period;
@@ -129,6 +138,11 @@ impl BatchManager {
             ..batch
         };
 
+        // Submit to peer networks for cross-anchoring
+        if let Some(federation_client) = &self.federation_client {
+            federation_client.clone().cross_anchor_batch_async(final_batch.clone());
+        }
+
         // Trigger external anchoring if enabled
         if let Some(anchor_manager) = &self.anchor_manager {
             anchor_manager.clone().anchor_batch_async(final_batch.clone());
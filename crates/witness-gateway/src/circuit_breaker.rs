@@ -0,0 +1,263 @@
+//! Per-witness circuit breakers
+//!
+//! A witness that fails several sign requests or health probes in a row is
+//! taken out of rotation (open). Once it has been open for a while, a
+//! successful background probe lets requests through on trial (half-open);
+//! a successful request closes the circuit again, a failure reopens it.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use witness_core::{NetworkConfig, WitnessInfo};
+
+use crate::metrics;
+use crate::witness_client::WitnessClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Healthy; receives sign requests
+    Closed,
+    /// Failing; skipped until a probe succeeds after the cool-down
+    Open,
+    /// Recovering; receives sign requests until the next outcome decides
+    HalfOpen,
+}
+
+/// When to open a circuit and how long to keep it open
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+
+    /// How long an open circuit stays open before probes may close it
+    pub open_duration: Duration,
+
+    /// How often every witness is probed
+    pub probe_interval: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            open_duration: Duration::from_secs(30),
+            probe_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+}
+
+/// Health of every witness in the network, shared by routing and probes
+pub struct WitnessHealth {
+    config: BreakerConfig,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+impl WitnessHealth {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn state(&self, witness_id: &str) -> CircuitState {
+        self.breakers
+            .lock()
+            .unwrap()
+            .get(witness_id)
+            .map_or(CircuitState::Closed, |breaker| breaker.state)
+    }
+
+    /// Witnesses that may be sent sign requests
+    pub fn available<'a>(&self, witnesses: &'a [WitnessInfo]) -> Vec<&'a WitnessInfo> {
+        witnesses
+            .iter()
+            .filter(|witness| self.state(&witness.id) != CircuitState::Open)
+            .collect()
+    }
+
+    /// The witness answered a sign request
+    pub fn record_success(&self, witness_id: &str) {
+        self.update(witness_id, |breaker| {
+            breaker.consecutive_failures = 0;
+            CircuitState::Closed
+        });
+    }
+
+    /// The witness was unreachable, failed or timed out
+    pub fn record_failure(&self, witness_id: &str) {
+        self.record_failure_at(witness_id, Instant::now());
+    }
+
+    fn record_failure_at(&self, witness_id: &str, now: Instant) {
+        let failure_threshold = self.config.failure_threshold;
+        self.update(witness_id, |breaker| {
+            breaker.consecutive_failures += 1;
+            match breaker.state {
+                CircuitState::Closed if breaker.consecutive_failures < failure_threshold => {
+                    CircuitState::Closed
+                }
+                // A failed trial reopens at once, and restarts the cool-down
+                CircuitState::Closed | CircuitState::HalfOpen => {
+                    breaker.opened_at = Some(now);
+                    CircuitState::Open
+                }
+                CircuitState::Open => CircuitState::Open,
+            }
+        });
+    }
+
+    /// Result of a background health probe
+    pub fn record_probe(&self, witness_id: &str, healthy: bool) {
+        self.record_probe_at(witness_id, healthy, Instant::now());
+    }
+
+    fn record_probe_at(&self, witness_id: &str, healthy: bool, now: Instant) {
+        if !healthy {
+            self.record_failure_at(witness_id, now);
+            return;
+        }
+
+        let open_duration = self.config.open_duration;
+        self.update(witness_id, |breaker| match breaker.state {
+            CircuitState::Open
+                if breaker
+                    .opened_at
+                    .is_some_and(|opened_at| now.duration_since(opened_at) >= open_duration) =>
+            {
+                CircuitState::HalfOpen
+            }
+            // Only a sign request closes a half-open circuit
+            state => state,
+        });
+    }
+
+    fn update(&self, witness_id: &str, f: impl FnOnce(&mut Breaker) -> CircuitState) {
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers.entry(witness_id.to_string()).or_default();
+
+        let previous = breaker.state;
+        breaker.state = f(breaker);
+        let state = breaker.state;
+        drop(breakers);
+
+        metrics::set_witness_health(witness_id, state != CircuitState::Open);
+        if state != previous {
+            match state {
+                CircuitState::Open => tracing::warn!("Witness {} circuit opened", witness_id),
+                CircuitState::HalfOpen => tracing::info!("Witness {} circuit half-open, sending trial requests", witness_id),
+                CircuitState::Closed => tracing::info!("Witness {} circuit closed", witness_id),
+            }
+        }
+    }
+
+    /// Probe every witness's health endpoint in the background
+    pub fn start_probes(self: Arc<Self>, network: Arc<NetworkConfig>) {
+        tokio::spawn(async move {
+            let client = WitnessClient::new();
+            let mut interval = tokio::time::interval(self.config.probe_interval);
+            loop {
+                interval.tick().await;
+                let client = &client;
+                let probes = network.witnesses.iter().map(|witness| async move {
+                    (witness, client.health_check(witness).await)
+                });
+                for (witness, healthy) in futures_util::future::join_all(probes).await {
+                    self.record_probe(&witness.id, healthy);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker_transitions() {
+        let health = WitnessHealth::new(BreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(30),
+            probe_interval: Duration::from_secs(10),
+        });
+        let start = Instant::now();
+
+        health.record_failure_at("w1", start);
+        assert_eq!(health.state("w1"), CircuitState::Closed);
+        health.record_failure_at("w1", start);
+        assert_eq!(health.state("w1"), CircuitState::Open);
+
+        // Healthy probes only count after the cool-down
+        health.record_probe_at("w1", true, start + Duration::from_secs(10));
+        assert_eq!(health.state("w1"), CircuitState::Open);
+        health.record_probe_at("w1", true, start + Duration::from_secs(30));
+        assert_eq!(health.state("w1"), CircuitState::HalfOpen);
+
+        // A failed trial reopens and restarts the cool-down
+        health.record_failure_at("w1", start + Duration::from_secs(31));
+        assert_eq!(health.state("w1"), CircuitState::Open);
+        health.record_probe_at("w1", true, start + Duration::from_secs(40));
+        assert_eq!(health.state("w1"), CircuitState::Open);
+        health.record_probe_at("w1", true, start + Duration::from_secs(61));
+        assert_eq!(health.state("w1"), CircuitState::HalfOpen);
+
+        health.record_success("w1");
+        assert_eq!(health.state("w1"), CircuitState::Closed);
+
+        // Failures must be consecutive
+        health.record_failure_at("w1", start);
+        health.record_success("w1");
+        health.record_failure_at("w1", start);
+        assert_eq!(health.state("w1"), CircuitState::Closed);
+
+        // Failed probes open the circuit too
+        health.record_probe_at("w2", false, start);
+        health.record_probe_at("w2", false, start);
+        assert_eq!(health.state("w2"), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_available_witnesses() {
+        let health = WitnessHealth::new(BreakerConfig {
+            failure_threshold: 1,
+            ..BreakerConfig::default()
+        });
+        let witnesses: Vec<WitnessInfo> = ["w1", "w2", "w3"]
+            .iter()
+            .map(|id| serde_json::from_value(serde_json::json!({
+                "id": id,
+                "pubkey": "00",
+                "endpoint": format!("http://{}", id),
+            }))
+            .unwrap())
+            .collect();
+
+        health.record_failure("w2");
+        let available: Vec<&str> = health
+            .available(&witnesses)
+            .iter()
+            .map(|witness| witness.id.as_str())
+            .collect();
+        assert_eq!(available, vec!["w1", "w3"]);
+    }
+}
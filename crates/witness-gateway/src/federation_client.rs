@@ -1,20 +1,36 @@
 use anyhow::{Context, Result};
 use reqwest::Client;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use witness_core::{
     AttestationBatch, CrossAnchor, CrossAnchorRequest, CrossAnchorResponse, NetworkConfig,
     PeerNetworkInfo,
 };
 
+use crate::metrics;
 use crate::storage::Storage;
 
+/// Outcome of an attempt to cross-anchor a batch
+#[derive(Debug)]
+pub enum CrossAnchoring {
+    /// Federation is disabled; nothing was attempted
+    Disabled,
+
+    /// Another task is cross-anchoring this batch; nothing was attempted
+    InFlight,
+
+    /// Enough peer networks have cross-anchored the batch
+    Anchored(Vec<CrossAnchor>),
+}
+
 /// Client for federation operations with peer networks
-#[allow(dead_code)]
 pub struct FederationClient {
     config: Arc<NetworkConfig>,
     storage: Arc<Storage>,
     http_client: Client,
+    /// Batches with a cross-anchoring round in progress
+    in_flight: Mutex<HashSet<u64>>,
 }
 
 impl FederationClient {
@@ -28,46 +44,190 @@ impl FederationClient {
             config,
             storage,
             http_client,
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Number of distinct peer networks that must cross-anchor each batch
+    pub fn required_cross_anchors(&self) -> usize {
+        self.config.federation.required_cross_anchors()
+    }
+
+    /// Cross-anchor a batch in the background
+    pub fn cross_anchor_batch_async(self: Arc<Self>, batch: AttestationBatch) {
+        if !self.config.federation.enabled || self.config.federation.peer_networks.is_empty() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            self.log_attempt(&batch, self.cross_anchor_batch(&batch).await);
+        });
+    }
+
+    /// Start the background task that retries batches below the cross-anchor threshold
+    pub fn start_retry_task(self: Arc<Self>) {
+        let federation = &self.config.federation;
+
+        if !federation.enabled
+            || federation.peer_networks.is_empty()
+            || federation.cross_anchor_retry_interval == 0
+        {
+            return;
+        }
+
+        if self.required_cross_anchors() > federation.peer_networks.len() {
+            tracing::warn!(
+                "cross_anchor_threshold ({}) exceeds the number of peer networks ({}); batches will never be fully cross-anchored",
+                federation.cross_anchor_threshold,
+                federation.peer_networks.len()
+            );
+        }
+
+        let retry_interval = federation.cross_anchor_retry_interval;
+
+        tracing::info!(
+            "Starting cross-anchor retry task with interval: {} seconds",
+            retry_interval
+        );
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(retry_interval));
+            // The first tick completes immediately; skip it so fresh batches get their first attempt
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.retry_pending_batches().await {
+                    tracing::error!("Failed to retry cross-anchoring: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Retry cross-anchoring for recent batches that have not reached the threshold
+    async fn retry_pending_batches(&self) -> Result<()> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let since = now.saturating_sub(self.config.federation.cross_anchor_retry_window);
+
+        let batches = self
+            .storage
+            .get_batches_below_cross_anchor_threshold(since, self.required_cross_anchors())
+            .await?;
+
+        for batch in batches {
+            tracing::info!("Retrying cross-anchoring for batch {}", batch.id);
+
+            self.log_attempt(&batch, self.cross_anchor_batch(&batch).await);
+        }
+
+        Ok(())
+    }
+
+    fn log_attempt(&self, batch: &AttestationBatch, result: Result<CrossAnchoring>) {
+        match result {
+            Ok(CrossAnchoring::Anchored(cross_anchors)) => {
+                tracing::debug!("Batch {} cross-anchored by {} peers", batch.id, cross_anchors.len());
+            }
+            Ok(CrossAnchoring::InFlight) => {
+                tracing::debug!("Skipped batch {}: already being cross-anchored", batch.id);
+            }
+            Ok(CrossAnchoring::Disabled) => {}
+            Err(e) => {
+                tracing::warn!("Cross-anchoring incomplete for batch {}: {}", batch.id, e);
+            }
         }
     }
 
-    /// Submit a batch to all peer networks for cross-anchoring
-    #[allow(dead_code)]
-    pub async fn cross_anchor_batch(&self, batch: &AttestationBatch) -> Result<Vec<CrossAnchor>> {
+    /// Submit a batch to every peer network that has not cross-anchored it yet
+    ///
+    /// Verified cross-anchors are stored as they arrive. Returns all cross-anchors
+    /// for the batch, or an error if fewer than `cross_anchor_threshold` peer
+    /// networks have anchored it. Batches already being cross-anchored by
+    /// another task are skipped.
+    pub async fn cross_anchor_batch(&self, batch: &AttestationBatch) -> Result<CrossAnchoring> {
         if !self.config.federation.enabled {
-            return Ok(Vec::new());
+            return Ok(CrossAnchoring::Disabled);
+        }
+
+        if !self.in_flight.lock().unwrap().insert(batch.id) {
+            return Ok(CrossAnchoring::InFlight);
         }
 
+        let result = self.cross_anchor_batch_inner(batch).await;
+
+        self.in_flight.lock().unwrap().remove(&batch.id);
+
+        result.map(CrossAnchoring::Anchored)
+    }
+
+    async fn cross_anchor_batch_inner(&self, batch: &AttestationBatch) -> Result<Vec<CrossAnchor>> {
+        let mut cross_anchors = self.storage.get_cross_anchors(batch.id as i64).await?;
+
+        let pending: Vec<PeerNetworkInfo> = self
+            .config
+            .federation
+            .peer_networks
+            .iter()
+            .filter(|peer| {
+                !cross_anchors
+                    .iter()
+                    .any(|ca| ca.statement.witnessing_network == peer.id)
+            })
+            .cloned()
+            .collect();
+
         tracing::info!(
             "Submitting batch {} for cross-anchoring to {} peer networks",
             batch.id,
-            self.config.federation.peer_networks.len()
+            pending.len()
         );
 
         let mut tasks = Vec::new();
 
-        for peer in &self.config.federation.peer_networks {
-            let peer = peer.clone();
+        for peer in pending {
             let batch = batch.clone();
             let client = self.http_client.clone();
-
             let task = tokio::spawn(async move {
-                Self::request_cross_anchor(&client, &peer, &batch).await
+                let cross_anchor = Self::request_cross_anchor(&client, &peer, &batch).await?;
+
+                if cross_anchor.statement.batch != batch {
+                    anyhow::bail!("Peer {} anchored a different batch", peer.id);
+                }
+
+                let verified = witness_core::verify_cross_anchor(&cross_anchor, &peer)
+                    .with_context(|| format!("Invalid cross-anchor from peer: {}", peer.id))?;
+
+                tracing::debug!(
+                    "Verified {} signatures on cross-anchor from {}",
+                    verified,
+                    peer.id
+                );
+
+                Ok(cross_anchor)
             });
 
             tasks.push(task);
         }
 
-        // Collect results
-        let mut cross_anchors = Vec::new();
-
+        // Collect results, storing each verified cross-anchor
         for task in tasks {
             match task.await {
                 Ok(Ok(cross_anchor)) => {
                     tracing::info!(
                         "Received cross-anchor from network: {}",
-                        cross_anchor.witnessing_network
+                        cross_anchor.statement.witnessing_network
                     );
+
+                    if let Err(e) = self.storage.store_cross_anchor(&cross_anchor).await {
+                        tracing::error!("Failed to store cross-anchor: {}", e);
+                        continue;
+                    }
+
+                    metrics::record_cross_anchor(&cross_anchor.statement.witnessing_network);
                     cross_anchors.push(cross_anchor);
                 }
                 Ok(Err(e)) => {
@@ -79,24 +239,26 @@ impl FederationClient {
             }
         }
 
-        // Store cross-anchors
-        for cross_anchor in &cross_anchors {
-            if let Err(e) = self.storage.store_cross_anchor(cross_anchor).await {
-                tracing::error!("Failed to store cross-anchor: {}", e);
-            }
-        }
+        let required = self.required_cross_anchors();
 
         tracing::info!(
-            "Received {} cross-anchors for batch {} (threshold: {})",
-            cross_anchors.len(),
+            "Batch {} has {} cross-anchors (threshold: {})",
             batch.id,
-            self.config.federation.cross_anchor_threshold
+            cross_anchors.len(),
+            required
         );
 
+        if cross_anchors.len() < required {
+            anyhow::bail!(
+                "Insufficient cross-anchors: got {}, required {}",
+                cross_anchors.len(),
+                required
+            );
+        }
+
         Ok(cross_anchors)
     }
 
-    #[allow(dead_code)]
     async fn request_cross_anchor(
         client: &Client,
         peer: &PeerNetworkInfo,
@@ -134,3 +296,45 @@ impl FederationClient {
         Ok(cross_anchor_response.cross_anchor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cross_anchor_batch_skips_in_flight() {
+        let config: NetworkConfig = serde_json::from_value(serde_json::json!({
+            "id": "test-net",
+            "witnesses": [],
+            "threshold": 1,
+            "federation": {
+                "enabled": true,
+                "peer_networks": [],
+                "cross_anchor_threshold": 1,
+            },
+        }))
+        .unwrap();
+        let storage = Storage::new("sqlite::memory:").await.unwrap();
+        storage.migrate().await.unwrap();
+        let client = FederationClient::new(Arc::new(config), Arc::new(storage));
+
+        let batch = AttestationBatch {
+            id: 1,
+            network_id: "test-net".to_string(),
+            merkle_root: [1u8; 32],
+            period_start: 1700000000,
+            period_end: 1700003600,
+            attestation_count: 1,
+        };
+
+        client.in_flight.lock().unwrap().insert(batch.id);
+        assert!(matches!(
+            client.cross_anchor_batch(&batch).await.unwrap(),
+            CrossAnchoring::InFlight
+        ));
+
+        // Once the other attempt is done, the batch is tried again
+        client.in_flight.lock().unwrap().remove(&batch.id);
+        assert!(client.cross_anchor_batch(&batch).await.is_err());
+    }
+}
@@ -1,30 +1,36 @@
 mod admin;
+mod aggregator;
 mod anchor_manager;
 mod anchor_providers;
 mod batch_manager;
+mod circuit_breaker;
 mod federation_client;
 mod freebird;
+mod manifests;
 mod metrics;
 mod server;
+mod signature_audit;
 mod storage;
 mod witness_client;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing_subscriber;
 use witness_core::NetworkConfig;
 
 use admin::AdminState;
+use aggregator::AggregationConfig;
 use anchor_manager::AnchorManager;
 use batch_manager::BatchManager;
+use circuit_breaker::{BreakerConfig, WitnessHealth};
 use federation_client::FederationClient;
 use freebird::FreebirdClient;
-use server::GatewayServer;
+use server::{GatewayServer, SignatureCollection};
+use signature_audit::SignatureAudit;
 use storage::Storage;
-use witness_client::WitnessClient;
 
 #[derive(Parser, Debug)]
 #[command(name = "witness-gateway")]
@@ -45,6 +51,48 @@ struct Args {
     /// Enable admin dashboard UI at /admin
     #[arg(long, default_value = "false")]
     admin_ui: bool,
+
+    /// Directory of signed network manifests to serve at /v1/manifest
+    #[arg(long)]
+    manifests: Option<PathBuf>,
+
+    /// File holding the Ed25519 private key (hex) that authenticates sign requests to witnesses
+    #[arg(long, env = "WITNESS_GATEWAY_AUTH_KEY")]
+    auth_key: Option<PathBuf>,
+
+    /// Aggregate timestamp requests arriving within this many milliseconds,
+    /// so witnesses sign one merkle root per micro-batch
+    #[arg(long)]
+    aggregate_window_ms: Option<u64>,
+
+    /// Largest micro-batch in aggregation mode
+    #[arg(long, default_value = "1000", requires = "aggregate_window_ms")]
+    aggregate_max_size: usize,
+
+    /// Give up on a witness that has not answered a sign request within this many milliseconds
+    #[arg(long, default_value = "10000")]
+    witness_timeout_ms: u64,
+
+    /// Fail a timestamp request if threshold signatures are not in within this many milliseconds
+    #[arg(long, default_value = "10000")]
+    signature_deadline_ms: u64,
+
+    /// Once the threshold is met, keep waiting for the other witnesses in the
+    /// background and attach their signatures to the stored attestation
+    #[arg(long)]
+    attach_late_signatures: bool,
+
+    /// Take a witness out of rotation after this many consecutive failures
+    #[arg(long, default_value = "3")]
+    circuit_failures: u32,
+
+    /// Keep a failing witness out of rotation for at least this many seconds
+    #[arg(long, default_value = "30")]
+    circuit_open_secs: u64,
+
+    /// Probe every witness's health endpoint this often (seconds)
+    #[arg(long, default_value = "10")]
+    health_probe_secs: u64,
 }
 
 #[tokio::main]
@@ -94,6 +142,39 @@ async fn main() -> Result<()> {
             network_config.external_anchors.minimum_required);
     }
 
+    // Load and verify the signed manifest chain
+    let manifests = match &args.manifests {
+        Some(dir) => {
+            let chain = manifests::load_manifest_chain(dir, &network_config)?;
+            tracing::info!(
+                "Loaded {} signed manifests (latest version {})",
+                chain.len(),
+                chain.last().map_or(0, |m| m.manifest.version)
+            );
+            chain
+        }
+        None => {
+            tracing::warn!("No signed manifests configured; clients must trust /v1/config");
+            Vec::new()
+        }
+    };
+
+    // Load the key witnesses use to recognise this gateway
+    let auth_key = match &args.auth_key {
+        Some(path) => {
+            let key = load_auth_key(path)?;
+            tracing::info!(
+                "Authenticating sign requests with gateway key {}",
+                witness_core::encode_public_key(&key.verifying_key())
+            );
+            Some(key)
+        }
+        None => {
+            tracing::warn!("No gateway auth key configured; sign requests are unauthenticated");
+            None
+        }
+    };
+
     // Initialize storage
     // For sqlx-sqlite: sqlite:path?mode=rwc (read-write-create)
     // The ?mode=rwc tells SQLite to create the database file if it doesn't exist
@@ -113,25 +194,43 @@ async fn main() -> Result<()> {
         storage.clone(),
     ).await);
 
-    // Initialize batch manager (Phase 2) with anchor manager
-    let batch_manager = Arc::new(
-        BatchManager::new(network_config.clone(), storage.clone())
-            .with_anchor_manager(anchor_manager.clone())
-    );
-
     // Initialize federation client (Phase 2)
     let federation_client = Arc::new(FederationClient::new(
         network_config.clone(),
         storage.clone(),
     ));
 
-    // Start batch manager background task
+    // Initialize batch manager (Phase 2) with anchor manager and federation client
+    let batch_manager = Arc::new(
+        BatchManager::new(network_config.clone(), storage.clone())
+            .with_anchor_manager(anchor_manager.clone())
+            .with_federation_client(federation_client.clone())
+    );
+
+    // Start batch manager and cross-anchor retry background tasks
     batch_manager.clone().start();
+    federation_client.clone().start_retry_task();
+
+    // Signature verification results, shared with the admin witness list
+    let signature_audit = Arc::new(SignatureAudit::default());
+
+    // Per-witness circuit breakers, driven by sign requests and health probes
+    let witness_health = Arc::new(WitnessHealth::new(BreakerConfig {
+        failure_threshold: args.circuit_failures.max(1),
+        open_duration: Duration::from_secs(args.circuit_open_secs),
+        probe_interval: Duration::from_secs(args.health_probe_secs.max(1)),
+    }));
+    witness_health.clone().start_probes(network_config.clone());
 
     // Create admin state if admin UI is enabled
     let admin_state = if args.admin_ui {
         tracing::info!("Admin dashboard enabled at /admin");
-        Some(AdminState::new(network_config.clone(), storage.clone()))
+        Some(AdminState::new(
+            network_config.clone(),
+            storage.clone(),
+            signature_audit.clone(),
+            witness_health.clone(),
+        ))
     } else {
         None
     };
@@ -163,20 +262,6 @@ async fn main() -> Result<()> {
         }
     });
 
-    // Witness health checker (every 30 seconds)
-    let health_config = network_config.clone();
-    tokio::spawn(async move {
-        let client = WitnessClient::new();
-        let mut interval = tokio::time::interval(Duration::from_secs(30));
-        loop {
-            interval.tick().await;
-            for witness in &health_config.witnesses {
-                let healthy = client.health_check(witness).await;
-                metrics::set_witness_health(&witness.id, healthy);
-            }
-        }
-    });
-
     // Start server
     let server = GatewayServer::new(
         network_config,
@@ -185,8 +270,40 @@ async fn main() -> Result<()> {
         federation_client,
         freebird_client,
         metrics_handle,
-    );
+        signature_audit,
+    )
+    .with_manifests(manifests)
+    .with_witness_health(witness_health)
+    .with_signature_collection(SignatureCollection {
+        witness_timeout: Duration::from_millis(args.witness_timeout_ms),
+        deadline: Duration::from_millis(args.signature_deadline_ms),
+        attach_late: args.attach_late_signatures,
+    });
+    let server = match auth_key {
+        Some(key) => server.with_auth_key(key),
+        None => server,
+    };
+    let server = match args.aggregate_window_ms {
+        Some(window_ms) => server.with_aggregation(AggregationConfig {
+            window: Duration::from_millis(window_ms),
+            max_size: args.aggregate_max_size.max(1),
+        }),
+        None => server,
+    };
     server.run(args.port, admin_state).await?;
 
     Ok(())
 }
+
+/// Read a hex-encoded Ed25519 private key from a file
+fn load_auth_key(path: &Path) -> Result<ed25519_dalek::SigningKey> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read gateway auth key: {:?}", path))?;
+
+    let bytes: [u8; 32] = hex::decode(content.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| format!("Gateway auth key must be 32 hex-encoded bytes: {:?}", path))?;
+
+    Ok(ed25519_dalek::SigningKey::from_bytes(&bytes))
+}
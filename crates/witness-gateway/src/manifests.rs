@@ -0,0 +1,52 @@
+//! Loading the signed network manifest chain served at /v1/manifest
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use witness_core::{NetworkConfig, SignedManifest};
+
+/// Load every manifest in `dir`, verify the chain, and check the latest one
+/// matches the witness set the gateway is configured with
+pub fn load_manifest_chain(dir: &Path, config: &NetworkConfig) -> Result<Vec<SignedManifest>> {
+    let mut chain = Vec::new();
+
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read manifest directory: {:?}", dir))?
+    {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read manifest: {:?}", path))?;
+        let manifest: SignedManifest = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse manifest: {:?}", path))?;
+        chain.push(manifest);
+    }
+
+    chain.sort_by_key(|m| m.manifest.version);
+
+    witness_core::verify_manifest_chain(&chain).context("Invalid manifest chain")?;
+
+    let latest = &chain.last().unwrap().manifest.network;
+    let matches = latest.id == config.id
+        && latest.threshold == config.threshold
+        && latest.signature_scheme == config.signature_scheme
+        && latest.group_public_key == config.group_public_key
+        && latest.witnesses.len() == config.witnesses.len()
+        && config.witnesses.iter().all(|witness| {
+            latest
+                .witnesses
+                .iter()
+                .any(|m| m.id == witness.id && m.pubkey == witness.pubkey)
+        });
+
+    if !matches {
+        anyhow::bail!(
+            "Latest manifest (version {}) does not match the witness set in the network config",
+            chain.last().unwrap().manifest.version
+        );
+    }
+
+    Ok(chain)
+}
@@ -16,11 +16,41 @@ pub fn record_attestation() {
     counter!("witness_attestations_total").increment(1);
 }
 
+/// Record hashes timestamped together under one signed root
+pub fn record_batch_timestamp(hashes: usize) {
+    counter!("witness_batch_timestamp_hashes_total").increment(hashes as u64);
+}
+
+/// Record the number of distinct hashes in a flushed micro-batch
+pub fn record_micro_batch(hashes: usize) {
+    histogram!("witness_micro_batch_size").record(hashes as f64);
+}
+
 /// Record signatures collected from witnesses
 pub fn record_signatures(witness_id: &str) {
     counter!("witness_signatures_collected", "witness" => witness_id.to_string()).increment(1);
 }
 
+/// Record how long a witness took to answer a sign request
+pub fn record_witness_latency(witness_id: &str, seconds: f64, success: bool) {
+    histogram!(
+        "witness_witness_latency_seconds",
+        "witness" => witness_id.to_string(),
+        "result" => if success { "ok" } else { "error" }
+    )
+    .record(seconds);
+}
+
+/// Record a witness signature dropped because it failed verification
+pub fn record_invalid_signature(witness_id: &str) {
+    counter!("witness_invalid_signatures_total", "witness" => witness_id.to_string()).increment(1);
+}
+
+/// Record a signature attached after its attestation was stored
+pub fn record_late_signature(witness_id: &str) {
+    counter!("witness_late_signatures_total", "witness" => witness_id.to_string()).increment(1);
+}
+
 /// Record a batch creation
 pub fn record_batch() {
     counter!("witness_batches_total").increment(1);
@@ -31,6 +61,11 @@ pub fn record_anchor(provider: &str) {
     counter!("witness_external_anchors_total", "provider" => provider.to_string()).increment(1);
 }
 
+/// Record a cross-anchor received from a peer network
+pub fn record_cross_anchor(network: &str) {
+    counter!("witness_cross_anchors_total", "network" => network.to_string()).increment(1);
+}
+
 /// Update the 24h attestation gauge
 pub fn set_attestations_24h(count: u64) {
     gauge!("witness_attestations_24h").set(count as f64);
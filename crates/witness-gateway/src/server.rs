@@ -10,22 +10,30 @@ use axum::{
 };
 use futures_util::{SinkExt, StreamExt};
 use metrics_exporter_prometheus::PrometheusHandle;
+use std::collections::{HashMap, HashSet};
+use futures_util::stream::FuturesUnordered;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tower_http::cors::CorsLayer;
 use witness_core::{
-    Attestation, CrossAnchorRequest, CrossAnchorResponse, ExternalAnchorProof, MerkleTree,
-    NetworkConfig, SignatureScheme, SignedAttestation, TimestampRequest, TimestampResponse,
-    VerifyRequest, VerifyResponse,
+    Attestation, BatchTimestampRequest, BatchTimestampResponse, CrossAnchorRequest,
+    CrossAnchorResponse, CrossAnchorStatement, ExternalAnchorProof, FederatedAttestation,
+    FederatedVerifyRequest, FederatedVerifyResponse, FreebirdToken, MerkleProof, MerkleTree, NetworkConfig, NetworkSnapshot, SignResponse, SignatureScheme, SignedAttestation,
+    SignedManifest, TimestampRequest, TimestampResponse, VerificationLevel, VerifyRequest,
+    VerifyResponse, WitnessInfo, WitnessReceipt,
 };
 
 use crate::admin::{admin_router, AdminState};
+use crate::aggregator::{self, AggregationConfig};
 use crate::batch_manager::BatchManager;
+use crate::circuit_breaker::{BreakerConfig, WitnessHealth};
 use crate::federation_client::FederationClient;
 use crate::freebird::{FreebirdClient, FreebirdError};
 use crate::metrics::{self, RequestTimer};
+use crate::signature_audit::SignatureAudit;
 use crate::storage::Storage;
-use crate::witness_client::WitnessClient;
+use crate::witness_client::{SignRefused, WitnessClient};
 
 /// Event broadcast to WebSocket clients when an attestation is created
 #[derive(Clone, Debug, serde::Serialize)]
@@ -47,8 +55,42 @@ pub struct GatewayServer {
     freebird_client: Option<Arc<FreebirdClient>>,
     event_tx: broadcast::Sender<AttestationEvent>,
     metrics_handle: PrometheusHandle,
+    manifests: Arc<Vec<SignedManifest>>,
+    last_sequence: Arc<tokio::sync::Mutex<u64>>,
+    aggregation: Option<AggregationConfig>,
+    aggregator: Option<mpsc::Sender<PendingTimestamp>>,
+    signature_collection: SignatureCollection,
+    signature_audit: Arc<SignatureAudit>,
+    witness_health: Arc<WitnessHealth>,
 }
 
+/// How long to wait for witness signatures
+#[derive(Debug, Clone, Copy)]
+pub struct SignatureCollection {
+    /// Give up on a witness that has not answered within this time
+    pub witness_timeout: Duration,
+
+    /// Fail the request if threshold signatures are not in within this time
+    pub deadline: Duration,
+
+    /// Keep waiting for the remaining witnesses after the threshold is met,
+    /// and attach their signatures to the stored attestation
+    pub attach_late: bool,
+}
+
+impl Default for SignatureCollection {
+    fn default() -> Self {
+        Self {
+            witness_timeout: Duration::from_secs(10),
+            deadline: Duration::from_secs(10),
+            attach_late: false,
+        }
+    }
+}
+
+/// A timestamp request waiting in a micro-batch
+type PendingTimestamp = ([u8; 32], oneshot::Sender<Result<TimestampResponse, AppError>>);
+
 impl GatewayServer {
     pub fn new(
         config: Arc<NetworkConfig>,
@@ -57,6 +99,7 @@ impl GatewayServer {
         federation_client: Arc<FederationClient>,
         freebird_client: Option<Arc<FreebirdClient>>,
         metrics_handle: PrometheusHandle,
+        signature_audit: Arc<SignatureAudit>,
     ) -> Self {
         // Create broadcast channel for WebSocket events with capacity 256
         let (event_tx, _) = broadcast::channel(256);
@@ -70,24 +113,102 @@ impl GatewayServer {
             freebird_client,
             event_tx,
             metrics_handle,
+            manifests: Arc::new(Vec::new()),
+            last_sequence: Arc::new(tokio::sync::Mutex::new(0)),
+            aggregation: None,
+            aggregator: None,
+            signature_collection: SignatureCollection::default(),
+            signature_audit,
+            witness_health: Arc::new(WitnessHealth::new(BreakerConfig::default())),
         }
     }
 
-    pub async fn run(self, port: u16, admin_state: Option<AdminState>) -> anyhow::Result<()> {
+    /// Serve a verified manifest chain, oldest first
+    pub fn with_manifests(mut self, manifests: Vec<SignedManifest>) -> Self {
+        self.manifests = Arc::new(manifests);
+        self
+    }
+
+    /// Sign requests to witnesses with this gateway key
+    pub fn with_auth_key(mut self, auth_key: ed25519_dalek::SigningKey) -> Self {
+        self.witness_client = Arc::new(WitnessClient::new().with_auth_key(auth_key));
+        self
+    }
+
+    /// Hold single-hash timestamp requests in micro-batches signed as one root
+    pub fn with_aggregation(mut self, aggregation: AggregationConfig) -> Self {
+        self.aggregation = Some(aggregation);
+        self
+    }
+
+    /// Route sign requests by these circuit breakers, shared with health probes
+    pub fn with_witness_health(mut self, witness_health: Arc<WitnessHealth>) -> Self {
+        self.witness_health = witness_health;
+        self
+    }
+
+    /// Limit how long timestamp requests wait for witness signatures
+    pub fn with_signature_collection(mut self, signature_collection: SignatureCollection) -> Self {
+        self.signature_collection = signature_collection;
+        self
+    }
+
+    /// Create the attestation for the next sequence number
+    ///
+    /// Witnesses refuse to sign a sequence twice, so a number handed out for a
+    /// request that later fails is never reused, and concurrent requests never
+    /// share one. Sequence and timestamp are assigned together so both increase.
+    /// With a leaf count, the hash is the merkle root over that many hashes.
+    async fn next_attestation(
+        &self,
+        hash: [u8; 32],
+        leaf_count: Option<u64>,
+    ) -> Result<Attestation, AppError> {
+        let mut last_sequence = self.last_sequence.lock().await;
+        let stored = self.storage.get_next_sequence(&self.config.id).await?;
+
+        let sequence = stored.max(*last_sequence + 1);
+        *last_sequence = sequence;
+
+        Ok(match leaf_count {
+            Some(leaf_count) => Attestation::new_root(hash, leaf_count, self.config.id.clone(), sequence),
+            None => Attestation::new(hash, self.config.id.clone(), sequence),
+        })
+    }
+
+    pub async fn run(mut self, port: u16, admin_state: Option<AdminState>) -> anyhow::Result<()> {
+        if let Some(aggregation) = self.aggregation {
+            let (tx, rx) = mpsc::channel(aggregation.max_size);
+            self.aggregator = Some(tx);
+            tokio::spawn(run_aggregator(self.clone(), rx, aggregation));
+
+            tracing::info!(
+                "Aggregating timestamp requests over {:?} windows of up to {} hashes",
+                aggregation.window,
+                aggregation.max_size
+            );
+        }
+
         let mut app = Router::new()
             .route("/", get(root_handler))
             .route("/health", get(health_handler))
             .route("/metrics", get(metrics_handler))
             .route("/v1/config", get(config_handler))
+            .route("/v1/manifest", get(manifest_handler))
+            .route("/v1/manifest/:version", get(manifest_version_handler))
             .route("/v1/timestamp", post(timestamp_handler))
+            .route("/v1/timestamp/batch", post(batch_timestamp_handler))
             .route("/v1/timestamp/:hash", get(get_timestamp_handler))
             .route("/v1/verify", post(verify_handler))
+            .route("/v1/verify/federated", post(verify_federated_handler))
             // Phase 2: Federation endpoints
             .route("/v1/federation/anchor", post(federation_anchor_handler))
             // Phase 3: External anchor endpoints
             .route("/v1/anchors/:hash", get(get_anchors_handler))
             // Phase 6: Light client proof endpoint
             .route("/v1/proof/:hash", get(get_proof_handler))
+            // Self-contained receipt bundle
+            .route("/v1/receipt/:hash", get(get_receipt_handler))
             // WebSocket events endpoint
             .route("/ws/events", get(ws_events_handler))
             .layer(CorsLayer::permissive())
@@ -120,6 +241,28 @@ async fn config_handler(State(server): State<GatewayServer>) -> impl IntoRespons
     Json(server.config.as_ref().clone())
 }
 
+async fn manifest_handler(
+    State(server): State<GatewayServer>,
+) -> Result<impl IntoResponse, AppError> {
+    let manifest = server.manifests.last().ok_or(AppError::NotFound)?;
+    Ok(Json(manifest.clone()))
+}
+
+async fn manifest_version_handler(
+    State(server): State<GatewayServer>,
+    axum::extract::Path(version): axum::extract::Path<u64>,
+) -> Result<impl IntoResponse, AppError> {
+    let manifest = server
+        .manifests
+        .iter()
+        .find(|m| m.manifest.version == version)
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(manifest.clone()))
+}
+
+/// Most hashes accepted in one batch timestamp request
+const MAX_BATCH_HASHES: usize = 10_000;
+
 async fn timestamp_handler(
     State(server): State<GatewayServer>,
     Json(request): Json<TimestampRequest>,
@@ -127,134 +270,548 @@ async fn timestamp_handler(
     let _timer = RequestTimer::new("timestamp");
     tracing::info!("Received timestamp request for hash: {}", request.hash);
 
-    // Check Freebird token if configured
-    if let Some(ref freebird) = server.freebird_client {
-        match &request.freebird_token {
-            Some(token) => {
-                // Verify token with Freebird verifier
-                freebird.verify(token).await?;
-                // Token is consumed by verifier (nullifier recorded)
-                tracing::info!("Freebird token verified for hash: {}", request.hash);
-            }
-            None if freebird.is_required() => {
-                return Err(AppError::FreebirdTokenRequired);
-            }
-            None => {
-                // Permissive mode - allow without token
-                tracing::debug!("No Freebird token provided (permissive mode)");
-            }
-        }
-    }
+    check_freebird_token(&server, request.freebird_token.as_ref()).await?;
 
-    // Parse hash
-    let hash_bytes = hex::decode(&request.hash)
-        .map_err(|_| AppError::InvalidHash)?;
-
-    let hash: [u8; 32] = hash_bytes
-        .try_into()
-        .map_err(|_| AppError::InvalidHash)?;
+    let hash = parse_hash(&request.hash)?;
 
     // Check for duplicate
     if server.storage.check_duplicate(&hash).await? {
         tracing::info!("Hash already timestamped: {}", request.hash);
 
-        // Return existing attestation
-        let existing = server.storage.get_attestation(&hash).await?
+        // Return existing attestation (with its inclusion proof, if batched)
+        let existing = lookup_timestamp(&server, &hash)
+            .await?
             .ok_or(AppError::InternalError)?;
 
-        return Ok(Json(TimestampResponse {
-            attestation: existing,
-        }));
+        return Ok(Json(existing));
     }
 
-    // Get next sequence number
-    let sequence = server.storage.get_next_sequence(&server.config.id).await?;
+    // In aggregation mode, wait for the micro-batch to be signed
+    if let Some(aggregator) = &server.aggregator {
+        let (reply, response) = oneshot::channel();
+        aggregator
+            .send((hash, reply))
+            .await
+            .map_err(|_| AppError::InternalError)?;
+        let response = response.await.map_err(|_| AppError::InternalError)??;
+        return Ok(Json(response));
+    }
 
-    // Create attestation
-    let attestation = Attestation::new(hash, server.config.id.clone(), sequence);
+    // Create attestation with the next sequence number
+    let attestation = server.next_attestation(hash, None).await?;
 
     tracing::debug!("Created attestation: {}", attestation);
 
-    // Request signatures from all witnesses concurrently
-    let mut tasks = Vec::new();
+    let (signed, late) = collect_signatures(&server, attestation).await?;
 
-    for witness in &server.config.witnesses {
-        let witness = witness.clone();
-        let attestation = attestation.clone();
-        let client = server.witness_client.clone();
+    // Store attestation
+    server.storage.store_attestation(&signed).await?;
+    late.attach(&server);
 
-        let task = tokio::spawn(async move {
-            match client.request_signature(&witness, &attestation).await {
-                Ok(response) => {
-                    tracing::info!("Got signature from witness: {}", witness.id);
-                    Some(response)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get signature from {}: {}", witness.id, e);
-                    None
-                }
-            }
-        });
+    // Record metrics
+    metrics::record_attestation();
+
+    tracing::info!(
+        "Successfully timestamped hash {} with sequence {}",
+        request.hash,
+        signed.attestation.sequence
+    );
 
-        tasks.push(task);
+    // Broadcast event to WebSocket clients (non-blocking)
+    let event = AttestationEvent {
+        event_type: "attestation",
+        hash: request.hash.clone(),
+        timestamp: signed.attestation.timestamp,
+    };
+    // Ignore send errors (no receivers is ok)
+    let _ = server.event_tx.send(event);
+
+    Ok(Json(TimestampResponse::new(signed)))
+}
+
+/// Timestamp many hashes with one signing round over their merkle root
+///
+/// Each hash gets back the signed root attestation and its inclusion proof.
+/// Hashes timestamped before keep their existing timestamp.
+async fn batch_timestamp_handler(
+    State(server): State<GatewayServer>,
+    Json(request): Json<BatchTimestampRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let _timer = RequestTimer::new("timestamp_batch");
+    tracing::info!("Received batch timestamp request for {} hashes", request.hashes.len());
+
+    if request.hashes.is_empty() || request.hashes.len() > MAX_BATCH_HASHES {
+        return Err(AppError::InvalidBatchSize {
+            got: request.hashes.len(),
+            max: MAX_BATCH_HASHES,
+        });
     }
 
-    // Collect results and create signed attestation based on signature scheme
-    let signed = match server.config.signature_scheme {
-        SignatureScheme::Ed25519 => {
-            // Ed25519: Collect individual signatures
-            let mut signed = SignedAttestation::new(attestation.clone());
+    check_freebird_token(&server, request.freebird_token.as_ref()).await?;
 
-            for task in tasks {
-                if let Ok(Some(response)) = task.await {
-                    metrics::record_signatures(&response.witness_id);
-                    signed.add_signature(response.witness_id, response.signature);
-                }
-            }
+    let hashes = request
+        .hashes
+        .iter()
+        .map(|hash| parse_hash(hash))
+        .collect::<Result<Vec<_>, _>>()?;
 
-            tracing::info!(
-                "Collected {} Ed25519 signatures (threshold: {})",
-                signed.signature_count(),
-                server.config.threshold
-            );
+    let responses = timestamp_hashes(&server, &hashes).await?;
+
+    let timestamps = hashes
+        .iter()
+        .map(|hash| responses.get(hash).cloned().ok_or(AppError::InternalError))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(BatchTimestampResponse { timestamps }))
+}
+
+/// Timestamp hashes with at most one signing round over their merkle root
+///
+/// Hashes timestamped before keep their existing timestamp. A lone new hash is
+/// signed directly; several are signed as a root with their leaf count.
+async fn timestamp_hashes(
+    server: &GatewayServer,
+    hashes: &[[u8; 32]],
+) -> Result<HashMap<[u8; 32], TimestampResponse>, AppError> {
+    let mut responses: HashMap<[u8; 32], TimestampResponse> = HashMap::new();
+    let mut earlier: HashMap<[u8; 32], HashSet<[u8; 32]>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut leaves = Vec::new();
+
+    for hash in hashes {
+        if !seen.insert(*hash) {
+            continue;
+        }
+        if let Some(existing) = server.storage.get_attestation(hash).await? {
+            responses.insert(*hash, TimestampResponse::new(existing));
+        } else if let Some((root, _)) = server.storage.get_batch_leaf(hash).await? {
+            earlier.entry(root).or_default().insert(*hash);
+        } else {
+            leaves.push(*hash);
+        }
+    }
+
+    // Rebuild each earlier batch once rather than once per hash
+    for (root, requested) in earlier {
+        let attestation = server
+            .storage
+            .get_attestation(&root)
+            .await?
+            .ok_or(AppError::InternalError)?;
 
-            if signed.signature_count() < server.config.threshold {
-                return Err(AppError::InsufficientSignatures {
-                    got: signed.signature_count(),
-                    required: server.config.threshold,
+        let tree = MerkleTree::new(server.storage.get_batch_leaves(&root).await?);
+        for proof in tree.proofs() {
+            if requested.contains(&proof.leaf) {
+                responses.entry(proof.leaf).or_insert_with(|| TimestampResponse {
+                    attestation: attestation.clone(),
+                    inclusion_proof: Some(proof),
                 });
             }
+        }
+    }
 
-            signed
+    if !leaves.is_empty() {
+        // A lone new hash is signed directly, as by `/v1/timestamp`
+        let tree = MerkleTree::new(leaves.clone());
+        let leaf_count = (leaves.len() > 1).then_some(leaves.len() as u64);
+        let attestation = server.next_attestation(tree.root(), leaf_count).await?;
+
+        tracing::debug!("Created batch attestation over {} hashes: {}", leaves.len(), attestation);
+
+        let (signed, late) = collect_signatures(server, attestation).await?;
+
+        server.storage.store_attestation(&signed).await?;
+        if leaves.len() > 1 {
+            server.storage.store_batch_leaves(&tree.root(), &leaves).await?;
+        }
+        late.attach(server);
+
+        metrics::record_attestation();
+        metrics::record_batch_timestamp(leaves.len());
+
+        tracing::info!(
+            "Successfully timestamped {} hashes under root {} with sequence {}",
+            leaves.len(),
+            hex::encode(tree.root()),
+            signed.attestation.sequence
+        );
+
+        let event = AttestationEvent {
+            event_type: if leaves.len() > 1 { "batch_attestation" } else { "attestation" },
+            hash: hex::encode(tree.root()),
+            timestamp: signed.attestation.timestamp,
+        };
+        let _ = server.event_tx.send(event);
+
+        if leaves.len() == 1 {
+            responses.insert(leaves[0], TimestampResponse::new(signed));
+        } else {
+            for proof in tree.proofs() {
+                responses.insert(
+                    proof.leaf,
+                    TimestampResponse {
+                        attestation: signed.clone(),
+                        inclusion_proof: Some(proof),
+                    },
+                );
+            }
         }
+    }
 
-        SignatureScheme::BLS => {
-            // BLS: Collect individual signatures and aggregate
-            let mut individual_signatures = Vec::new();
-            let mut signer_ids = Vec::new();
-
-            for task in tasks {
-                if let Ok(Some(response)) = task.await {
-                    metrics::record_signatures(&response.witness_id);
-                    individual_signatures.push(response.signature);
-                    signer_ids.push(response.witness_id);
+    Ok(responses)
+}
+
+/// Sign micro-batches of timestamp requests as they fill up
+async fn run_aggregator(
+    server: GatewayServer,
+    mut rx: mpsc::Receiver<PendingTimestamp>,
+    aggregation: AggregationConfig,
+) {
+    while let Some(batch) = aggregator::next_batch(&mut rx, &aggregation).await {
+        tokio::spawn(flush_micro_batch(server.clone(), batch));
+    }
+}
+
+async fn flush_micro_batch(server: GatewayServer, batch: Vec<PendingTimestamp>) {
+    // The same hash may be waited on by several requests
+    let mut waiting: HashMap<[u8; 32], Vec<oneshot::Sender<_>>> = HashMap::new();
+    for (hash, reply) in batch {
+        waiting.entry(hash).or_default().push(reply);
+    }
+
+    let hashes: Vec<[u8; 32]> = waiting.keys().copied().collect();
+    tracing::debug!("Flushing micro-batch of {} hashes", hashes.len());
+    metrics::record_micro_batch(hashes.len());
+
+    match timestamp_hashes(&server, &hashes).await {
+        Ok(responses) => {
+            for (hash, replies) in waiting {
+                for reply in replies {
+                    let _ = reply.send(responses.get(&hash).cloned().ok_or(AppError::InternalError));
                 }
             }
+        }
+        Err(e) => {
+            let error = e.shared();
+            for reply in waiting.into_values().flatten() {
+                let _ = reply.send(Err(error.shared()));
+            }
+        }
+    }
+}
 
-            tracing::info!(
-                "Collected {} BLS signatures to aggregate (threshold: {})",
-                individual_signatures.len(),
-                server.config.threshold
-            );
+/// Check the request's Freebird token if the gateway is configured for it
+async fn check_freebird_token(
+    server: &GatewayServer,
+    token: Option<&FreebirdToken>,
+) -> Result<(), AppError> {
+    let Some(ref freebird) = server.freebird_client else {
+        return Ok(());
+    };
 
-            if individual_signatures.len() < server.config.threshold {
-                return Err(AppError::InsufficientSignatures {
-                    got: individual_signatures.len(),
-                    required: server.config.threshold,
-                });
+    match token {
+        Some(token) => {
+            // Verify token with Freebird verifier
+            freebird.verify(token).await?;
+            // Token is consumed by verifier (nullifier recorded)
+            tracing::info!("Freebird token verified");
+        }
+        None if freebird.is_required() => {
+            return Err(AppError::FreebirdTokenRequired);
+        }
+        None => {
+            // Permissive mode - allow without token
+            tracing::debug!("No Freebird token provided (permissive mode)");
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32], AppError> {
+    hex::decode(hash)
+        .map_err(|_| AppError::InvalidHash)?
+        .try_into()
+        .map_err(|_| AppError::InvalidHash)
+}
+
+/// Find the timestamp of a hash signed directly or as a leaf of a batch root
+async fn lookup_timestamp(
+    server: &GatewayServer,
+    hash: &[u8; 32],
+) -> Result<Option<TimestampResponse>, AppError> {
+    if let Some(attestation) = server.storage.get_attestation(hash).await? {
+        return Ok(Some(TimestampResponse::new(attestation)));
+    }
+
+    let Some((root, index)) = server.storage.get_batch_leaf(hash).await? else {
+        return Ok(None);
+    };
+
+    let attestation = server
+        .storage
+        .get_attestation(&root)
+        .await?
+        .ok_or(AppError::InternalError)?;
+
+    let tree = MerkleTree::new(server.storage.get_batch_leaves(&root).await?);
+    let siblings = tree
+        .proof(index)
+        .ok_or_else(|| AppError::Other(anyhow::anyhow!("Failed to generate inclusion proof")))?;
+
+    Ok(Some(TimestampResponse {
+        attestation,
+        inclusion_proof: Some(MerkleProof {
+            leaf: *hash,
+            siblings,
+            index,
+            root,
+        }),
+    }))
+}
+
+/// A sign request in flight to one witness
+type SignatureTask = tokio::task::JoinHandle<Option<SignResponse>>;
+
+/// Witnesses whose circuit is closed, or an error if too few are left to
+/// reach the threshold
+fn available_witnesses(server: &GatewayServer) -> Result<Vec<&WitnessInfo>, AppError> {
+    let threshold = server.config.threshold;
+
+    let witnesses = server.witness_health.available(&server.config.witnesses);
+    if witnesses.len() < threshold {
+        tracing::warn!(
+            "Only {} of {} witnesses are healthy (threshold: {})",
+            witnesses.len(),
+            server.config.witnesses.len(),
+            threshold
+        );
+        return Err(AppError::WitnessesUnavailable {
+            healthy: witnesses.len(),
+            required: threshold,
+        });
+    }
+    Ok(witnesses)
+}
+
+/// Send a sign request to each of these witnesses, recording how long each
+/// takes and whether it answered
+fn request_signatures<F, Fut>(
+    server: &GatewayServer,
+    witnesses: &[&WitnessInfo],
+    request: F,
+) -> FuturesUnordered<SignatureTask>
+where
+    F: Fn(Arc<WitnessClient>, WitnessInfo) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<SignResponse>> + Send + 'static,
+{
+    let timeout = server.signature_collection.witness_timeout;
+
+    witnesses
+        .iter()
+        .map(|witness| {
+            let witness = (*witness).clone();
+            let response = request(server.witness_client.clone(), witness.clone());
+            let audit = server.signature_audit.clone();
+            let health = server.witness_health.clone();
+
+            tokio::spawn(async move {
+                let start = Instant::now();
+                let result = tokio::time::timeout(timeout, response).await;
+                let latency = start.elapsed().as_secs_f64();
+
+                // A witness that answers is up, even if it refuses to sign
+                match &result {
+                    Ok(Ok(_)) => health.record_success(&witness.id),
+                    Ok(Err(e)) if e.is::<SignRefused>() => health.record_success(&witness.id),
+                    Ok(Err(_)) | Err(_) => health.record_failure(&witness.id),
+                }
+
+                match result {
+                    // A witness may only sign for itself
+                    Ok(Ok(response)) if response.witness_id != witness.id => {
+                        metrics::record_witness_latency(&witness.id, latency, false);
+                        let reason = format!("answered as witness {}", response.witness_id);
+                        reject_signature(&audit, &witness.id, &reason);
+                        None
+                    }
+                    Ok(Ok(response)) => {
+                        metrics::record_witness_latency(&witness.id, latency, true);
+                        tracing::info!("Got signature from witness: {}", witness.id);
+                        Some(response)
+                    }
+                    Ok(Err(e)) => {
+                        metrics::record_witness_latency(&witness.id, latency, false);
+                        tracing::warn!("Failed to get signature from {}: {}", witness.id, e);
+                        None
+                    }
+                    Err(_) => {
+                        metrics::record_witness_latency(&witness.id, latency, false);
+                        tracing::warn!("Witness {} did not answer within {:?}", witness.id, timeout);
+                        None
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// Whether a witness response carries a valid signature, so it can count
+/// towards the threshold
+///
+/// Checking on arrival keeps one bad signature from spoiling an aggregate
+/// or a threshold combination.
+fn accept_response(server: &GatewayServer, attestation: &Attestation, response: &SignResponse) -> bool {
+    let Some(witness) = server.config.find_witness(&response.witness_id) else {
+        tracing::warn!("Dropping signature from unknown witness: {}", response.witness_id);
+        return false;
+    };
+    let Some(pubkey) = witness.pubkey_at(attestation.timestamp) else {
+        return false;
+    };
+
+    let verify_bls = || {
+        witness_core::decode_bls_public_key(pubkey)
+            .and_then(|key| witness_core::verify_signature_bls(attestation, &response.signature, &key))
+            .is_ok()
+    };
+    let valid = match server.config.signature_scheme {
+        SignatureScheme::Ed25519 => witness_core::decode_public_key(pubkey)
+            .and_then(|key| witness_core::verify_signature(attestation, &response.signature, &key))
+            .is_ok(),
+        SignatureScheme::BLS => verify_bls(),
+        // Partials are only usable with a share index to interpolate at
+        SignatureScheme::ThresholdBLS => witness.share_index.is_some() && verify_bls(),
+    };
+
+    if valid {
+        server.signature_audit.record_valid(&response.witness_id);
+    } else {
+        reject_signature(&server.signature_audit, &response.witness_id, "signature does not verify");
+    }
+    valid
+}
+
+/// Whether a witness response carries a valid signature on a cross-anchor
+/// statement, so it can count towards the threshold
+fn accept_cross_anchor_response(
+    server: &GatewayServer,
+    statement: &CrossAnchorStatement,
+    response: &SignResponse,
+) -> bool {
+    let Some(witness) = server.config.find_witness(&response.witness_id) else {
+        tracing::warn!("Dropping signature from unknown witness: {}", response.witness_id);
+        return false;
+    };
+    let Some(pubkey) = witness.pubkey_at(statement.timestamp) else {
+        return false;
+    };
+
+    // Threshold witnesses sign cross-anchors individually with their share keys
+    let valid = match server.config.signature_scheme {
+        SignatureScheme::Ed25519 => witness_core::decode_public_key(pubkey)
+            .and_then(|key| witness_core::verify_cross_anchor_signature(statement, &response.signature, &key))
+            .is_ok(),
+        SignatureScheme::BLS | SignatureScheme::ThresholdBLS => witness_core::decode_bls_public_key(pubkey)
+            .and_then(|key| witness_core::verify_cross_anchor_signature_bls(statement, &response.signature, &key))
+            .is_ok(),
+    };
+
+    if valid {
+        server.signature_audit.record_valid(&response.witness_id);
+    } else {
+        reject_signature(&server.signature_audit, &response.witness_id, "signature does not verify");
+    }
+    valid
+}
+
+/// Count a dropped signature against the witness that sent it
+fn reject_signature(audit: &SignatureAudit, witness_id: &str, reason: &str) {
+    metrics::record_invalid_signature(witness_id);
+    tracing::warn!("Dropping signature from witness {}: {}", witness_id, reason);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    if audit.record_invalid(witness_id, now) {
+        tracing::error!(
+            "Witness {} is a repeat offender: {} invalid signatures in the last {} seconds",
+            witness_id,
+            audit.record(witness_id).recent_invalid(now),
+            crate::signature_audit::REPEAT_OFFENDER_WINDOW
+        );
+    }
+}
+
+/// Request signatures on an attestation from every witness and combine them
+///
+/// Returns as soon as threshold signatures are in. Witnesses that have not
+/// answered yet are handed back, to be attached once the attestation is stored.
+async fn collect_signatures(
+    server: &GatewayServer,
+    attestation: Attestation,
+) -> Result<(SignedAttestation, LateSignatures), AppError> {
+    let threshold = server.config.threshold;
+
+    // Skip witnesses whose circuit is open, and fail fast if too few are left
+    let witnesses = available_witnesses(server)?;
+
+    let mut pending = request_signatures(server, &witnesses, |client, witness| {
+        let attestation = attestation.clone();
+        async move { client.request_signature(&witness, &attestation).await }
+    });
+    let responses = race_signatures(server, &mut pending, |response| {
+        accept_response(server, &attestation, response)
+    })
+    .await;
+
+    let late = LateSignatures {
+        attestation: attestation.clone(),
+        pending,
+    };
+
+    tracing::info!(
+        "Collected {} {} signatures (threshold: {}, still waiting on {})",
+        responses.len(),
+        server.config.signature_scheme,
+        threshold,
+        late.pending.len()
+    );
+
+    if responses.len() < threshold {
+        late.abort();
+        return Err(AppError::InsufficientSignatures {
+            got: responses.len(),
+            required: threshold,
+        });
+    }
+
+    // Clock readings that came back with the signatures, checked below
+    let mut time_readings = Vec::new();
+    for response in &responses {
+        metrics::record_signatures(&response.witness_id);
+        time_readings.extend(response.time_reading.clone());
+    }
+
+    // Create signed attestation based on signature scheme
+    let mut signed = match server.config.signature_scheme {
+        SignatureScheme::Ed25519 => {
+            // Ed25519: Collect individual signatures
+            let mut signed = SignedAttestation::new(attestation.clone());
+            for response in responses {
+                signed.add_signature(response.witness_id, response.signature);
             }
+            signed
+        }
+
+        SignatureScheme::BLS => {
+            // BLS: Aggregate individual signatures
+            let (signer_ids, individual_signatures): (Vec<_>, Vec<_>) = responses
+                .into_iter()
+                .map(|response| (response.witness_id, response.signature))
+                .unzip();
 
-            // Aggregate BLS signatures
             let aggregated_signature = witness_core::aggregate_signatures_bls(&individual_signatures)
                 .map_err(|e| {
                     tracing::error!("BLS aggregation failed: {}", e);
@@ -272,8 +829,40 @@ async fn timestamp_handler(
                 signer_ids,
             )
         }
+
+        SignatureScheme::ThresholdBLS => {
+            // Threshold BLS: Interpolate the partials
+            let partials: Vec<_> = responses
+                .into_iter()
+                .filter_map(|response| {
+                    let witness = server.config.find_witness(&response.witness_id)?;
+                    Some((witness.share_index?, response.signature))
+                })
+                .collect();
+
+            let group_signature =
+                witness_core::combine_partial_signatures(&partials, threshold)
+                    .map_err(|e| {
+                        tracing::error!("Threshold signature combination failed: {}", e);
+                        AppError::InvalidSignature
+                    })?;
+
+            SignedAttestation::new_with_threshold(attestation.clone(), group_signature)
+        }
     };
 
+    // A bad clock reading only costs the interval a bound, not the attestation
+    signed.time_readings = time_readings
+        .into_iter()
+        .filter(|reading| {
+            let valid = witness_core::verify_time_reading(reading, &attestation, &server.config).is_ok();
+            if !valid {
+                tracing::warn!("Invalid time reading from witness: {}", reading.witness_id);
+            }
+            valid
+        })
+        .collect();
+
     // Verify signatures
     let verified_count = witness_core::verify_signed_attestation(&signed, &server.config)
         .map_err(|e| {
@@ -283,30 +872,118 @@ async fn timestamp_handler(
 
     tracing::info!("Verified {} signatures", verified_count);
 
-    // Store attestation
-    server.storage.store_attestation(&signed).await?;
+    Ok((signed, late))
+}
 
-    // Record metrics
-    metrics::record_attestation();
+/// Take accepted responses as they arrive, until threshold are in or the
+/// collection deadline passes
+async fn race_signatures(
+    server: &GatewayServer,
+    pending: &mut FuturesUnordered<SignatureTask>,
+    accept: impl Fn(&SignResponse) -> bool,
+) -> Vec<SignResponse> {
+    let threshold = server.config.threshold;
+    let deadline = tokio::time::Instant::now() + server.signature_collection.deadline;
+
+    let mut responses = Vec::new();
+    while responses.len() < threshold {
+        match tokio::time::timeout_at(deadline, pending.next()).await {
+            Ok(Some(Ok(Some(response)))) => {
+                if accept(&response) {
+                    responses.push(response);
+                }
+            }
+            // A witness failed; keep waiting for the others
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(_) => {
+                tracing::warn!(
+                    "Signature deadline passed with {} of {} required signatures",
+                    responses.len(),
+                    threshold
+                );
+                break;
+            }
+        }
+    }
+    responses
+}
+
+/// Sign requests still outstanding when the threshold was met
+struct LateSignatures {
+    attestation: Attestation,
+    pending: FuturesUnordered<SignatureTask>,
+}
+
+impl LateSignatures {
+    /// Once the attestation is stored, attach signatures that arrive later
+    /// (if configured), or stop waiting for them
+    fn attach(self, server: &GatewayServer) {
+        if self.pending.is_empty() {
+            return;
+        }
+        if !server.signature_collection.attach_late {
+            self.abort();
+            return;
+        }
+
+        let server = server.clone();
+        tokio::spawn(async move {
+            let mut pending = self.pending;
+            while let Some(result) = pending.next().await {
+                let Ok(Some(response)) = result else {
+                    continue;
+                };
+                let witness_id = response.witness_id.clone();
+                if let Err(e) = attach_late_signature(&server, &self.attestation, response).await {
+                    tracing::warn!("Failed to attach late signature from {}: {:#}", witness_id, e);
+                }
+            }
+        });
+    }
+
+    fn abort(self) {
+        for task in self.pending.iter() {
+            task.abort();
+        }
+    }
+}
+
+/// Add a witness's signature and clock reading to a stored attestation
+///
+/// Only individual (Ed25519) signatures can be added; aggregated and
+/// threshold signatures are final once stored, so only the clock reading is kept.
+async fn attach_late_signature(
+    server: &GatewayServer,
+    attestation: &Attestation,
+    response: SignResponse,
+) -> anyhow::Result<()> {
+    if !accept_response(server, attestation, &response) {
+        return Ok(());
+    }
+
+    if server.config.signature_scheme == SignatureScheme::Ed25519 {
+        server
+            .storage
+            .add_signature(&attestation.hash, &response.witness_id, &response.signature)
+            .await?;
+    }
+
+    if let Some(reading) = response.time_reading {
+        match witness_core::verify_time_reading(&reading, attestation, &server.config) {
+            Ok(()) => server.storage.add_time_reading(&attestation.hash, &reading).await?,
+            Err(_) => tracing::warn!("Invalid time reading from witness: {}", reading.witness_id),
+        }
+    }
 
+    metrics::record_late_signature(&response.witness_id);
     tracing::info!(
-        "Successfully timestamped hash {} with sequence {}",
-        request.hash,
-        signed.attestation.sequence
+        "Attached late signature from witness {} to attestation {}",
+        response.witness_id,
+        attestation.sequence
     );
 
-    // Broadcast event to WebSocket clients (non-blocking)
-    let event = AttestationEvent {
-        event_type: "attestation",
-        hash: request.hash.clone(),
-        timestamp: signed.attestation.timestamp,
-    };
-    // Ignore send errors (no receivers is ok)
-    let _ = server.event_tx.send(event);
-
-    Ok(Json(TimestampResponse {
-        attestation: signed,
-    }))
+    Ok(())
 }
 
 async fn get_timestamp_handler(
@@ -322,13 +999,11 @@ async fn get_timestamp_handler(
         .try_into()
         .map_err(|_| AppError::InvalidHash)?;
 
-    let attestation = server
-        .storage
-        .get_attestation(&hash_array)
+    let response = lookup_timestamp(&server, &hash_array)
         .await?
         .ok_or(AppError::NotFound)?;
 
-    Ok(Json(TimestampResponse { attestation }))
+    Ok(Json(response))
 }
 
 async fn verify_handler(
@@ -376,52 +1051,38 @@ async fn federation_anchor_handler(
         request.batch.network_id
     );
 
-    // Create an attestation for this batch's merkle root
-    let sequence = server.storage.get_next_sequence(&server.config.id).await?;
+    // Build the statement our witnesses will sign over the peer's batch
+    let statement = CrossAnchorStatement::new(request.batch, server.config.id.clone());
 
-    let attestation = Attestation::new(
-        request.batch.merkle_root,
-        server.config.id.clone(),
-        sequence,
+    tracing::debug!(
+        "Created cross-anchor statement for batch {} of network {}",
+        statement.batch.id,
+        statement.batch.network_id
     );
 
-    tracing::debug!("Created attestation for batch cross-anchor: {}", attestation);
-
-    // Request signatures from all witnesses
-    let mut tasks = Vec::new();
-
-    for witness in &server.config.witnesses {
-        let witness = witness.clone();
-        let attestation = attestation.clone();
-        let client = server.witness_client.clone();
-
-        let task = tokio::spawn(async move {
-            match client.request_signature(&witness, &attestation).await {
-                Ok(response) => {
-                    tracing::info!("Got signature from witness: {}", witness.id);
-                    Some(response)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get signature from {}: {}", witness.id, e);
-                    None
-                }
-            }
-        });
-
-        tasks.push(task);
+    // Race the healthy witnesses, as for attestations
+    let witnesses = available_witnesses(&server)?;
+    let mut pending = request_signatures(&server, &witnesses, |client, witness| {
+        let statement = statement.clone();
+        async move { client.request_cross_anchor_signature(&witness, &statement).await }
+    });
+    let responses = race_signatures(&server, &mut pending, |response| {
+        accept_cross_anchor_response(&server, &statement, response)
+    })
+    .await;
+
+    // Cross-anchors are final once returned, so later signatures are not needed
+    for task in pending.iter() {
+        task.abort();
     }
 
-    // Collect signatures
-    let mut signatures = Vec::new();
-
-    for task in tasks {
-        if let Ok(Some(response)) = task.await {
-            signatures.push(witness_core::WitnessSignature {
-                witness_id: response.witness_id,
-                signature: response.signature,
-            });
-        }
-    }
+    let signatures: Vec<_> = responses
+        .into_iter()
+        .map(|response| witness_core::WitnessSignature {
+            witness_id: response.witness_id,
+            signature: response.signature,
+        })
+        .collect();
 
     tracing::info!(
         "Collected {} signatures for cross-anchor (threshold: {})",
@@ -437,22 +1098,14 @@ async fn federation_anchor_handler(
         });
     }
 
-    // Create cross-anchor
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
     let cross_anchor = witness_core::CrossAnchor {
-        batch: request.batch,
-        witnessing_network: server.config.id.clone(),
+        statement,
         signatures,
-        timestamp,
     };
 
     tracing::info!(
         "Created cross-anchor for network: {}",
-        cross_anchor.batch.network_id
+        cross_anchor.statement.batch.network_id
     );
 
     Ok(Json(CrossAnchorResponse { cross_anchor }))
@@ -563,13 +1216,139 @@ async fn get_proof_handler(
     }))
 }
 
+// ============================================================================
+// Receipt Handler
+// ============================================================================
+
+async fn get_receipt_handler(
+    State(server): State<GatewayServer>,
+    axum::extract::Path(hash): axum::extract::Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    tracing::debug!("Building receipt for hash: {}", hash);
+
+    let receipt = build_receipt(&server, &hash).await?;
+
+    Ok(Json(receipt))
+}
+
+/// Collect the attestation, batch, merkle proof and anchors for a hash
+async fn build_receipt(server: &GatewayServer, hash: &str) -> Result<WitnessReceipt, AppError> {
+    let hash_bytes = hex::decode(hash).map_err(|_| AppError::InvalidHash)?;
+    let hash_array: [u8; 32] = hash_bytes
+        .try_into()
+        .map_err(|_| AppError::InvalidHash)?;
+
+    let timestamp = lookup_timestamp(server, &hash_array)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let network = NetworkSnapshot::from_config(&server.config);
+
+    // For a batch timestamp, the attested root is what goes into federation batches
+    let attested_hash = timestamp.attestation.attestation.hash;
+    let mut receipt = WitnessReceipt::new(timestamp.attestation, network);
+    receipt.inclusion_proof = timestamp.inclusion_proof;
+
+    // Attach batch, merkle proof and anchors once the attestation is batched
+    if let Some((batch_id, merkle_index, merkle_root)) = server
+        .storage
+        .get_attestation_batch_info(&hex::encode(attested_hash))
+        .await?
+    {
+        let batch_hashes = server
+            .storage
+            .get_batch_attestation_hashes(batch_id)
+            .await?;
+
+        let tree = MerkleTree::new(batch_hashes);
+        let siblings = tree
+            .proof(merkle_index)
+            .ok_or_else(|| AppError::Other(anyhow::anyhow!("Failed to generate merkle proof")))?;
+
+        receipt.merkle_proof = Some(MerkleProof {
+            leaf: attested_hash,
+            siblings,
+            index: merkle_index,
+            root: merkle_root,
+        });
+        receipt.batch = server.storage.get_batch(batch_id).await?;
+        receipt.cross_anchors = server.storage.get_cross_anchors(batch_id).await?;
+        receipt.external_anchors = server.storage.get_anchor_proofs(batch_id as u64).await?;
+    }
+
+    Ok(receipt)
+}
+
+async fn verify_federated_handler(
+    State(server): State<GatewayServer>,
+    Json(request): Json<FederatedVerifyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    tracing::info!("Federated verification for hash: {}", request.hash);
+
+    let receipt = match build_receipt(&server, &request.hash).await {
+        Ok(receipt) => receipt,
+        Err(AppError::NotFound) => {
+            return Ok(Json(FederatedVerifyResponse {
+                federated_attestation: None,
+                verified: false,
+                verification_level: VerificationLevel::None,
+                message: "No attestation found".to_string(),
+            }));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let config = receipt.network.to_network_config();
+    let result = witness_core::verify_receipt(&receipt, &config);
+
+    let federated_attestation = Some(FederatedAttestation {
+        attestation: receipt.attestation,
+        merkle_proof: receipt.merkle_proof,
+        cross_anchors: receipt.cross_anchors,
+        batch: receipt.batch,
+        external_anchors: receipt.external_anchors,
+    });
+
+    let response = match result {
+        Ok(verification) => {
+            let verification_level = verification.level();
+
+            let mut message = format!("Valid: {}", verification_level);
+            if !verification.invalid_cross_anchors.is_empty() {
+                message.push_str(&format!(
+                    " ({} invalid cross-anchors: {})",
+                    verification.invalid_cross_anchors.len(),
+                    verification.invalid_cross_anchors.join("; ")
+                ));
+            }
+
+            FederatedVerifyResponse {
+                federated_attestation,
+                verified: true,
+                message,
+                verification_level,
+            }
+        }
+        Err(e) => FederatedVerifyResponse {
+            federated_attestation,
+            verified: false,
+            verification_level: VerificationLevel::None,
+            message: format!("Invalid: {}", e),
+        },
+    };
+
+    Ok(Json(response))
+}
+
 // Error handling
 enum AppError {
     InvalidHash,
+    InvalidBatchSize { got: usize, max: usize },
     NotFound,
     NotBatched,
     InvalidSignature,
     InsufficientSignatures { got: usize, required: usize },
+    WitnessesUnavailable { healthy: usize, required: usize },
     InternalError,
     DatabaseError(sqlx::Error),
     Other(anyhow::Error),
@@ -579,6 +1358,42 @@ enum AppError {
     FreebirdVerificationFailed(String),
 }
 
+impl AppError {
+    /// A copy of this error for each request waiting on a shared micro-batch
+    ///
+    /// Database and other internal errors are logged here and passed on as
+    /// plain internal errors.
+    fn shared(&self) -> AppError {
+        match self {
+            AppError::InvalidHash => AppError::InvalidHash,
+            AppError::InvalidBatchSize { got, max } => AppError::InvalidBatchSize { got: *got, max: *max },
+            AppError::NotFound => AppError::NotFound,
+            AppError::NotBatched => AppError::NotBatched,
+            AppError::InvalidSignature => AppError::InvalidSignature,
+            AppError::InsufficientSignatures { got, required } => AppError::InsufficientSignatures {
+                got: *got,
+                required: *required,
+            },
+            AppError::WitnessesUnavailable { healthy, required } => AppError::WitnessesUnavailable {
+                healthy: *healthy,
+                required: *required,
+            },
+            AppError::InternalError => AppError::InternalError,
+            AppError::DatabaseError(e) => {
+                tracing::error!("Database error: {}", e);
+                AppError::InternalError
+            }
+            AppError::Other(e) => {
+                tracing::error!("Error: {}", e);
+                AppError::InternalError
+            }
+            AppError::FreebirdTokenRequired => AppError::FreebirdTokenRequired,
+            AppError::FreebirdTokenInvalid => AppError::FreebirdTokenInvalid,
+            AppError::FreebirdVerificationFailed(msg) => AppError::FreebirdVerificationFailed(msg.clone()),
+        }
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
         AppError::DatabaseError(e)
@@ -610,6 +1425,10 @@ impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
             AppError::InvalidHash => (StatusCode::BAD_REQUEST, "Invalid hash format".to_string()),
+            AppError::InvalidBatchSize { got, max } => (
+                StatusCode::BAD_REQUEST,
+                format!("Batch must contain between 1 and {} hashes, got {}", max, got),
+            ),
             AppError::NotFound => (StatusCode::NOT_FOUND, "Attestation not found".to_string()),
             AppError::NotBatched => (StatusCode::NOT_FOUND, "Attestation not yet batched".to_string()),
             AppError::InvalidSignature => (StatusCode::BAD_REQUEST, "Invalid signature".to_string()),
@@ -617,6 +1436,10 @@ impl IntoResponse for AppError {
                 StatusCode::SERVICE_UNAVAILABLE,
                 format!("Insufficient signatures: got {}, required {}", got, required),
             ),
+            AppError::WitnessesUnavailable { healthy, required } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Too few healthy witnesses: {} healthy, {} required", healthy, required),
+            ),
             AppError::InternalError => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string()),
             AppError::DatabaseError(e) => {
                 tracing::error!("Database error: {}", e);
@@ -705,3 +1528,271 @@ async fn handle_ws_connection(
 async fn root_handler() -> impl IntoResponse {
     axum::response::Redirect::temporary("/admin")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use witness_core::{AttestationBatch, AttestationSignatures, CrossAnchorSignRequest, SignRequest};
+
+    /// Answer attestation and cross-anchor sign requests as `id` after
+    /// `delay`, signing the canonical bytes with `sign`
+    async fn mock_witness<F>(id: String, pubkey: String, delay: Duration, sign: F) -> WitnessInfo
+    where
+        F: Fn(&[u8]) -> Vec<u8> + Clone + Send + Sync + 'static,
+    {
+        let witness_id = id.clone();
+        let respond = move |message: Vec<u8>| {
+            let (witness_id, sign) = (id.clone(), sign.clone());
+            async move {
+                tokio::time::sleep(delay).await;
+                Json(SignResponse {
+                    witness_id,
+                    signature: sign(&message),
+                    time_reading: None,
+                })
+            }
+        };
+        let respond_cross_anchor = respond.clone();
+        let app = Router::new()
+            .route(
+                "/v1/sign",
+                post(move |Json(request): Json<SignRequest>| respond(request.attestation.to_bytes())),
+            )
+            .route(
+                "/v1/sign/cross-anchor",
+                post(move |Json(request): Json<CrossAnchorSignRequest>| {
+                    respond_cross_anchor(request.statement.to_bytes())
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        WitnessInfo {
+            id: witness_id,
+            pubkey,
+            endpoint: format!("http://{}", addr),
+            share_index: None,
+            pop: None,
+            key_activated_at: None,
+            previous_keys: Vec::new(),
+        }
+    }
+
+    /// An Ed25519 witness `witness-<seed>`
+    async fn ed25519_witness(seed: u8, delay: Duration) -> WitnessInfo {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let pubkey = witness_core::encode_public_key(&key.verifying_key());
+        mock_witness(format!("witness-{}", seed), pubkey, delay, move |message| {
+            witness_core::sign_message(message, &key)
+        })
+        .await
+    }
+
+    /// A BLS witness `witness-<seed>`, which signs with another key if `forge` is set
+    async fn bls_witness(seed: u8, delay: Duration, forge: bool) -> WitnessInfo {
+        let (secret_key, public_key) = witness_core::generate_bls_keypair();
+        let pop = hex::encode(witness_core::generate_bls_pop(&secret_key));
+        let signing_key = if forge { witness_core::generate_bls_keypair().0 } else { secret_key };
+
+        let pubkey = witness_core::encode_bls_public_key(&public_key);
+        let mut witness = mock_witness(format!("witness-{}", seed), pubkey, delay, move |message| {
+            witness_core::sign_message_bls(message, &signing_key)
+        })
+        .await;
+        witness.pop = Some(pop);
+        witness
+    }
+
+    async fn gateway(
+        signature_scheme: SignatureScheme,
+        witnesses: Vec<WitnessInfo>,
+        threshold: usize,
+        signature_collection: SignatureCollection,
+    ) -> GatewayServer {
+        let config = Arc::new(NetworkConfig {
+            id: "test-net".to_string(),
+            witnesses,
+            threshold,
+            signature_scheme,
+            group_public_key: None,
+            federation: Default::default(),
+            external_anchors: Default::default(),
+            federation_peers: Vec::new(),
+            legacy_encoding_until: None,
+        });
+        let storage = Arc::new(Storage::new("sqlite::memory:").await.unwrap());
+        storage.migrate().await.unwrap();
+
+        GatewayServer::new(
+            config.clone(),
+            storage.clone(),
+            Arc::new(BatchManager::new(config.clone(), storage.clone())),
+            Arc::new(FederationClient::new(config, storage)),
+            None,
+            PrometheusBuilder::new().build_recorder().handle(),
+            Arc::new(SignatureAudit::default()),
+        )
+        .with_signature_collection(signature_collection)
+    }
+
+    fn attestation(hash: u8) -> Attestation {
+        Attestation::new([hash; 32], "test-net".to_string(), hash as u64)
+    }
+
+    #[tokio::test]
+    async fn test_collect_signatures_with_mock_witnesses() {
+        let witnesses = vec![
+            ed25519_witness(1, Duration::ZERO).await,
+            ed25519_witness(2, Duration::ZERO).await,
+            ed25519_witness(3, Duration::from_secs(3600)).await,
+        ];
+        let collection = SignatureCollection {
+            witness_timeout: Duration::from_secs(30),
+            deadline: Duration::from_secs(30),
+            attach_late: false,
+        };
+
+        // Returns once threshold signatures are in, without waiting for the hung witness
+        let server = gateway(SignatureScheme::Ed25519, witnesses.clone(), 2, collection).await;
+        let start = Instant::now();
+        let Ok((signed, late)) = collect_signatures(&server, attestation(1)).await else {
+            panic!("threshold signatures were not collected");
+        };
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(signed.signature_count(), 2);
+        assert_eq!(late.pending.len(), 1);
+        late.abort();
+
+        // A threshold that needs the hung witness fails at the deadline
+        let deadline = Duration::from_millis(300);
+        let server = gateway(SignatureScheme::Ed25519, witnesses.clone(), 3, SignatureCollection { deadline, ..collection }).await;
+        let start = Instant::now();
+        let result = collect_signatures(&server, attestation(2)).await;
+        assert!(matches!(result, Err(AppError::InsufficientSignatures { got: 2, required: 3 })));
+        assert!(start.elapsed() >= deadline);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // A slow witness's signature is attached to the stored attestation
+        let slow = ed25519_witness(4, Duration::from_millis(200)).await;
+        let witnesses = vec![witnesses[0].clone(), witnesses[1].clone(), slow];
+        let server = gateway(SignatureScheme::Ed25519, witnesses, 2, SignatureCollection { attach_late: true, ..collection }).await;
+        let Ok((signed, late)) = collect_signatures(&server, attestation(3)).await else {
+            panic!("threshold signatures were not collected");
+        };
+        assert_eq!(signed.signature_count(), 2);
+        server.storage.store_attestation(&signed).await.unwrap();
+        late.attach(&server);
+
+        let stored = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let stored = server.storage.get_attestation(&[3; 32]).await.unwrap().unwrap();
+                if stored.signature_count() == 3 {
+                    return stored;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("late signature was not attached");
+        assert!(witness_core::verify_signed_attestation(&stored, &server.config).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_bls_signature_is_dropped() {
+        // The forger answers first, so its signature is checked before the threshold is met
+        let witnesses = vec![
+            bls_witness(1, Duration::ZERO, false).await,
+            bls_witness(2, Duration::from_millis(200), false).await,
+            bls_witness(3, Duration::ZERO, true).await,
+        ];
+        let collection = SignatureCollection {
+            witness_timeout: Duration::from_secs(30),
+            deadline: Duration::from_secs(30),
+            attach_late: false,
+        };
+        let server = gateway(SignatureScheme::BLS, witnesses, 2, collection).await;
+
+        let Ok((signed, late)) = collect_signatures(&server, attestation(1)).await else {
+            panic!("the valid signatures were not aggregated");
+        };
+        assert!(late.pending.is_empty());
+
+        let AttestationSignatures::Aggregated { signers, .. } = &signed.signatures else {
+            panic!("expected an aggregated signature");
+        };
+        assert_eq!(signers, &["witness-1", "witness-2"]);
+        assert_eq!(witness_core::verify_signed_attestation(&signed, &server.config).unwrap(), 2);
+
+        let forger = server.signature_audit.record("witness-3");
+        assert_eq!(forger.invalid_signatures, 1);
+        assert_eq!(forger.valid_signatures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_federation_anchor_with_mock_witnesses() {
+        let witnesses = vec![
+            ed25519_witness(1, Duration::ZERO).await,
+            ed25519_witness(2, Duration::ZERO).await,
+            ed25519_witness(3, Duration::from_secs(3600)).await,
+        ];
+        let deadline = Duration::from_millis(300);
+        let collection = SignatureCollection {
+            witness_timeout: Duration::from_secs(30),
+            deadline,
+            attach_late: false,
+        };
+        let server = gateway(SignatureScheme::Ed25519, witnesses, 2, collection).await;
+        let request = || {
+            Json(CrossAnchorRequest {
+                batch: AttestationBatch {
+                    id: 7,
+                    network_id: "peer-net".to_string(),
+                    merkle_root: [5; 32],
+                    period_start: 0,
+                    period_end: 60,
+                    attestation_count: 1,
+                },
+            })
+        };
+
+        // Answers once threshold witnesses have signed, without waiting for the hung one
+        let start = Instant::now();
+        let Ok(response) = federation_anchor_handler(State(server.clone()), request()).await else {
+            panic!("threshold signatures were not collected");
+        };
+        assert!(start.elapsed() < deadline);
+        let body = axum::body::to_bytes(response.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let response: CrossAnchorResponse = serde_json::from_slice(&body).unwrap();
+        let mut signers: Vec<_> = response
+            .cross_anchor
+            .signatures
+            .iter()
+            .map(|signature| signature.witness_id.as_str())
+            .collect();
+        signers.sort();
+        assert_eq!(signers, ["witness-1", "witness-2"]);
+
+        // A witness with an open circuit is not asked, so the deadline passes
+        for _ in 0..3 {
+            server.witness_health.record_failure("witness-1");
+        }
+        let start = Instant::now();
+        let result = federation_anchor_handler(State(server.clone()), request()).await;
+        assert!(matches!(result, Err(AppError::InsufficientSignatures { got: 1, required: 2 })));
+        assert!(start.elapsed() >= deadline);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // and too few healthy witnesses fail straight away
+        for _ in 0..3 {
+            server.witness_health.record_failure("witness-2");
+        }
+        let result = federation_anchor_handler(State(server), request()).await;
+        assert!(matches!(result, Err(AppError::WitnessesUnavailable { healthy: 1, required: 2 })));
+    }
+}
@@ -0,0 +1,134 @@
+//! Per-witness record of signature verification results
+//!
+//! The gateway checks every witness signature as it arrives and drops the
+//! ones that fail. Witnesses that keep sending bad signatures are flagged as
+//! repeat offenders in the admin witness list, until they have gone a full
+//! window without one.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Invalid signatures within `REPEAT_OFFENDER_WINDOW` that make a witness a
+/// repeat offender
+pub const REPEAT_OFFENDER_THRESHOLD: usize = 3;
+
+/// How far back invalid signatures count towards the threshold (seconds)
+pub const REPEAT_OFFENDER_WINDOW: u64 = 3600;
+
+/// Verification results for one witness since the gateway started
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WitnessRecord {
+    pub valid_signatures: u64,
+    pub invalid_signatures: u64,
+
+    /// Unix time of the latest invalid signature
+    pub last_invalid_at: Option<u64>,
+
+    /// Unix times of the invalid signatures within the window
+    #[serde(skip)]
+    recent_invalid: VecDeque<u64>,
+}
+
+impl WitnessRecord {
+    /// Invalid signatures within the window ending at `now`
+    pub fn recent_invalid(&self, now: u64) -> usize {
+        let since = now.saturating_sub(REPEAT_OFFENDER_WINDOW);
+        self.recent_invalid.iter().filter(|&&at| at > since).count()
+    }
+
+    pub fn is_repeat_offender(&self, now: u64) -> bool {
+        self.recent_invalid(now) >= REPEAT_OFFENDER_THRESHOLD
+    }
+}
+
+#[derive(Default)]
+pub struct SignatureAudit {
+    witnesses: Mutex<HashMap<String, WitnessRecord>>,
+}
+
+impl SignatureAudit {
+    pub fn record_valid(&self, witness_id: &str) {
+        let mut witnesses = self.witnesses.lock().unwrap();
+        witnesses.entry(witness_id.to_string()).or_default().valid_signatures += 1;
+    }
+
+    /// Record an invalid signature; true if this makes the witness a repeat offender
+    pub fn record_invalid(&self, witness_id: &str, now: u64) -> bool {
+        let mut witnesses = self.witnesses.lock().unwrap();
+        let record = witnesses.entry(witness_id.to_string()).or_default();
+
+        let since = now.saturating_sub(REPEAT_OFFENDER_WINDOW);
+        while record.recent_invalid.front().is_some_and(|&at| at <= since) {
+            record.recent_invalid.pop_front();
+        }
+
+        record.invalid_signatures += 1;
+        record.last_invalid_at = Some(now);
+        record.recent_invalid.push_back(now);
+        record.recent_invalid.len() == REPEAT_OFFENDER_THRESHOLD
+    }
+
+    pub fn record(&self, witness_id: &str) -> WitnessRecord {
+        self.witnesses
+            .lock()
+            .unwrap()
+            .get(witness_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat_offenders() {
+        let audit = SignatureAudit::default();
+
+        audit.record_valid("w1");
+        assert!(!audit.record_invalid("w1", 100));
+        assert!(!audit.record_invalid("w1", 101));
+        assert!(!audit.record("w1").is_repeat_offender(101));
+
+        // Flagged once, when the threshold is reached
+        assert!(audit.record_invalid("w1", 102));
+        assert!(!audit.record_invalid("w1", 103));
+
+        let record = audit.record("w1");
+        assert!(record.is_repeat_offender(103));
+        assert_eq!(record.valid_signatures, 1);
+        assert_eq!(record.invalid_signatures, 4);
+        assert_eq!(record.last_invalid_at, Some(103));
+
+        assert!(!audit.record("w2").is_repeat_offender(103));
+    }
+
+    #[test]
+    fn test_repeat_offenders_decay() {
+        let audit = SignatureAudit::default();
+
+        // Invalid signatures spread further apart than the window never add up
+        for i in 0..10 {
+            assert!(!audit.record_invalid("w1", 1000 + i * REPEAT_OFFENDER_WINDOW));
+        }
+        assert_eq!(audit.record("w1").invalid_signatures, 10);
+
+        // An offender is cleared once its invalid signatures leave the window
+        let start = 100_000;
+        for i in 0..3 {
+            audit.record_invalid("w2", start + i);
+        }
+        let record = audit.record("w2");
+        assert!(record.is_repeat_offender(start + 2));
+        assert!(record.is_repeat_offender(start + REPEAT_OFFENDER_WINDOW - 1));
+        assert!(!record.is_repeat_offender(start + REPEAT_OFFENDER_WINDOW));
+
+        // and flagged again if it starts over
+        for i in 0..2 {
+            assert!(!audit.record_invalid("w2", start + 2 * REPEAT_OFFENDER_WINDOW + i));
+        }
+        assert!(audit.record_invalid("w2", start + 2 * REPEAT_OFFENDER_WINDOW + 2));
+    }
+}
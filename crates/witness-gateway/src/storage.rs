@@ -1,10 +1,16 @@
 use anyhow::Result;
-use sqlx::{sqlite::SqlitePool, Row};
+use sqlx::{
+    sqlite::{SqlitePool, SqliteRow},
+    Row,
+};
 use witness_core::{
     signature_scheme::AttestationSignatures, Attestation, AttestationBatch, CrossAnchor,
-    ExternalAnchorProof, SignedAttestation, WitnessSignature,
+    CrossAnchorStatement, ExternalAnchorProof, SignedAttestation, TimeReading, WitnessSignature,
 };
 
+/// Marker witness_id for a stored threshold BLS group signature
+const THRESHOLD_SIGNATURE_ID: &str = "BLS_THRESHOLD";
+
 pub struct Storage {
     pool: SqlitePool,
 }
@@ -126,6 +132,86 @@ impl Storage {
         .await
         .ok(); // Ignore error if column already exists
 
+        // Cross-anchor statement encoding version (existing rows are legacy v0)
+        sqlx::query(
+            r#"
+            ALTER TABLE cross_anchors ADD COLUMN version INTEGER NOT NULL DEFAULT 0
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+        // Attestation encoding version (existing rows are legacy v0)
+        sqlx::query(
+            r#"
+            ALTER TABLE attestations ADD COLUMN version INTEGER NOT NULL DEFAULT 0
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+        // Sub-second timestamp part (v2 attestations)
+        sqlx::query(
+            r#"
+            ALTER TABLE attestations ADD COLUMN timestamp_millis INTEGER
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+        // Leaf count of root attestations
+        sqlx::query(
+            r#"
+            ALTER TABLE attestations ADD COLUMN leaf_count INTEGER
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .ok(); // Ignore error if column already exists
+
+        // Witness clock readings bounding each attestation's time
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS time_readings (
+                hash TEXT NOT NULL,
+                witness_id TEXT NOT NULL,
+                time_ms INTEGER NOT NULL,
+                signature BLOB NOT NULL,
+                FOREIGN KEY (hash) REFERENCES attestations(hash),
+                PRIMARY KEY (hash, witness_id)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Hashes timestamped as leaves of a signed merkle root
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS batch_leaves (
+                root TEXT NOT NULL,
+                leaf_index INTEGER NOT NULL,
+                hash TEXT NOT NULL,
+                FOREIGN KEY (root) REFERENCES attestations(hash),
+                PRIMARY KEY (root, leaf_index)
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_batch_leaves_hash
+            ON batch_leaves(hash)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Phase 3: External anchor proofs
         sqlx::query(
             r#"
@@ -162,39 +248,37 @@ impl Storage {
         // Store attestation
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO attestations (hash, timestamp, network_id, sequence, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT OR REPLACE INTO attestations (hash, timestamp, network_id, sequence, version, created_at, timestamp_millis, leaf_count)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
         )
         .bind(&hash_hex)
         .bind(signed.attestation.timestamp as i64)
         .bind(&signed.attestation.network_id)
         .bind(signed.attestation.sequence as i64)
+        .bind(signed.attestation.version as i64)
         .bind(
             std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs() as i64,
         )
+        .bind(signed.attestation.timestamp_millis.map(|millis| millis as i64))
+        .bind(signed.attestation.leaf_count.map(|count| count as i64))
         .execute(&self.pool)
         .await?;
 
+        for reading in &signed.time_readings {
+            self.add_time_reading(&signed.attestation.hash, reading).await?;
+        }
+
         // Store signatures based on type
         match &signed.signatures {
             AttestationSignatures::MultiSig { signatures } => {
                 // Store individual signatures
                 for sig in signatures {
-                    sqlx::query(
-                        r#"
-                        INSERT OR IGNORE INTO signatures (hash, witness_id, signature)
-                        VALUES (?1, ?2, ?3)
-                        "#,
-                    )
-                    .bind(&hash_hex)
-                    .bind(&sig.witness_id)
-                    .bind(&sig.signature)
-                    .execute(&self.pool)
-                    .await?;
+                    self.add_signature(&signed.attestation.hash, &sig.witness_id, &sig.signature)
+                        .await?;
                 }
             }
             AttestationSignatures::Aggregated { signature, signers } => {
@@ -213,18 +297,67 @@ impl Storage {
                 .execute(&self.pool)
                 .await?;
             }
+            AttestationSignatures::Threshold { signature } => {
+                // Store the group signature under a marker witness_id
+                sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO signatures (hash, witness_id, signature)
+                    VALUES (?1, ?2, ?3)
+                    "#,
+                )
+                .bind(&hash_hex)
+                .bind(THRESHOLD_SIGNATURE_ID)
+                .bind(signature)
+                .execute(&self.pool)
+                .await?;
+            }
         }
 
         Ok(())
     }
 
+    /// Add one witness's signature to a stored multi-signature attestation
+    pub async fn add_signature(&self, hash: &[u8; 32], witness_id: &str, signature: &[u8]) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO signatures (hash, witness_id, signature)
+            VALUES (?1, ?2, ?3)
+            "#,
+        )
+        .bind(hex::encode(hash))
+        .bind(witness_id)
+        .bind(signature)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Add a witness's clock reading to a stored attestation
+    pub async fn add_time_reading(&self, hash: &[u8; 32], reading: &TimeReading) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO time_readings (hash, witness_id, time_ms, signature)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(hex::encode(hash))
+        .bind(&reading.witness_id)
+        .bind(reading.time_ms as i64)
+        .bind(&reading.signature)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_attestation(&self, hash: &[u8; 32]) -> Result<Option<SignedAttestation>> {
         let hash_hex = hex::encode(hash);
 
         // Get attestation
         let row = sqlx::query(
             r#"
-            SELECT hash, timestamp, network_id, sequence
+            SELECT hash, timestamp, network_id, sequence, version, timestamp_millis, leaf_count
             FROM attestations
             WHERE hash = ?1
             "#,
@@ -246,6 +379,13 @@ impl Storage {
             timestamp: row.get::<i64, _>("timestamp") as u64,
             network_id: row.get("network_id"),
             sequence: row.get::<i64, _>("sequence") as u64,
+            version: row.get::<i64, _>("version") as u8,
+            timestamp_millis: row
+                .get::<Option<i64>, _>("timestamp_millis")
+                .map(|millis| millis as u16),
+            leaf_count: row
+                .get::<Option<i64>, _>("leaf_count")
+                .map(|count| count as u64),
         };
 
         // Get signatures
@@ -264,37 +404,38 @@ impl Storage {
             return Ok(None);
         }
 
-        // Check if this is an aggregated signature
-        let first_witness_id: String = sig_rows[0].get("witness_id");
-
-        let signatures = if first_witness_id.starts_with("BLS_AGGREGATED:") {
-            // Reconstruct aggregated signature
-            let signature: Vec<u8> = sig_rows[0].get("signature");
-            let signers_str = first_witness_id.strip_prefix("BLS_AGGREGATED:").unwrap();
-            let signers: Vec<String> = signers_str.split(',').map(|s| s.to_string()).collect();
-
-            AttestationSignatures::Aggregated { signature, signers }
-        } else {
-            // Reconstruct multi-sig
-            let witness_sigs: Vec<WitnessSignature> = sig_rows
-                .iter()
-                .map(|row| WitnessSignature {
-                    witness_id: row.get("witness_id"),
-                    signature: row.get("signature"),
-                })
-                .collect();
-
-            AttestationSignatures::MultiSig {
-                signatures: witness_sigs,
-            }
-        };
+        let signatures = signatures_from_rows(&sig_rows);
 
         Ok(Some(SignedAttestation {
             attestation,
             signatures,
+            time_readings: self.get_time_readings(&hash_hex).await?,
         }))
     }
 
+    async fn get_time_readings(&self, hash_hex: &str) -> Result<Vec<TimeReading>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT witness_id, time_ms, signature
+            FROM time_readings
+            WHERE hash = ?1
+            ORDER BY time_ms ASC
+            "#,
+        )
+        .bind(hash_hex)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TimeReading {
+                witness_id: row.get("witness_id"),
+                time_ms: row.get::<i64, _>("time_ms") as u64,
+                signature: row.get("signature"),
+            })
+            .collect())
+    }
+
     pub async fn get_next_sequence(&self, network_id: &str) -> Result<u64> {
         let row = sqlx::query(
             r#"
@@ -316,9 +457,8 @@ impl Storage {
 
         let row = sqlx::query(
             r#"
-            SELECT COUNT(*) as count
-            FROM attestations
-            WHERE hash = ?1
+            SELECT (SELECT COUNT(*) FROM attestations WHERE hash = ?1)
+                 + (SELECT COUNT(*) FROM batch_leaves WHERE hash = ?1) as count
             "#,
         )
         .bind(&hash_hex)
@@ -329,13 +469,85 @@ impl Storage {
         Ok(count > 0)
     }
 
+    /// Record the leaves of a signed merkle root, in tree order
+    pub async fn store_batch_leaves(&self, root: &[u8; 32], leaves: &[[u8; 32]]) -> Result<()> {
+        let root_hex = hex::encode(root);
+        let mut tx = self.pool.begin().await?;
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO batch_leaves (hash, root, leaf_index)
+                VALUES (?1, ?2, ?3)
+                "#,
+            )
+            .bind(hex::encode(leaf))
+            .bind(&root_hex)
+            .bind(index as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Signed root a hash was first timestamped under, and its leaf index
+    pub async fn get_batch_leaf(&self, hash: &[u8; 32]) -> Result<Option<([u8; 32], usize)>> {
+        let row = sqlx::query(
+            r#"
+            SELECT root, leaf_index
+            FROM batch_leaves
+            WHERE hash = ?1
+            ORDER BY rowid ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(hex::encode(hash))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let root: [u8; 32] = hex::decode(row.get::<String, _>("root"))?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid root hash length"))?;
+
+        Ok(Some((root, row.get::<i64, _>("leaf_index") as usize)))
+    }
+
+    /// All leaves of a signed root, in tree order
+    pub async fn get_batch_leaves(&self, root: &[u8; 32]) -> Result<Vec<[u8; 32]>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT hash
+            FROM batch_leaves
+            WHERE root = ?1
+            ORDER BY leaf_index ASC
+            "#,
+        )
+        .bind(hex::encode(root))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                hex::decode(row.get::<String, _>("hash"))?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid leaf hash length"))
+            })
+            .collect()
+    }
+
     // ========== Phase 2: Batch Management ==========
 
     /// Get all unbatched attestations since a given time
     pub async fn get_unbatched_attestations(&self, since: u64) -> Result<Vec<SignedAttestation>> {
         let rows = sqlx::query(
             r#"
-            SELECT hash, timestamp, network_id, sequence
+            SELECT hash, timestamp, network_id, sequence, version, timestamp_millis, leaf_count
             FROM attestations
             WHERE batch_id IS NULL AND timestamp >= ?1
             ORDER BY sequence ASC
@@ -357,6 +569,13 @@ impl Storage {
                 timestamp: row.get::<i64, _>("timestamp") as u64,
                 network_id: row.get("network_id"),
                 sequence: row.get::<i64, _>("sequence") as u64,
+                version: row.get::<i64, _>("version") as u8,
+                timestamp_millis: row
+                    .get::<Option<i64>, _>("timestamp_millis")
+                    .map(|millis| millis as u16),
+                leaf_count: row
+                    .get::<Option<i64>, _>("leaf_count")
+                    .map(|count| count as u64),
             };
 
             // Get signatures
@@ -371,41 +590,12 @@ impl Storage {
             .fetch_all(&self.pool)
             .await?;
 
-            // Reconstruct signatures based on type
-            let signatures = if !sig_rows.is_empty() {
-                let first_witness_id: String = sig_rows[0].get("witness_id");
-
-                if first_witness_id.starts_with("BLS_AGGREGATED:") {
-                    // Aggregated signature
-                    let signature: Vec<u8> = sig_rows[0].get("signature");
-                    let signers_str = first_witness_id.strip_prefix("BLS_AGGREGATED:").unwrap();
-                    let signers: Vec<String> =
-                        signers_str.split(',').map(|s| s.to_string()).collect();
-
-                    AttestationSignatures::Aggregated { signature, signers }
-                } else {
-                    // Multi-sig
-                    let witness_sigs: Vec<WitnessSignature> = sig_rows
-                        .iter()
-                        .map(|row| WitnessSignature {
-                            witness_id: row.get("witness_id"),
-                            signature: row.get("signature"),
-                        })
-                        .collect();
-
-                    AttestationSignatures::MultiSig {
-                        signatures: witness_sigs,
-                    }
-                }
-            } else {
-                AttestationSignatures::MultiSig {
-                    signatures: Vec::new(),
-                }
-            };
+            let signatures = signatures_from_rows(&sig_rows);
 
             attestations.push(SignedAttestation {
                 attestation,
                 signatures,
+                time_readings: self.get_time_readings(&hash_str).await?,
             });
         }
 
@@ -473,7 +663,6 @@ impl Storage {
     }
 
     /// Get a batch by ID
-    #[allow(dead_code)]
     pub async fn get_batch(&self, batch_id: i64) -> Result<Option<AttestationBatch>> {
         let row = sqlx::query(
             r#"
@@ -503,6 +692,48 @@ impl Storage {
         }))
     }
 
+    /// Get batches closed since `since` that fewer than `threshold` peer networks have cross-anchored
+    pub async fn get_batches_below_cross_anchor_threshold(
+        &self,
+        since: u64,
+        threshold: usize,
+    ) -> Result<Vec<AttestationBatch>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT b.id, b.network_id, b.merkle_root, b.period_start, b.period_end, b.attestation_count
+            FROM batches b
+            LEFT JOIN cross_anchors c ON c.batch_id = b.id
+            WHERE b.period_end >= ?1
+            GROUP BY b.id
+            HAVING COUNT(DISTINCT c.witnessing_network) < ?2
+            ORDER BY b.id ASC
+            "#,
+        )
+        .bind(since as i64)
+        .bind(threshold as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let batches = rows
+            .iter()
+            .map(|row| {
+                let merkle_root_vec: Vec<u8> = row.get("merkle_root");
+                let merkle_root: [u8; 32] = merkle_root_vec.try_into().unwrap();
+
+                AttestationBatch {
+                    id: row.get::<i64, _>("id") as u64,
+                    network_id: row.get("network_id"),
+                    merkle_root,
+                    period_start: row.get::<i64, _>("period_start") as u64,
+                    period_end: row.get::<i64, _>("period_end") as u64,
+                    attestation_count: row.get::<i64, _>("attestation_count") as u64,
+                }
+            })
+            .collect();
+
+        Ok(batches)
+    }
+
     /// Get batch ID for an attestation hash
     pub async fn get_batch_id_for_attestation(&self, hash: &[u8; 32]) -> Result<Option<i64>> {
         let hash_hex = hex::encode(hash);
@@ -577,18 +808,20 @@ impl Storage {
     }
 
     /// Store a cross-anchor
-    #[allow(dead_code)]
     pub async fn store_cross_anchor(&self, cross_anchor: &CrossAnchor) -> Result<()> {
+        let statement = &cross_anchor.statement;
+
         // Insert cross-anchor
         let result = sqlx::query(
             r#"
-            INSERT INTO cross_anchors (batch_id, witnessing_network, timestamp, created_at)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO cross_anchors (batch_id, witnessing_network, timestamp, version, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
             "#,
         )
-        .bind(cross_anchor.batch.id as i64)
-        .bind(&cross_anchor.witnessing_network)
-        .bind(cross_anchor.timestamp as i64)
+        .bind(statement.batch.id as i64)
+        .bind(&statement.witnessing_network)
+        .bind(statement.timestamp as i64)
+        .bind(statement.version as i64)
         .bind(
             std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -619,11 +852,10 @@ impl Storage {
     }
 
     /// Get cross-anchors for a batch
-    #[allow(dead_code)]
     pub async fn get_cross_anchors(&self, batch_id: i64) -> Result<Vec<CrossAnchor>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, witnessing_network, timestamp
+            SELECT id, witnessing_network, timestamp, version
             FROM cross_anchors
             WHERE batch_id = ?1
             "#,
@@ -663,10 +895,13 @@ impl Storage {
                 .collect();
 
             cross_anchors.push(CrossAnchor {
-                batch: batch.clone(),
-                witnessing_network: row.get("witnessing_network"),
+                statement: CrossAnchorStatement {
+                    version: row.get::<i64, _>("version") as u8,
+                    batch: batch.clone(),
+                    witnessing_network: row.get("witnessing_network"),
+                    timestamp: row.get::<i64, _>("timestamp") as u64,
+                },
                 signatures,
-                timestamp: row.get::<i64, _>("timestamp") as u64,
             });
         }
 
@@ -792,7 +1027,7 @@ impl Storage {
     pub async fn get_recent_attestations(&self, limit: usize) -> Result<Vec<SignedAttestation>> {
         let rows = sqlx::query(
             r#"
-            SELECT hash, timestamp, network_id, sequence
+            SELECT hash, timestamp, network_id, sequence, version, timestamp_millis, leaf_count
             FROM attestations
             ORDER BY timestamp DESC, sequence DESC
             LIMIT ?1
@@ -814,6 +1049,13 @@ impl Storage {
                 timestamp: row.get::<i64, _>("timestamp") as u64,
                 network_id: row.get("network_id"),
                 sequence: row.get::<i64, _>("sequence") as u64,
+                version: row.get::<i64, _>("version") as u8,
+                timestamp_millis: row
+                    .get::<Option<i64>, _>("timestamp_millis")
+                    .map(|millis| millis as u16),
+                leaf_count: row
+                    .get::<Option<i64>, _>("leaf_count")
+                    .map(|count| count as u64),
             };
 
             // Get signatures
@@ -828,38 +1070,12 @@ impl Storage {
             .fetch_all(&self.pool)
             .await?;
 
-            let signatures = if !sig_rows.is_empty() {
-                let first_witness_id: String = sig_rows[0].get("witness_id");
-
-                if first_witness_id.starts_with("BLS_AGGREGATED:") {
-                    let signature: Vec<u8> = sig_rows[0].get("signature");
-                    let signers_str = first_witness_id.strip_prefix("BLS_AGGREGATED:").unwrap();
-                    let signers: Vec<String> =
-                        signers_str.split(',').map(|s| s.to_string()).collect();
-
-                    AttestationSignatures::Aggregated { signature, signers }
-                } else {
-                    let witness_sigs: Vec<WitnessSignature> = sig_rows
-                        .iter()
-                        .map(|row| WitnessSignature {
-                            witness_id: row.get("witness_id"),
-                            signature: row.get("signature"),
-                        })
-                        .collect();
-
-                    AttestationSignatures::MultiSig {
-                        signatures: witness_sigs,
-                    }
-                }
-            } else {
-                AttestationSignatures::MultiSig {
-                    signatures: Vec::new(),
-                }
-            };
+            let signatures = signatures_from_rows(&sig_rows);
 
             attestations.push(SignedAttestation {
                 attestation,
                 signatures,
+                time_readings: self.get_time_readings(&hash_str).await?,
             });
         }
 
@@ -886,6 +1102,42 @@ impl Storage {
     }
 }
 
+/// Rebuild attestation signatures from rows of the signatures table
+fn signatures_from_rows(sig_rows: &[SqliteRow]) -> AttestationSignatures {
+    let Some(first) = sig_rows.first() else {
+        return AttestationSignatures::MultiSig {
+            signatures: Vec::new(),
+        };
+    };
+
+    let first_witness_id: String = first.get("witness_id");
+
+    if first_witness_id == THRESHOLD_SIGNATURE_ID {
+        AttestationSignatures::Threshold {
+            signature: first.get("signature"),
+        }
+    } else if let Some(signers_str) = first_witness_id.strip_prefix("BLS_AGGREGATED:") {
+        // Aggregated signature
+        let signature: Vec<u8> = first.get("signature");
+        let signers: Vec<String> = signers_str.split(',').map(|s| s.to_string()).collect();
+
+        AttestationSignatures::Aggregated { signature, signers }
+    } else {
+        // Multi-sig
+        let witness_sigs: Vec<WitnessSignature> = sig_rows
+            .iter()
+            .map(|row| WitnessSignature {
+                witness_id: row.get("witness_id"),
+                signature: row.get("signature"),
+            })
+            .collect();
+
+        AttestationSignatures::MultiSig {
+            signatures: witness_sigs,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -902,6 +1154,9 @@ mod tests {
             timestamp: 1700000000 + sequence,
             network_id: "test-network".to_string(),
             sequence,
+            version: witness_core::ATTESTATION_VERSION,
+            timestamp_millis: Some(250),
+            leaf_count: None,
         };
 
         let signatures = AttestationSignatures::MultiSig {
@@ -917,9 +1172,16 @@ mod tests {
             ],
         };
 
+        let time_readings = vec![TimeReading {
+            witness_id: "witness-1".to_string(),
+            time_ms: (1700000000 + sequence) * 1000 + 240,
+            signature: vec![9, 9, 9, 9],
+        }];
+
         SignedAttestation {
             attestation,
             signatures,
+            time_readings,
         }
     }
 
@@ -941,6 +1203,9 @@ mod tests {
         assert_eq!(retrieved.attestation.hash, hash);
         assert_eq!(retrieved.attestation.sequence, 1);
         assert_eq!(retrieved.attestation.network_id, "test-network");
+        assert_eq!(retrieved.attestation.version, witness_core::ATTESTATION_VERSION);
+        assert_eq!(retrieved.attestation.timestamp_millis, Some(250));
+        assert_eq!(retrieved.time_interval(), Some((1700000001240, 1700000001240)));
 
         // Check signatures
         match &retrieved.signatures {
@@ -953,6 +1218,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_attach_late_signature() {
+        let storage = setup_test_db().await;
+
+        let hash = [2u8; 32];
+        storage.store_attestation(&create_test_attestation(hash, 1)).await.unwrap();
+
+        let reading = TimeReading {
+            witness_id: "witness-3".to_string(),
+            time_ms: 1700000001300,
+            signature: vec![7; 64],
+        };
+        storage.add_signature(&hash, "witness-3", &[9; 64]).await.unwrap();
+        storage.add_time_reading(&hash, &reading).await.unwrap();
+
+        let retrieved = storage.get_attestation(&hash).await.unwrap().unwrap();
+        assert_eq!(retrieved.signature_count(), 3);
+        assert_eq!(retrieved.time_readings.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_store_and_get_attestation_bls() {
         let storage = setup_test_db().await;
@@ -963,6 +1248,9 @@ mod tests {
             timestamp: 1700000000,
             network_id: "test-network".to_string(),
             sequence: 1,
+            version: witness_core::ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
         };
 
         let signed = SignedAttestation {
@@ -971,6 +1259,7 @@ mod tests {
                 signature: vec![10, 20, 30, 40],
                 signers: vec!["witness-1".to_string(), "witness-2".to_string()],
             },
+            time_readings: vec![],
         };
 
         // Store
@@ -990,6 +1279,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_store_and_get_attestation_threshold() {
+        let storage = setup_test_db().await;
+
+        let hash = [2u8; 32];
+        let attestation = Attestation::new(hash, "test-network".to_string(), 1);
+        let signed = SignedAttestation::new_with_threshold(attestation, vec![7; 48]);
+
+        storage.store_attestation(&signed).await.unwrap();
+
+        let retrieved = storage.get_attestation(&hash).await.unwrap().unwrap();
+
+        match &retrieved.signatures {
+            AttestationSignatures::Threshold { signature } => {
+                assert_eq!(signature, &vec![7; 48]);
+            }
+            _ => panic!("Expected Threshold"),
+        }
+    }
+
     #[tokio::test]
     async fn test_check_duplicate() {
         let storage = setup_test_db().await;
@@ -1007,6 +1316,24 @@ mod tests {
         assert!(storage.check_duplicate(&hash).await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_batch_leaves() {
+        let storage = setup_test_db().await;
+
+        let root = [9u8; 32];
+        let leaves = vec![[3u8; 32], [4u8; 32], [5u8; 32]];
+        storage.store_attestation(&create_test_attestation(root, 1)).await.unwrap();
+        storage.store_batch_leaves(&root, &leaves).await.unwrap();
+
+        assert_eq!(storage.get_batch_leaf(&[4u8; 32]).await.unwrap(), Some((root, 1)));
+        assert_eq!(storage.get_batch_leaf(&[6u8; 32]).await.unwrap(), None);
+        assert_eq!(storage.get_batch_leaves(&root).await.unwrap(), leaves);
+
+        // A leaf counts as timestamped even without an attestation of its own
+        assert!(storage.check_duplicate(&[5u8; 32]).await.unwrap());
+        assert!(storage.get_attestation(&[5u8; 32]).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_get_next_sequence() {
         let storage = setup_test_db().await;
@@ -1177,4 +1504,79 @@ mod tests {
 
         assert_eq!(storage.count_batches().await.unwrap(), 1);
     }
+
+    #[tokio::test]
+    async fn test_batches_below_cross_anchor_threshold() {
+        let storage = setup_test_db().await;
+
+        let hash = [7u8; 32];
+        let signed = create_test_attestation(hash, 1);
+        storage.store_attestation(&signed).await.unwrap();
+
+        let batch = AttestationBatch {
+            id: 0,
+            network_id: "test-network".to_string(),
+            merkle_root: [3u8; 32],
+            period_start: 1700000000,
+            period_end: 1700003600,
+            attestation_count: 1,
+        };
+        let batch_id = storage.store_batch(&batch, &[hash]).await.unwrap();
+        let batch = AttestationBatch {
+            id: batch_id as u64,
+            ..batch
+        };
+
+        let pending = storage
+            .get_batches_below_cross_anchor_threshold(1700000000, 1)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0], batch);
+
+        // Batches closed before the retry window are ignored
+        let pending = storage
+            .get_batches_below_cross_anchor_threshold(1700003601, 1)
+            .await
+            .unwrap();
+        assert!(pending.is_empty());
+
+        let cross_anchor = CrossAnchor {
+            statement: CrossAnchorStatement::new(batch.clone(), "peer-net".to_string()),
+            signatures: vec![WitnessSignature {
+                witness_id: "peer-1".to_string(),
+                signature: vec![1, 2, 3],
+            }],
+        };
+        storage.store_cross_anchor(&cross_anchor).await.unwrap();
+
+        let stored = storage.get_cross_anchors(batch_id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].statement.batch, batch);
+        assert_eq!(stored[0].statement.version, witness_core::CROSS_ANCHOR_VERSION);
+
+        // One peer network reached: below a threshold of 2, meets a threshold of 1
+        let pending = storage
+            .get_batches_below_cross_anchor_threshold(1700000000, 2)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        let pending = storage
+            .get_batches_below_cross_anchor_threshold(1700000000, 1)
+            .await
+            .unwrap();
+        assert!(pending.is_empty());
+
+        // Rows stored before the version column are legacy cross-anchors
+        sqlx::query(
+            "INSERT INTO cross_anchors (batch_id, witnessing_network, timestamp, created_at) VALUES (?1, 'old-net', 1700000000, 1700000000)",
+        )
+        .bind(batch_id)
+        .execute(&storage.pool)
+        .await
+        .unwrap();
+        let stored = storage.get_cross_anchors(batch_id).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(stored.iter().any(|ca| ca.statement.witnessing_network == "old-net" && ca.statement.is_legacy()));
+    }
 }
@@ -1,10 +1,34 @@
 use anyhow::{Context, Result};
-use reqwest::Client;
+use ed25519_dalek::SigningKey;
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
 use std::time::Duration;
-use witness_core::{Attestation, SignRequest, SignResponse, WitnessInfo};
+use witness_core::{
+    Attestation, CrossAnchorSignRequest, CrossAnchorStatement, RequestAuth, SignRequest,
+    SignResponse, SignRootRequest, WitnessInfo,
+};
+
+/// A witness answered a sign request but refused it (4xx)
+///
+/// The witness is up, so this says nothing about its health.
+#[derive(Debug)]
+pub struct SignRefused {
+    pub witness_id: String,
+    pub status: reqwest::StatusCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for SignRefused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Witness {} returned error {}: {}", self.witness_id, self.status, self.message)
+    }
+}
+
+impl std::error::Error for SignRefused {}
 
 pub struct WitnessClient {
     client: Client,
+    auth_key: Option<SigningKey>,
 }
 
 impl WitnessClient {
@@ -14,7 +38,39 @@ impl WitnessClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client }
+        Self {
+            client,
+            auth_key: None,
+        }
+    }
+
+    /// Authenticate sign requests with this gateway key
+    pub fn with_auth_key(mut self, auth_key: SigningKey) -> Self {
+        self.auth_key = Some(auth_key);
+        self
+    }
+
+    /// POST a JSON body to a witness, signed with the gateway key if configured
+    fn post_signed<T: Serialize>(
+        &self,
+        witness: &WitnessInfo,
+        path: &str,
+        request: &T,
+    ) -> Result<RequestBuilder> {
+        let body = serde_json::to_vec(request)?;
+
+        let mut builder = self
+            .client
+            .post(format!("{}{}", witness.endpoint, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        if let Some(auth_key) = &self.auth_key {
+            for (name, value) in RequestAuth::sign(path, &body, auth_key).headers() {
+                builder = builder.header(name, value);
+            }
+        }
+
+        Ok(builder.body(body))
     }
 
     pub async fn request_signature(
@@ -22,16 +78,62 @@ impl WitnessClient {
         witness: &WitnessInfo,
         attestation: &Attestation,
     ) -> Result<SignResponse> {
-        let url = format!("{}/v1/sign", witness.endpoint);
+        // Merkle roots over micro-batches have their own sign request
+        let builder = if attestation.is_root() {
+            let request = SignRootRequest {
+                attestation: attestation.clone(),
+            };
+            self.post_signed(witness, "/v1/sign/root", &request)?
+        } else {
+            let request = SignRequest {
+                attestation: attestation.clone(),
+            };
+            self.post_signed(witness, "/v1/sign", &request)?
+        };
+
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("Failed to connect to witness: {}", witness.id))?;
 
-        let request = SignRequest {
-            attestation: attestation.clone(),
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            if status.is_client_error() {
+                return Err(SignRefused {
+                    witness_id: witness.id.clone(),
+                    status,
+                    message: error_text,
+                }
+                .into());
+            }
+            anyhow::bail!(
+                "Witness {} returned error {}: {}",
+                witness.id,
+                status,
+                error_text
+            );
+        }
+
+        let sign_response: SignResponse = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse response from witness: {}", witness.id))?;
+
+        Ok(sign_response)
+    }
+
+    pub async fn request_cross_anchor_signature(
+        &self,
+        witness: &WitnessInfo,
+        statement: &CrossAnchorStatement,
+    ) -> Result<SignResponse> {
+        let request = CrossAnchorSignRequest {
+            statement: statement.clone(),
         };
 
         let response = self
-            .client
-            .post(&url)
-            .json(&request)
+            .post_signed(witness, "/v1/sign/cross-anchor", &request)?
             .send()
             .await
             .with_context(|| format!("Failed to connect to witness: {}", witness.id))?;
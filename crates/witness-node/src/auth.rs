@@ -0,0 +1,125 @@
+//! Request authentication
+//!
+//! When `authorized_gateways` is set, the signing endpoints only accept
+//! requests signed by one of those gateway keys, made within the allowed
+//! clock skew, with a nonce this node has not seen before. The DKG endpoints
+//! always require the same kind of signature from a `dkg_coordinators` key.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use witness_core::RequestAuth;
+
+use crate::server::{AppError, WitnessServer};
+
+/// Largest sign request body accepted
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Nonces seen within the clock skew window, with their request timestamps
+#[derive(Default)]
+pub struct NonceCache {
+    seen: Mutex<HashMap<String, u64>>,
+}
+
+impl NonceCache {
+    /// Record a nonce; false if it was already used
+    pub(crate) fn insert(&self, nonce: &str, timestamp: u64, now: u64, max_age: u64) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+
+        // Anything older than the skew window would be rejected as stale anyway
+        seen.retain(|_, &mut seen_at| now.saturating_sub(seen_at) <= max_age);
+
+        if seen.contains_key(nonce) {
+            return false;
+        }
+        seen.insert(nonce.to_string(), timestamp);
+        true
+    }
+}
+
+pub async fn require_gateway_auth(
+    State(server): State<WitnessServer>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if server.config.authorized_gateways.is_empty() {
+        return Ok(next.run(request).await);
+    }
+
+    let request = authenticate(&server, request, &server.config.authorized_gateways, "gateway").await?;
+    Ok(next.run(request).await)
+}
+
+/// DKG endpoints only answer configured coordinators, and only while DKG is enabled
+pub async fn require_coordinator_auth(
+    State(server): State<WitnessServer>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if server.config.dkg_dir.is_none() {
+        return Err(AppError::DkgDisabled);
+    }
+
+    let request = authenticate(&server, request, &server.config.dkg_coordinators, "coordinator").await?;
+    Ok(next.run(request).await)
+}
+
+/// Check a request's signature, freshness and nonce against `authorized` keys
+async fn authenticate(
+    server: &WitnessServer,
+    request: Request,
+    authorized: &[String],
+    role: &str,
+) -> Result<Request, AppError> {
+    let config = &server.config;
+
+    let auth = RequestAuth::from_headers(|name| {
+        request.headers().get(name).and_then(|value| value.to_str().ok())
+    })
+    .ok_or_else(|| AppError::Unauthorized(format!("Missing {} authentication headers", role)))?;
+
+    if !authorized
+        .iter()
+        .any(|key| key.eq_ignore_ascii_case(&auth.gateway_key))
+    {
+        tracing::warn!("Request from unauthorized {} key {}", role, auth.gateway_key);
+        return Err(AppError::Unauthorized(format!(
+            "The {} key is not authorized for this witness",
+            role
+        )));
+    }
+
+    let now = crate::config::now();
+    if auth.timestamp.abs_diff(now) > config.primary.max_clock_skew {
+        return Err(AppError::Unauthorized(format!(
+            "The {} authentication timestamp is outside the allowed clock skew",
+            role
+        )));
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::Unauthorized("Unreadable request body".to_string()))?;
+
+    auth.verify(parts.uri.path(), &bytes).map_err(|_| {
+        tracing::warn!("Invalid request signature from {} {}", role, auth.gateway_key);
+        AppError::Unauthorized(format!("Invalid {} signature", role))
+    })?;
+
+    // Only remember nonces of authentic requests, so strangers cannot fill the cache
+    if !server
+        .nonces
+        .insert(&auth.nonce, auth.timestamp, now, config.primary.max_clock_skew)
+    {
+        tracing::warn!("Replayed request from {} {}", role, auth.gateway_key);
+        return Err(AppError::Unauthorized(format!("Replayed {} request", role)));
+    }
+
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
@@ -0,0 +1,375 @@
+//! Independent time validation
+//!
+//! The node polls a set of HTTP time sources and reads each response's `Date`
+//! header. Each response bounds the local clock's offset to an interval that
+//! accounts for the one-second header resolution and the round trip.
+//! Marzullo's algorithm then finds the offset most sources agree on. If too
+//! few sources agree, the estimate is stale, or the local clock may be off by
+//! more than `max_offset`, the node refuses to sign. Otherwise witnesses that
+//! share one NTP server could all be fooled together.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time sources to check the local clock against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeConfig {
+    /// HTTP(S) URLs whose `Date` response header is trusted as a time reading
+    pub sources: Vec<String>,
+
+    /// Sources that must agree before the clock is trusted
+    #[serde(default = "default_min_sources")]
+    pub min_sources: usize,
+
+    /// Largest tolerated offset of the local clock (seconds)
+    #[serde(default = "default_max_offset")]
+    pub max_offset: u64,
+
+    /// How often to poll the sources (seconds)
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: u64,
+
+    /// Age after which an estimate no longer counts (seconds)
+    #[serde(default = "default_max_age")]
+    pub max_age: u64,
+}
+
+fn default_min_sources() -> usize {
+    2
+}
+
+fn default_max_offset() -> u64 {
+    5
+}
+
+fn default_poll_interval() -> u64 {
+    60
+}
+
+fn default_max_age() -> u64 {
+    300
+}
+
+impl TimeConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.min_sources == 0 || self.min_sources > self.sources.len() {
+            anyhow::bail!(
+                "time.min_sources must be between 1 and the number of sources ({})",
+                self.sources.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Bounds on the local clock's offset from one source (true time minus local time)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sample {
+    low_ms: i64,
+    high_ms: i64,
+}
+
+impl Sample {
+    /// A source reported `date` (whole seconds) for a request sent at local
+    /// time `sent_ms` and answered at `received_ms`
+    fn new(date: SystemTime, sent_ms: i64, received_ms: i64) -> Option<Self> {
+        let date_ms = date.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64;
+
+        Some(Self {
+            low_ms: date_ms - received_ms,
+            high_ms: date_ms + 1000 - sent_ms,
+        })
+    }
+}
+
+/// Validated estimate of the local clock's offset
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeEstimate {
+    /// Smallest plausible offset (milliseconds, true time minus local time)
+    pub offset_low_ms: i64,
+
+    /// Largest plausible offset (milliseconds)
+    pub offset_high_ms: i64,
+
+    /// Sources whose readings are consistent with the estimate
+    pub agreeing: usize,
+
+    /// Sources that answered
+    pub responding: usize,
+
+    #[serde(skip)]
+    measured: Instant,
+}
+
+/// Marzullo's algorithm: the interval contained in the most samples
+fn intersect(samples: &[Sample]) -> Option<(i64, i64, usize)> {
+    // Starts sort before ends at the same point, so touching intervals overlap
+    let mut edges: Vec<(i64, u8)> = samples
+        .iter()
+        .flat_map(|s| [(s.low_ms, 0), (s.high_ms, 1)])
+        .collect();
+    edges.sort();
+
+    let mut best: Option<(i64, i64, usize)> = None;
+    let mut count = 0;
+
+    for (i, &(value, kind)) in edges.iter().enumerate() {
+        if kind == 0 {
+            count += 1;
+            if best.is_none_or(|(_, _, n)| count > n) {
+                best = Some((value, edges[i + 1].0, count));
+            }
+        } else {
+            count -= 1;
+        }
+    }
+
+    best
+}
+
+pub struct ClockMonitor {
+    config: Option<TimeConfig>,
+    client: reqwest::Client,
+    estimate: RwLock<Option<TimeEstimate>>,
+}
+
+impl ClockMonitor {
+    pub fn new(config: Option<TimeConfig>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            config,
+            client,
+            estimate: RwLock::new(None),
+        }
+    }
+
+    /// Poll every source once and update the estimate; a failed poll clears it,
+    /// so the node stops signing until the sources agree again
+    pub async fn poll(&self) {
+        let Some(config) = &self.config else {
+            return;
+        };
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for source in &config.sources {
+            let client = self.client.clone();
+            let source = source.clone();
+            tasks.spawn(async move {
+                let sample = query(&client, &source).await;
+                if let Err(e) = &sample {
+                    tracing::warn!("Time source {} failed: {:#}", source, e);
+                }
+                sample.ok()
+            });
+        }
+
+        let mut samples = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            if let Ok(Some(sample)) = result {
+                samples.push(sample);
+            }
+        }
+
+        let Some((low, high, agreeing)) = intersect(&samples) else {
+            tracing::warn!("No time source answered; clock cannot be validated");
+            *self.estimate.write().unwrap() = None;
+            return;
+        };
+
+        if agreeing < config.min_sources {
+            tracing::warn!(
+                "Only {} of {} time sources agree (need {}); clock cannot be validated",
+                agreeing,
+                samples.len(),
+                config.min_sources
+            );
+            *self.estimate.write().unwrap() = None;
+            return;
+        }
+
+        tracing::debug!(
+            "Clock offset between {} ms and {} ms ({} of {} sources agree)",
+            low,
+            high,
+            agreeing,
+            samples.len()
+        );
+
+        *self.estimate.write().unwrap() = Some(TimeEstimate {
+            offset_low_ms: low,
+            offset_high_ms: high,
+            agreeing,
+            responding: samples.len(),
+            measured: Instant::now(),
+        });
+    }
+
+    /// Keep polling the sources in the background, after an initial `poll`
+    pub fn start(self: Arc<Self>) {
+        let Some(config) = &self.config else {
+            return;
+        };
+        let period = Duration::from_secs(config.poll_interval.max(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                self.poll().await;
+            }
+        });
+    }
+
+    /// Whether the local clock can be trusted for signing, with the reason if not
+    pub fn check(&self) -> Result<(), String> {
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+
+        let estimate = self.estimate.read().unwrap();
+        let Some(estimate) = estimate.as_ref() else {
+            return Err("no agreement among time sources yet".to_string());
+        };
+
+        if estimate.measured.elapsed() > Duration::from_secs(config.max_age) {
+            return Err("time estimate is stale".to_string());
+        }
+
+        // Every offset the sources allow must be within bounds
+        let max_offset_ms = (config.max_offset * 1000) as i64;
+        if estimate.offset_low_ms < -max_offset_ms || estimate.offset_high_ms > max_offset_ms {
+            return Err(format!(
+                "local clock is off by between {} ms and {} ms (max {} s)",
+                estimate.offset_low_ms, estimate.offset_high_ms, config.max_offset
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Clock status for `/health`, if validation is enabled
+    pub fn health(&self) -> Option<serde_json::Value> {
+        self.config.as_ref()?;
+
+        let mut health = serde_json::json!({
+            "trusted": self.check().is_ok(),
+            "estimate": *self.estimate.read().unwrap(),
+        });
+        if let Err(reason) = self.check() {
+            health["reason"] = reason.into();
+        }
+
+        Some(health)
+    }
+}
+
+fn local_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+async fn query(client: &reqwest::Client, source: &str) -> anyhow::Result<Sample> {
+    let sent_ms = local_ms();
+    let response = client.head(source).send().await?;
+    let received_ms = local_ms();
+
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .ok_or_else(|| anyhow::anyhow!("no Date header"))?
+        .to_str()?;
+    let date = httpdate::parse_http_date(date)?;
+
+    Sample::new(date, sent_ms, received_ms).ok_or_else(|| anyhow::anyhow!("Date before 1970"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::header, routing::get, Router};
+
+    fn sample(low_ms: i64, high_ms: i64) -> Sample {
+        Sample { low_ms, high_ms }
+    }
+
+    #[test]
+    fn test_intersect_ignores_outliers() {
+        // Two sources agree around +200 ms; a third claims an hour ahead
+        let samples = [sample(-300, 700), sample(100, 1100), sample(3_600_000, 3_601_000)];
+        assert_eq!(intersect(&samples), Some((100, 700, 2)));
+
+        assert_eq!(intersect(&[]), None);
+    }
+
+    /// Serve a `Date` header `skew` seconds away from the real time
+    async fn mock_time_server(skew: i64) -> String {
+        let skew_ahead = skew >= 0;
+        let app = Router::new().route(
+            "/",
+            get(move || async move {
+                let skew = Duration::from_secs(skew.unsigned_abs());
+                let date = if skew_ahead { SystemTime::now() + skew } else { SystemTime::now() - skew };
+                [(header::DATE, httpdate::fmt_http_date(date))]
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}/", addr)
+    }
+
+    fn monitor(sources: Vec<String>, min_sources: usize) -> ClockMonitor {
+        ClockMonitor::new(Some(TimeConfig {
+            sources,
+            min_sources,
+            max_offset: 5,
+            poll_interval: 60,
+            max_age: 300,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_clock_monitor_with_mock_sources() {
+        let honest = vec![mock_time_server(0).await, mock_time_server(0).await];
+        let liar = mock_time_server(3600).await;
+
+        // Untrusted until the first successful poll
+        let clock = monitor([honest.clone(), vec![liar.clone()]].concat(), 2);
+        assert!(clock.check().is_err());
+        clock.poll().await;
+        assert!(clock.check().is_ok());
+
+        // A majority of sources an hour ahead means the local clock is wrong
+        let skewed = vec![mock_time_server(3600).await, liar];
+        let clock = monitor([skewed, vec![honest[0].clone()]].concat(), 2);
+        clock.poll().await;
+        assert!(clock.check().is_err());
+
+        // Not enough agreeing sources
+        let clock = monitor(vec![honest[0].clone(), "http://127.0.0.1:1/".to_string()], 2);
+        clock.poll().await;
+        assert!(clock.check().is_err());
+
+        // A failed poll drops an earlier good estimate instead of signing on it
+        let clock = monitor(vec!["http://127.0.0.1:1/".to_string(); 2], 2);
+        *clock.estimate.write().unwrap() = Some(TimeEstimate {
+            offset_low_ms: 0,
+            offset_high_ms: 0,
+            agreeing: 2,
+            responding: 2,
+            measured: Instant::now(),
+        });
+        assert!(clock.check().is_ok());
+        clock.poll().await;
+        assert!(clock.check().is_err());
+    }
+}
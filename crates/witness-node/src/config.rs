@@ -1,107 +1,508 @@
 use anyhow::{Context, Result};
-use blst::min_sig::{PublicKey as BlsPublicKey, SecretKey as BlsSecretKey};
-use ed25519_dalek::{SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
-use witness_core::SignatureScheme;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use witness_core::{RetiredKey, SignatureScheme};
+use zeroize::Zeroizing;
+
+use crate::clock::TimeConfig;
+use crate::keystore::{Keystore, PassphraseSource, WitnessKey};
+use crate::remote_signer::{RemoteSigner, RemoteSignerConfig};
+use crate::signer::{LocalSigner, Pkcs11Config, Signer};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WitnessNodeConfig {
     /// Unique identifier for this witness
     pub id: String,
 
-    /// Signature scheme (ed25519 or bls)
-    #[serde(default)]
-    pub signature_scheme: SignatureScheme,
+    /// The network this witness belongs to (its primary network)
+    #[serde(flatten)]
+    pub primary: NetworkProfile,
 
-    /// Private key (hex encoded) - Ed25519 (32 bytes) or BLS (32 bytes)
-    pub private_key: String,
+    /// Further networks this node witnesses for, each with its own key
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub networks: Vec<NetworkProfile>,
 
     /// HTTP port to listen on
     #[serde(default = "default_port")]
     pub port: u16,
 
-    /// Network ID this witness belongs to
+    /// Independent time sources; when set, the node refuses to sign unless
+    /// enough of them agree that the local clock is accurate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimeConfig>,
+
+    /// Ed25519 public keys (hex encoded) of gateways allowed to request
+    /// signatures. If empty, any host that can reach the node may.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorized_gateways: Vec<String>,
+
+    /// Directory DKG ceremonies write the resulting key-share config to.
+    /// The DKG endpoints are disabled unless this is set. Ceremonies always
+    /// produce a key for the primary network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dkg_dir: Option<PathBuf>,
+
+    /// Ed25519 public keys (hex encoded) of coordinators allowed to drive
+    /// DKG ceremonies. Required when `dkg_dir` is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dkg_coordinators: Vec<String>,
+
+    /// Directory for state kept across restarts. Networks without their own
+    /// `signing_log` keep it here, in `<network_id>.signing-log.jsonl`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Kept only while DKG is enabled, to encrypt new key shares
+    #[serde(skip)]
+    passphrase: KeptPassphrase,
+}
+
+/// One network a witness node signs for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    /// Network ID
     pub network_id: String,
 
+    /// Signing key (private key, keystore, PKCS#11 token or remote signer)
+    #[serde(flatten)]
+    pub keys: KeyConfig,
+
     /// Maximum clock skew allowed (seconds)
     #[serde(default = "default_max_clock_skew")]
     pub max_clock_skew: u64,
+
+    /// Sign attestations using the legacy v0 encoding (no domain separation).
+    /// Only enable while gateways are being upgraded.
+    #[serde(default)]
+    pub allow_legacy_encoding: bool,
+
+    /// Append-only log of signed sequences, kept so the node never signs two
+    /// conflicting attestations. Defaults to a file in `data_dir`; with
+    /// neither set the log lives in memory only and is lost on restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_log: Option<PathBuf>,
+
+    /// Signed network manifest this witness serves at /v1/manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Keys retired by earlier rotations, still advertised via /v1/info
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_keys: Vec<RetiredKey>,
+}
+
+/// Where a witness's signing key lives, shared by witness nodes and signer daemons
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyConfig {
+    /// Signature scheme (ed25519, bls or threshold_bls)
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+
+    /// Private key (hex encoded) - Ed25519 (32 bytes), BLS (32 bytes) or BLS key share.
+    /// Stored in plaintext; prefer `keystore`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+
+    /// Encrypted keystore holding the private key (see `witness-node keygen`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<PathBuf>,
+
+    /// Ed25519 key on a PKCS#11 token (HSM), instead of a private key or keystore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pkcs11: Option<Pkcs11Config>,
+
+    /// Signer daemon holding the key on another host (see `witness-node signer`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
+
+    /// Key to switch to at a scheduled time (staged rotation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_key: Option<KeyRotation>,
+
+    /// Signer the keys were unlocked into
+    #[serde(skip)]
+    unlocked: UnlockedKeys,
+}
+
+/// A scheduled key rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// New private key (hex encoded), same scheme as the current key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+
+    /// Encrypted keystore holding the new key, unlocked with the same passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<PathBuf>,
+
+    /// Unix time from which attestations are signed with the new key
+    pub activate_at: u64,
 }
 
 fn default_port() -> u16 {
     3000
 }
 
-fn default_max_clock_skew() -> u64 {
+pub(crate) fn default_max_clock_skew() -> u64 {
     300 // 5 minutes
 }
 
+/// Keystore passphrase kept for DKG, never printed
+#[derive(Clone, Default)]
+struct KeptPassphrase(Option<Arc<Zeroizing<Vec<u8>>>>);
+
+impl std::fmt::Debug for KeptPassphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+/// Secrets read while unlocking signers, each read from the source at most once
+#[derive(Default)]
+pub struct UnlockSecrets {
+    /// Passphrase shared by every keystore
+    pub passphrase: Option<Zeroizing<Vec<u8>>>,
+    /// PIN shared by every PKCS#11 token
+    pub pin: Option<Zeroizing<Vec<u8>>>,
+}
+
+/// The signer a `KeyConfig` was unlocked into
+#[derive(Clone, Default)]
+struct UnlockedKeys(Option<Arc<dyn Signer>>);
+
+impl std::fmt::Debug for UnlockedKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("UnlockedKeys")
+            .field(&self.0.as_ref().map(|signer| signer.describe()))
+            .finish()
+    }
+}
+
 impl WitnessNodeConfig {
-    pub fn load(path: &Path) -> Result<Self> {
+    /// Load the config and unlock its signers, reading the keystore passphrase
+    /// or token PIN from `passphrase` if one is needed
+    pub fn load(path: &Path, passphrase: &PassphraseSource) -> Result<Self> {
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read config file: {:?}", path))?;
 
-        let config: WitnessNodeConfig = serde_json::from_str(&content)
+        let mut config: WitnessNodeConfig = serde_json::from_str(&content)
             .with_context(|| "Failed to parse config JSON")?;
 
-        // Validate private key
-        match config.signature_scheme {
-            SignatureScheme::Ed25519 => {
-                config.ed25519_signing_key()
-                    .with_context(|| "Invalid Ed25519 private key in configuration")?;
+        if let Some(time) = &config.time {
+            time.validate()?;
+        }
+
+        for key in &config.authorized_gateways {
+            witness_core::decode_public_key(key)
+                .with_context(|| format!("Invalid authorized gateway key: {}", key))?;
+        }
+
+        for key in &config.dkg_coordinators {
+            witness_core::decode_public_key(key)
+                .with_context(|| format!("Invalid DKG coordinator key: {}", key))?;
+        }
+        if config.dkg_dir.is_some() && config.dkg_coordinators.is_empty() {
+            anyhow::bail!("dkg_dir requires at least one key in dkg_coordinators");
+        }
+
+        if let Some(dir) = &config.data_dir {
+            let profiles = std::iter::once(&mut config.primary).chain(&mut config.networks);
+            for profile in profiles.filter(|profile| profile.signing_log.is_none()) {
+                if matches!(profile.network_id.as_str(), "" | "." | "..") || profile.network_id.contains(['/', '\\']) {
+                    anyhow::bail!(
+                        "Network {:?} cannot name a file in data_dir; set its signing_log",
+                        profile.network_id
+                    );
+                }
+                profile.signing_log = Some(dir.join(format!("{}.signing-log.jsonl", profile.network_id)));
+            }
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create data_dir: {:?}", dir))?;
+        }
+
+        let mut network_ids = std::collections::HashSet::new();
+        let mut signing_logs = std::collections::HashSet::new();
+        for profile in config.profiles() {
+            if !network_ids.insert(&profile.network_id) {
+                anyhow::bail!("Network {} is configured more than once", profile.network_id);
             }
-            SignatureScheme::BLS => {
-                config.bls_secret_key()
-                    .with_context(|| "Invalid BLS private key in configuration")?;
+            // Sequences are per network, so networks cannot share a signing log
+            if let Some(path) = &profile.signing_log {
+                if !signing_logs.insert(path) {
+                    anyhow::bail!(
+                        "Network {} shares the signing log {:?} with another network",
+                        profile.network_id,
+                        path
+                    );
+                }
             }
         }
 
+        // Every keystore is decrypted with the same passphrase, and every
+        // token opened with the same PIN, each read once
+        let mut unlocked = UnlockSecrets::default();
+        config.primary.keys.unlock(passphrase, &mut unlocked)?;
+        for profile in &mut config.networks {
+            profile
+                .keys
+                .unlock(passphrase, &mut unlocked)
+                .with_context(|| format!("Failed to unlock the key for network {}", profile.network_id))?;
+        }
+
+        if config.dkg_dir.is_some() {
+            config.passphrase = KeptPassphrase(unlocked.passphrase.map(Arc::new));
+        }
         Ok(config)
     }
 
-    // Ed25519 methods
-    pub fn ed25519_signing_key(&self) -> Result<SigningKey> {
-        let key_bytes = hex::decode(&self.private_key)
-            .with_context(|| "Failed to decode private key")?;
+    /// The primary network followed by any further hosted networks
+    pub fn profiles(&self) -> impl Iterator<Item = &NetworkProfile> {
+        std::iter::once(&self.primary).chain(&self.networks)
+    }
 
-        let key_array: [u8; 32] = key_bytes
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("Private key must be 32 bytes"))?;
+    /// Profile for a hosted network
+    pub fn profile(&self, network_id: &str) -> Option<&NetworkProfile> {
+        self.profiles().find(|profile| profile.network_id == network_id)
+    }
 
-        Ok(SigningKey::from_bytes(&key_array))
+    /// Passphrase that unlocked this witness's keystores, if DKG needs it
+    pub fn keystore_passphrase(&self) -> Option<&[u8]> {
+        self.passphrase.0.as_ref().map(|passphrase| passphrase.as_slice())
     }
 
-    pub fn ed25519_verifying_key(&self) -> Result<VerifyingKey> {
-        let signing_key = self.ed25519_signing_key()?;
-        Ok(signing_key.verifying_key())
+    /// Signer holding the primary network's keys
+    pub fn signer(&self) -> &Arc<dyn Signer> {
+        self.primary.signer()
+    }
+
+    /// Public key for the primary network
+    pub fn public_key(&self) -> String {
+        self.primary.public_key()
     }
+}
 
-    // BLS methods
-    pub fn bls_secret_key(&self) -> Result<BlsSecretKey> {
-        Ok(witness_core::decode_bls_secret_key(&self.private_key)?)
+impl NetworkProfile {
+    /// Signer holding this network's keys
+    pub fn signer(&self) -> &Arc<dyn Signer> {
+        self.keys.signer()
     }
 
-    pub fn bls_public_key(&self) -> Result<BlsPublicKey> {
-        let secret_key = self.bls_secret_key()?;
-        Ok(secret_key.sk_to_pk())
+    /// Whether the scheduled rotation has taken effect at `timestamp`
+    fn rotated_at(&self, timestamp: u64) -> bool {
+        self.signer()
+            .rotation()
+            .is_some_and(|activate_at| timestamp >= activate_at)
     }
 
-    // Generic methods
     pub fn public_key(&self) -> String {
-        match self.signature_scheme {
-            SignatureScheme::Ed25519 => {
-                self.ed25519_verifying_key()
-                    .map(|k| witness_core::encode_public_key(&k))
-                    .unwrap_or_else(|_| "invalid".to_string())
+        self.signer().public_key_at(now())
+    }
+
+    /// Activation time of the active key and the keys it replaced
+    pub fn key_history(&self) -> (Option<u64>, Vec<RetiredKey>) {
+        let mut previous_keys = self.previous_keys.clone();
+        let mut activated_at = previous_keys.iter().map(|key| key.retired_at).max();
+
+        if let Some(activate_at) = self.signer().rotation().filter(|_| self.rotated_at(now())) {
+            // The replaced key signed everything dated before the switch
+            let retired = activate_at.saturating_sub(1);
+            previous_keys.push(RetiredKey {
+                pubkey: self.signer().public_key_at(retired),
+                activated_at,
+                retired_at: activate_at,
+                pop: self.signer().pop_at(retired),
+            });
+            activated_at = Some(activate_at);
+        }
+
+        (activated_at, previous_keys)
+    }
+
+    /// Public key of a scheduled rotation that has not taken effect yet
+    pub fn pending_key(&self) -> Option<(String, u64)> {
+        let activate_at = self.signer().rotation().filter(|_| !self.rotated_at(now()))?;
+        Some((self.signer().public_key_at(activate_at), activate_at))
+    }
+
+    /// Proof of possession for the active key (bls only)
+    pub fn pop(&self) -> Option<String> {
+        self.signer().pop_at(now())
+    }
+}
+
+impl KeyConfig {
+    /// Open the configured signer: a remote signer daemon, a PKCS#11 token,
+    /// or keys decoded into memory. Keystores are decrypted with the passphrase
+    /// and tokens opened with the PIN in `secrets`, each read from `source` the
+    /// first time one is needed.
+    pub fn unlock(&mut self, source: &PassphraseSource, secrets: &mut UnlockSecrets) -> Result<()> {
+        let local_key = self.private_key.is_some() || self.keystore.is_some() || self.next_key.is_some();
+
+        if let Some(remote) = &self.remote_signer {
+            if local_key || self.pkcs11.is_some() {
+                anyhow::bail!("remote_signer cannot be combined with private_key, keystore, pkcs11 or next_key");
             }
-            SignatureScheme::BLS => {
-                self.bls_public_key()
-                    .map(|k| witness_core::encode_bls_public_key(&k))
-                    .unwrap_or_else(|_| "invalid".to_string())
+
+            let signer = RemoteSigner::connect(remote, self.signature_scheme)
+                .with_context(|| format!("Failed to connect to remote signer {}", remote.address))?;
+
+            self.unlocked = UnlockedKeys(Some(Arc::new(signer)));
+            return Ok(());
+        }
+
+        if let Some(pkcs11) = &self.pkcs11 {
+            if local_key {
+                anyhow::bail!("pkcs11 cannot be combined with private_key, keystore or next_key");
+            }
+            if self.signature_scheme != SignatureScheme::Ed25519 {
+                anyhow::bail!("pkcs11 only supports the ed25519 signature scheme");
             }
+
+            if secrets.pin.is_none() {
+                secrets.pin = Some(source.read(&format!("PIN for token {:?}: ", pkcs11.token_label))?);
+            }
+            let signer = open_pkcs11(pkcs11, secrets.pin.as_ref().unwrap())
+                .with_context(|| format!("Failed to open PKCS#11 token {:?}", pkcs11.token_label))?;
+
+            self.unlocked = UnlockedKeys(Some(signer));
+            return Ok(());
         }
+
+        let scheme = self.signature_scheme;
+
+        let mut load_key = |private_key: Option<&String>, keystore: Option<&PathBuf>, name: &str| {
+            match (private_key, keystore) {
+                (Some(private_key), None) => WitnessKey::from_hex(scheme, private_key)
+                    .with_context(|| format!("Invalid {} in configuration", name)),
+                (None, Some(path)) => {
+                    let keystore = Keystore::load(path)?;
+                    if (keystore.scheme == SignatureScheme::Ed25519) != (scheme == SignatureScheme::Ed25519) {
+                        anyhow::bail!(
+                            "Keystore {:?} holds a {} key, but the witness uses {}",
+                            path,
+                            keystore.scheme,
+                            scheme
+                        );
+                    }
+                    if secrets.passphrase.is_none() {
+                        secrets.passphrase = Some(source.read(&format!("Passphrase for {:?}: ", path))?);
+                    }
+                    keystore
+                        .decrypt(secrets.passphrase.as_ref().unwrap())
+                        .with_context(|| format!("Failed to unlock keystore {:?}", path))
+                }
+                (Some(_), Some(_)) => anyhow::bail!("Set either private_key or keystore for the {}, not both", name),
+                (None, None) => anyhow::bail!(
+                    "No private_key, keystore, pkcs11 or remote_signer configured for the {}",
+                    name
+                ),
+            }
+        };
+
+        let current = load_key(self.private_key.as_ref(), self.keystore.as_ref(), "private key")?;
+        let next = match &self.next_key {
+            Some(next_key) => Some((
+                load_key(next_key.private_key.as_ref(), next_key.keystore.as_ref(), "next key")?,
+                next_key.activate_at,
+            )),
+            None => None,
+        };
+
+        self.unlocked = UnlockedKeys(Some(Arc::new(LocalSigner::new(scheme, current, next))));
+        Ok(())
     }
 
+    /// Signer holding the keys
+    pub fn signer(&self) -> &Arc<dyn Signer> {
+        self.unlocked
+            .0
+            .as_ref()
+            .expect("KeyConfig::unlock opens the signer")
+    }
+
+    /// Whether the key is stored unencrypted in the config file
+    pub fn is_plaintext(&self) -> bool {
+        self.private_key.is_some()
+            || self
+                .next_key
+                .as_ref()
+                .is_some_and(|next_key| next_key.private_key.is_some())
+    }
+}
+
+#[cfg(unix)]
+fn open_pkcs11(config: &Pkcs11Config, pin: &[u8]) -> Result<Arc<dyn Signer>> {
+    Ok(Arc::new(crate::pkcs11::Pkcs11Signer::open(config, pin)?))
+}
+
+#[cfg(not(unix))]
+fn open_pkcs11(_config: &Pkcs11Config, _pin: &[u8]) -> Result<Arc<dyn Signer>> {
+    anyhow::bail!("PKCS#11 signing is only supported on Unix")
+}
+
+pub(crate) fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_multiple_networks() {
+        let path = std::env::temp_dir().join(format!("witness-config-{}.json", std::process::id()));
+        let mut config = serde_json::json!({
+            "id": "w1",
+            "network_id": "net-a",
+            "private_key": hex::encode([1u8; 32]),
+            "networks": [{
+                "network_id": "net-b",
+                "signature_scheme": "bls",
+                "private_key": hex::encode([2u8; 32]),
+                "max_clock_skew": 30,
+            }],
+        });
+        std::fs::write(&path, config.to_string()).unwrap();
+
+        let loaded = WitnessNodeConfig::load(&path, &PassphraseSource::EnvOrPrompt).unwrap();
+        let ids: Vec<_> = loaded.profiles().map(|profile| profile.network_id.as_str()).collect();
+        assert_eq!(ids, ["net-a", "net-b"]);
+
+        let net_b = loaded.profile("net-b").unwrap();
+        assert_eq!(net_b.keys.signature_scheme, SignatureScheme::BLS);
+        assert_eq!(net_b.max_clock_skew, 30);
+        assert!(net_b.pop().is_some());
+        assert_ne!(net_b.public_key(), loaded.public_key());
+        assert!(loaded.profile("net-c").is_none());
+
+        // Each network may only be configured once
+        config["networks"][0]["network_id"] = "net-a".into();
+        std::fs::write(&path, config.to_string()).unwrap();
+        assert!(WitnessNodeConfig::load(&path, &PassphraseSource::EnvOrPrompt).is_err());
+
+        // and networks cannot share a signing log
+        config["networks"][0]["network_id"] = "net-b".into();
+        config["signing_log"] = "/tmp/witness-shared-log.jsonl".into();
+        config["networks"][0]["signing_log"] = "/tmp/witness-shared-log.jsonl".into();
+        std::fs::write(&path, config.to_string()).unwrap();
+        let error = WitnessNodeConfig::load(&path, &PassphraseSource::EnvOrPrompt).unwrap_err();
+        assert!(error.to_string().contains("shares the signing log"));
+
+        // Networks without their own log default to one in data_dir
+        let data_dir = std::env::temp_dir().join(format!("witness-data-{}", std::process::id()));
+        config["networks"][0]["signing_log"] = serde_json::Value::Null;
+        config["data_dir"] = data_dir.to_str().unwrap().into();
+        std::fs::write(&path, config.to_string()).unwrap();
+        let loaded = WitnessNodeConfig::load(&path, &PassphraseSource::EnvOrPrompt).unwrap();
+        assert_eq!(loaded.primary.signing_log.as_deref(), Some(Path::new("/tmp/witness-shared-log.jsonl")));
+        assert_eq!(loaded.profile("net-b").unwrap().signing_log, Some(data_dir.join("net-b.signing-log.jsonl")));
+        assert!(data_dir.is_dir());
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,567 @@
+//! DKG ceremony endpoints
+//!
+//! A coordinator (`witness dkg`) drives the rounds and relays messages
+//! between nodes; every request must be signed by a `dkg_coordinators` key.
+//! Shares travel encrypted to each recipient's transport key, so the
+//! coordinator never learns them unless a recipient signs a complaint. On
+//! finalize the node writes a threshold BLS config holding its new share to
+//! `dkg_dir`, next to the complaints it answered.
+
+use anyhow::Context;
+use axum::{extract::State, Json};
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::Mutex;
+use witness_core::{
+    DkgComplaint, DkgComplaintsResponse, DkgDeal, DkgDealsRequest, DkgFinalizeRequest,
+    DkgFinalizeResponse, DkgReceiveRequest, DkgRevealRequest, DkgRevealedShare, DkgSession,
+    DkgSharesResponse, DkgStartRequest, SignatureScheme,
+};
+
+use crate::config::WitnessNodeConfig;
+use crate::keystore::{Keystore, WitnessKey};
+use crate::server::{signing_task, AppError, WitnessServer};
+
+/// In-progress ceremonies, by ceremony ID
+#[derive(Default)]
+pub struct DkgSessions {
+    sessions: Mutex<HashMap<String, DkgSession>>,
+}
+
+impl DkgSessions {
+    fn with_session<T>(
+        &self,
+        ceremony_id: &str,
+        f: impl FnOnce(&mut DkgSession) -> witness_core::Result<T>,
+    ) -> Result<T, AppError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(ceremony_id)
+            .ok_or_else(|| AppError::Dkg(format!("Unknown ceremony: {}", ceremony_id)))?;
+
+        f(session).map_err(|e| AppError::Dkg(e.to_string()))
+    }
+}
+
+/// Unsigned complaints against every dealer this node holds no valid share from
+fn open_complaints(session: &DkgSession) -> Vec<DkgComplaint> {
+    session
+        .complaints()
+        .into_iter()
+        .map(|dealer| session.complaint(dealer))
+        .collect()
+}
+
+/// Sign complaints outside the session lock; a remote signer may be slow
+async fn sign_complaints(
+    config: &WitnessNodeConfig,
+    complaints: Vec<DkgComplaint>,
+) -> Result<Vec<DkgComplaint>, AppError> {
+    let signer = config.signer().clone();
+
+    signing_task(move || {
+        complaints
+            .into_iter()
+            .map(|mut complaint| {
+                signer.sign_dkg_complaint(&mut complaint).map_err(|e| {
+                    tracing::error!("Failed to sign with {}: {:#}", signer.describe(), e);
+                    AppError::InternalError
+                })?;
+                Ok(complaint)
+            })
+            .collect()
+    })
+    .await
+}
+
+pub async fn start_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgStartRequest>,
+) -> Result<Json<DkgDeal>, AppError> {
+    // Ceremony IDs end up in file names
+    if request.ceremony_id.is_empty()
+        || !request
+            .ceremony_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(AppError::Dkg(
+            "Ceremony ID may only contain letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+
+    if share_config_path(&server.config, &request.ceremony_id).is_some_and(|path| path.exists()) {
+        return Err(AppError::Dkg(format!(
+            "Ceremony {} already completed on this witness",
+            request.ceremony_id
+        )));
+    }
+
+    let public_key = server.config.public_key();
+    let index = request
+        .participants
+        .iter()
+        .find(|p| p.pubkey == public_key)
+        .map(|p| p.index)
+        .ok_or_else(|| AppError::Dkg("This witness is not a ceremony participant".to_string()))?;
+
+    let session = DkgSession::new(
+        request.ceremony_id.clone(),
+        index,
+        request.threshold,
+        request.participants,
+    )
+    .map_err(|e| AppError::Dkg(e.to_string()))?;
+
+    let mut deal = session.deal().map_err(|e| AppError::Dkg(e.to_string()))?;
+
+    let signer = server.config.signer().clone();
+    let deal = signing_task(move || {
+        signer.sign_dkg_deal(&mut deal).map_err(|e| {
+            tracing::error!("Failed to sign with {}: {:#}", signer.describe(), e);
+            AppError::InternalError
+        })?;
+        Ok(deal)
+    })
+    .await?;
+
+    tracing::info!(
+        "Joined DKG ceremony {} as participant {} ({}-of-{})",
+        request.ceremony_id,
+        index,
+        request.threshold,
+        session.participants().len()
+    );
+
+    // A second start must not replace a ceremony in progress
+    match server.dkg_sessions.sessions.lock().unwrap().entry(request.ceremony_id) {
+        Entry::Occupied(entry) => {
+            tracing::warn!("Refused to restart DKG ceremony {}", entry.key());
+            return Err(AppError::Dkg(format!("Ceremony {} is already in progress", entry.key())));
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(session);
+        }
+    }
+
+    Ok(Json(deal))
+}
+
+pub async fn deals_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgDealsRequest>,
+) -> Result<Json<DkgSharesResponse>, AppError> {
+    let shares = server.dkg_sessions.with_session(&request.ceremony_id, |session| {
+        session.receive_deals(request.deals)?;
+        session.encrypted_shares()
+    })?;
+
+    Ok(Json(DkgSharesResponse { shares }))
+}
+
+pub async fn receive_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgReceiveRequest>,
+) -> Result<Json<DkgComplaintsResponse>, AppError> {
+    let complaints = server.dkg_sessions.with_session(&request.ceremony_id, |session| {
+        for share in &request.shares {
+            if !session.receive_share(share)? {
+                tracing::warn!(
+                    "Share from dealer {} does not match its commitments",
+                    share.dealer
+                );
+            }
+        }
+        Ok(open_complaints(session))
+    })?;
+
+    if !complaints.is_empty() {
+        let dealers: Vec<u32> = complaints.iter().map(|complaint| complaint.dealer).collect();
+        tracing::warn!("Complaining about dealers {:?}", dealers);
+    }
+
+    Ok(Json(DkgComplaintsResponse {
+        complaints: sign_complaints(&server.config, complaints).await?,
+    }))
+}
+
+pub async fn reveal_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgRevealRequest>,
+) -> Result<Json<DkgRevealedShare>, AppError> {
+    let complaint = request.complaint;
+    let share = server.dkg_sessions.with_session(&request.ceremony_id, |session| {
+        session
+            .reveal_share(&complaint)
+            .inspect_err(|e| tracing::warn!("Refused to reveal a share: {}", e))
+    })?;
+
+    tracing::info!(
+        "Revealing share for participant {} in answer to its signed complaint",
+        complaint.complainer
+    );
+
+    Ok(Json(DkgRevealedShare {
+        ceremony_id: request.ceremony_id,
+        dealer: complaint.dealer,
+        recipient: complaint.complainer,
+        share: witness_core::encode_bls_secret_key(&share),
+        complaint,
+    }))
+}
+
+pub async fn resolve_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgRevealedShare>,
+) -> Result<Json<DkgComplaintsResponse>, AppError> {
+    let share = witness_core::decode_bls_secret_key(&request.share)
+        .map_err(|e| AppError::Dkg(e.to_string()))?;
+
+    let complaints = server.dkg_sessions.with_session(&request.ceremony_id, |session| {
+        if !session.accept_revealed_share(request.dealer, &share)? {
+            tracing::warn!(
+                "Revealed share from dealer {} does not match its commitments",
+                request.dealer
+            );
+        }
+        Ok(open_complaints(session))
+    })?;
+
+    Ok(Json(DkgComplaintsResponse {
+        complaints: sign_complaints(&server.config, complaints).await?,
+    }))
+}
+
+pub async fn finalize_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<DkgFinalizeRequest>,
+) -> Result<Json<DkgFinalizeResponse>, AppError> {
+    let (output, answered) = server.dkg_sessions.with_session(&request.ceremony_id, |session| {
+        let output = session
+            .finalize(&request.qualified, &request.complaints, &request.reveals)
+            .inspect_err(|e| tracing::warn!("Refused to finalize: {}", e))?;
+        Ok((output, session.answered_complaints().cloned().collect::<Vec<_>>()))
+    })?;
+
+    let path = write_share_config(&server.config, &request.ceremony_id, &output.share.secret_key)
+        .and_then(|path| {
+            write_answered_complaints(&server.config, &request.ceremony_id, &answered)?;
+            Ok(path)
+        })
+        .map_err(|e| {
+            tracing::error!("Failed to write DKG key share: {:#}", e);
+            AppError::InternalError
+        })?;
+
+    server
+        .dkg_sessions
+        .sessions
+        .lock()
+        .unwrap()
+        .remove(&request.ceremony_id);
+
+    tracing::info!(
+        "DKG ceremony {} complete; key share written to {:?}",
+        request.ceremony_id,
+        path
+    );
+
+    Ok(Json(output.to_response()))
+}
+
+/// Write a copy of this node's config that signs for the primary network
+/// with the new key share
+fn write_share_config(
+    config: &WitnessNodeConfig,
+    ceremony_id: &str,
+    share: &blst::min_sig::SecretKey,
+) -> anyhow::Result<std::path::PathBuf> {
+    let dir = config.dkg_dir.as_ref().context("DKG is not enabled")?;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create DKG directory: {:?}", dir))?;
+
+    // Encrypt the share the same way the node's own key is stored
+    let (private_key, keystore) = match config.keystore_passphrase() {
+        Some(passphrase) => {
+            let path = dir.join(format!("{}-{}.keystore.json", config.id, ceremony_id));
+            let share = WitnessKey::Bls(share.clone());
+            Keystore::encrypt(&share, SignatureScheme::ThresholdBLS, passphrase)?.save(&path)?;
+            (None, Some(path))
+        }
+        None => (Some(witness_core::encode_bls_secret_key(share)), None),
+    };
+
+    // Other hosted networks keep their keys
+    let mut share_config = config.clone();
+    let primary = &mut share_config.primary;
+    primary.keys.signature_scheme = SignatureScheme::ThresholdBLS;
+    primary.keys.private_key = private_key;
+    primary.keys.keystore = keystore;
+    primary.keys.pkcs11 = None;
+    primary.keys.remote_signer = None;
+    primary.keys.next_key = None;
+    primary.manifest_path = None;
+    primary.previous_keys = vec![];
+    share_config.dkg_dir = None;
+
+    share_config.dkg_coordinators = vec![];
+
+    let path = share_config_path(config, ceremony_id).context("DKG is not enabled")?;
+    std::fs::write(&path, serde_json::to_string_pretty(&share_config)?)
+        .with_context(|| format!("Failed to write key share config: {:?}", path))?;
+
+    Ok(path)
+}
+
+/// Where a ceremony's key share config is written
+fn share_config_path(config: &WitnessNodeConfig, ceremony_id: &str) -> Option<std::path::PathBuf> {
+    let dir = config.dkg_dir.as_ref()?;
+    Some(dir.join(format!("{}-{}.json", config.id, ceremony_id)))
+}
+
+/// Keep the signed complaints this node revealed shares for, as evidence
+/// that each revealed share was asked for by its recipient
+fn write_answered_complaints(
+    config: &WitnessNodeConfig,
+    ceremony_id: &str,
+    answered: &[DkgComplaint],
+) -> anyhow::Result<()> {
+    if answered.is_empty() {
+        return Ok(());
+    }
+
+    let dir = config.dkg_dir.as_ref().context("DKG is not enabled")?;
+    let path = dir.join(format!("{}-{}.complaints.json", config.id, ceremony_id));
+    std::fs::write(&path, serde_json::to_string_pretty(answered)?)
+        .with_context(|| format!("Failed to write answered complaints: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keystore::PassphraseSource;
+    use ed25519_dalek::SigningKey;
+    use reqwest::StatusCode;
+    use serde::{de::DeserializeOwned, Serialize};
+    use witness_core::{DkgParticipantInfo, EncryptedShare, RequestAuth};
+
+    struct TestNode {
+        info: DkgParticipantInfo,
+        dkg_dir: std::path::PathBuf,
+    }
+
+    /// Serve a witness node with DKG enabled on a local port
+    async fn spawn_node(index: u32, coordinator: &SigningKey) -> TestNode {
+        let name = format!("witness-dkg-{}-{}", std::process::id(), index);
+        let dkg_dir = std::env::temp_dir().join(&name);
+        let config_path = std::env::temp_dir().join(format!("{}.json", name));
+
+        let key = SigningKey::from_bytes(&[index as u8; 32]);
+        let config = serde_json::json!({
+            "id": format!("w{}", index),
+            "network_id": "dkg-net",
+            "private_key": hex::encode(key.to_bytes()),
+            "dkg_dir": dkg_dir,
+            "dkg_coordinators": [witness_core::encode_public_key(&coordinator.verifying_key())],
+        });
+        std::fs::write(&config_path, config.to_string()).unwrap();
+        let config = WitnessNodeConfig::load(&config_path, &PassphraseSource::EnvOrPrompt).unwrap();
+        std::fs::remove_file(&config_path).unwrap();
+
+        let app = WitnessServer::new(config).unwrap().router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        TestNode {
+            info: DkgParticipantInfo {
+                index,
+                id: format!("w{}", index),
+                endpoint: format!("http://{}", addr),
+                pubkey: witness_core::encode_public_key(&key.verifying_key()),
+                signature_scheme: SignatureScheme::Ed25519,
+            },
+            dkg_dir,
+        }
+    }
+
+    async fn post<T: Serialize>(
+        node: &TestNode,
+        path: &str,
+        body: &T,
+        key: Option<&SigningKey>,
+    ) -> reqwest::Response {
+        let path = format!("/v1/dkg/{}", path);
+        let body = serde_json::to_vec(body).unwrap();
+
+        let mut builder = reqwest::Client::new()
+            .post(format!("{}{}", node.info.endpoint, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(key) = key {
+            for (name, value) in RequestAuth::sign(&path, &body, key).headers() {
+                builder = builder.header(name, value);
+            }
+        }
+
+        builder.body(body).send().await.unwrap()
+    }
+
+    async fn call<T: Serialize, R: DeserializeOwned>(
+        node: &TestNode,
+        path: &str,
+        body: &T,
+        coordinator: &SigningKey,
+    ) -> R {
+        let response = post(node, path, body, Some(coordinator)).await;
+        assert_eq!(response.status(), StatusCode::OK, "{} on {}", path, node.info.id);
+        response.json().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dkg_ceremony_over_http() {
+        let coordinator = SigningKey::from_bytes(&[9; 32]);
+        let stranger = SigningKey::from_bytes(&[8; 32]);
+        let mut nodes = Vec::new();
+        for index in 1..=3 {
+            nodes.push(spawn_node(index, &coordinator).await);
+        }
+
+        let start = DkgStartRequest {
+            ceremony_id: "ceremony-1".to_string(),
+            threshold: 2,
+            participants: nodes.iter().map(|node| node.info.clone()).collect(),
+        };
+
+        // Only the configured coordinator may drive a ceremony
+        let response = post(&nodes[0], "start", &start, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = post(&nodes[0], "start", &start, Some(&stranger)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut deals: Vec<DkgDeal> = Vec::new();
+        for node in &nodes {
+            deals.push(call(node, "start", &start, &coordinator).await);
+        }
+
+        // A second start cannot replace the ceremony in progress
+        let response = post(&nodes[0], "start", &start, Some(&coordinator)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let deals_request = DkgDealsRequest {
+            ceremony_id: "ceremony-1".to_string(),
+            deals,
+        };
+        let mut shares: Vec<EncryptedShare> = Vec::new();
+        for node in &nodes {
+            let response: DkgSharesResponse = call(node, "deals", &deals_request, &coordinator).await;
+            shares.extend(response.shares);
+        }
+
+        // Dealer 1's share for participant 2 is corrupted in transit
+        for share in shares.iter_mut() {
+            if share.dealer == 1 && share.recipient == 2 {
+                share.ciphertext = hex::encode([7u8; 32]);
+            }
+        }
+
+        let mut complaints = Vec::new();
+        for node in &nodes {
+            let request = DkgReceiveRequest {
+                ceremony_id: "ceremony-1".to_string(),
+                shares: shares
+                    .iter()
+                    .filter(|share| share.recipient == node.info.index)
+                    .cloned()
+                    .collect(),
+            };
+            let response: DkgComplaintsResponse = call(node, "receive", &request, &coordinator).await;
+            complaints.extend(response.complaints);
+        }
+        assert_eq!(complaints.len(), 1);
+        let complaint = complaints.remove(0);
+        assert_eq!((complaint.complainer, complaint.dealer), (2, 1));
+        witness_core::verify_dkg_complaint(&complaint, &nodes[1].info).unwrap();
+
+        // Malicious reveals: a complaint rewritten to another recipient, and
+        // one claiming the dealer's own index
+        let mut forged = complaint.clone();
+        forged.complainer = 3;
+        let own = DkgComplaint {
+            complainer: 1,
+            ..complaint.clone()
+        };
+        for complaint in [forged, own] {
+            let request = DkgRevealRequest {
+                ceremony_id: "ceremony-1".to_string(),
+                complaint,
+            };
+            let response = post(&nodes[0], "reveal", &request, Some(&coordinator)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        // The genuine complaint is answered exactly once
+        let request = DkgRevealRequest {
+            ceremony_id: "ceremony-1".to_string(),
+            complaint: complaint.clone(),
+        };
+        let revealed: DkgRevealedShare = call(&nodes[0], "reveal", &request, &coordinator).await;
+        assert_eq!(revealed.complaint, complaint);
+        let response = post(&nodes[0], "reveal", &request, Some(&coordinator)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response: DkgComplaintsResponse = call(&nodes[1], "resolve", &revealed, &coordinator).await;
+        assert!(response.complaints.is_empty());
+
+        // Malicious finalizes: dropping an honest dealer without a complaint,
+        // or for a complaint it answered, dropping the node itself, or
+        // qualifying a dealer that never dealt
+        let malicious = [
+            (1, vec![2, 3], vec![], vec![]),
+            (2, vec![2, 3], vec![complaint.clone()], vec![revealed.clone()]),
+            (0, vec![2, 3], vec![complaint.clone()], vec![]),
+            (2, vec![1, 2, 3, 4], vec![], vec![]),
+        ];
+        for (node, qualified, complaints, reveals) in malicious {
+            let request = DkgFinalizeRequest {
+                ceremony_id: "ceremony-1".to_string(),
+                qualified,
+                complaints,
+                reveals,
+            };
+            let response = post(&nodes[node], "finalize", &request, Some(&coordinator)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+
+        // The ceremony survives refused finalizes and completes honestly
+        let finalize = DkgFinalizeRequest {
+            ceremony_id: "ceremony-1".to_string(),
+            qualified: vec![1, 2, 3],
+            complaints: vec![],
+            reveals: vec![revealed],
+        };
+        let mut outcomes: Vec<DkgFinalizeResponse> = Vec::new();
+        for node in &nodes {
+            outcomes.push(call(node, "finalize", &finalize, &coordinator).await);
+        }
+        for outcome in &outcomes[1..] {
+            assert_eq!(outcome.group_public_key, outcomes[0].group_public_key);
+            assert_eq!(outcome.verification_keys, outcomes[0].verification_keys);
+        }
+
+        for node in &nodes {
+            assert!(node.dkg_dir.join(format!("{}-ceremony-1.json", node.info.id)).exists());
+        }
+
+        // Dealer 1 kept the complaint it revealed a share for
+        let evidence = std::fs::read_to_string(nodes[0].dkg_dir.join("w1-ceremony-1.complaints.json")).unwrap();
+        let evidence: Vec<DkgComplaint> = serde_json::from_str(&evidence).unwrap();
+        assert_eq!(evidence, vec![complaint]);
+
+        // A completed ceremony cannot be run again under the same ID
+        let response = post(&nodes[0], "start", &start, Some(&coordinator)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        for node in &nodes {
+            std::fs::remove_dir_all(&node.dkg_dir).unwrap();
+        }
+    }
+}
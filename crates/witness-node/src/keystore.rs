@@ -0,0 +1,522 @@
+//! Encrypted key storage
+//!
+//! A keystore file holds one witness private key encrypted under a passphrase
+//! (or the contents of a keyfile). The key is derived with scrypt and the
+//! secret is encrypted with AES-256-CTR and authenticated with HMAC-SHA256
+//! (encrypt-then-MAC). The MAC also covers the scheme, public key and KDF
+//! parameters, so none of them can be swapped without the passphrase.
+//!
+//! Decrypted keys are held as parsed key objects, which zeroize themselves
+//! when dropped; passphrases and intermediate buffers are zeroized too.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use anyhow::{Context, Result};
+use blst::min_sig::SecretKey as BlsSecretKey;
+use ed25519_dalek::SigningKey;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use witness_core::SignatureScheme;
+use zeroize::Zeroizing;
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+/// A derived 256-bit key, wiped on drop
+type DerivedKey = Zeroizing<[u8; 32]>;
+
+/// Keystore file format version
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Domain-separation tag for the keystore MAC
+const KEYSTORE_MAC_DOMAIN_TAG: &[u8] = b"WITNESS_KEYSTORE_V1";
+
+/// Environment variable holding the keystore passphrase
+pub const PASSPHRASE_ENV: &str = "WITNESS_KEY_PASSPHRASE";
+
+/// scrypt cost (N = 2^15, about 32 MiB and a fraction of a second)
+const DEFAULT_LOG_N: u8 = 15;
+
+/// A decoded witness private key
+#[derive(Clone)]
+pub enum WitnessKey {
+    Ed25519(SigningKey),
+    Bls(BlsSecretKey),
+}
+
+impl std::fmt::Debug for WitnessKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WitnessKey({})", self.public_key())
+    }
+}
+
+impl WitnessKey {
+    pub fn generate(scheme: SignatureScheme) -> Self {
+        match scheme {
+            SignatureScheme::Ed25519 => Self::Ed25519(witness_core::generate_keypair().0),
+            SignatureScheme::BLS | SignatureScheme::ThresholdBLS => {
+                Self::Bls(witness_core::generate_bls_keypair().0)
+            }
+        }
+    }
+
+    /// Parse a hex-encoded private key for `scheme`
+    pub fn from_hex(scheme: SignatureScheme, private_key: &str) -> Result<Self> {
+        match scheme {
+            SignatureScheme::Ed25519 => {
+                let key_bytes = Zeroizing::new(
+                    hex::decode(private_key).with_context(|| "Failed to decode private key")?,
+                );
+                let key_array: [u8; 32] = key_bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Private key must be 32 bytes"))?;
+                Ok(Self::Ed25519(SigningKey::from_bytes(&key_array)))
+            }
+            SignatureScheme::BLS | SignatureScheme::ThresholdBLS => {
+                Ok(Self::Bls(witness_core::decode_bls_secret_key(private_key)?))
+            }
+        }
+    }
+
+    fn from_bytes(scheme: SignatureScheme, bytes: &[u8]) -> Result<Self> {
+        match scheme {
+            SignatureScheme::Ed25519 => {
+                let key_array: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Private key must be 32 bytes"))?;
+                Ok(Self::Ed25519(SigningKey::from_bytes(&key_array)))
+            }
+            SignatureScheme::BLS | SignatureScheme::ThresholdBLS => Ok(Self::Bls(
+                BlsSecretKey::from_bytes(bytes)
+                    .map_err(|e| anyhow::anyhow!("Invalid BLS private key: {:?}", e))?,
+            )),
+        }
+    }
+
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        match self {
+            Self::Ed25519(key) => Zeroizing::new(key.to_bytes().to_vec()),
+            Self::Bls(key) => Zeroizing::new(key.to_bytes().to_vec()),
+        }
+    }
+
+    pub fn public_key(&self) -> String {
+        match self {
+            Self::Ed25519(key) => witness_core::encode_public_key(&key.verifying_key()),
+            Self::Bls(key) => witness_core::encode_bls_public_key(&key.sk_to_pk()),
+        }
+    }
+}
+
+/// scrypt parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+
+    /// Random salt (hex encoded)
+    pub salt: String,
+}
+
+/// An encrypted private key, as stored on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+
+    pub scheme: SignatureScheme,
+
+    /// Public key (hex encoded), so the key can be identified while locked
+    pub public_key: String,
+
+    pub kdf: KdfParams,
+
+    /// AES-256-CTR initial counter block (hex encoded)
+    pub iv: String,
+
+    /// Encrypted private key (hex encoded)
+    pub ciphertext: String,
+
+    /// HMAC-SHA256 over the other fields (hex encoded)
+    pub mac: String,
+}
+
+impl Keystore {
+    /// Encrypt `key` under `passphrase`
+    pub fn encrypt(key: &WitnessKey, scheme: SignatureScheme, passphrase: &[u8]) -> Result<Self> {
+        Self::encrypt_with_cost(key, scheme, passphrase, DEFAULT_LOG_N)
+    }
+
+    fn encrypt_with_cost(
+        key: &WitnessKey,
+        scheme: SignatureScheme,
+        passphrase: &[u8],
+        log_n: u8,
+    ) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut iv);
+
+        let kdf = KdfParams {
+            log_n,
+            r: 8,
+            p: 1,
+            salt: hex::encode(salt),
+        };
+        let (enc_key, mac_key) = derive_keys(passphrase, &kdf)?;
+
+        let mut ciphertext = key.to_bytes();
+        Aes256Ctr::new(enc_key.as_slice().into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+        let mut keystore = Self {
+            version: KEYSTORE_VERSION,
+            scheme,
+            public_key: key.public_key(),
+            kdf,
+            iv: hex::encode(iv),
+            ciphertext: hex::encode(ciphertext.as_slice()),
+            mac: String::new(),
+        };
+        keystore.mac = hex::encode(keystore.mac(&mac_key).finalize().into_bytes());
+
+        Ok(keystore)
+    }
+
+    /// Decrypt the key; fails on a wrong passphrase or a tampered file
+    pub fn decrypt(&self, passphrase: &[u8]) -> Result<WitnessKey> {
+        if self.version != KEYSTORE_VERSION {
+            anyhow::bail!("Unsupported keystore version {}", self.version);
+        }
+
+        let (enc_key, mac_key) = derive_keys(passphrase, &self.kdf)?;
+
+        let mac = hex::decode(&self.mac).context("Invalid keystore MAC encoding")?;
+        self.mac(&mac_key)
+            .verify_slice(&mac)
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted keystore"))?;
+
+        let iv: [u8; 16] = hex::decode(&self.iv)
+            .ok()
+            .and_then(|iv| iv.try_into().ok())
+            .context("Invalid keystore IV")?;
+        let mut plaintext =
+            Zeroizing::new(hex::decode(&self.ciphertext).context("Invalid keystore ciphertext")?);
+        Aes256Ctr::new(enc_key.as_slice().into(), &iv.into()).apply_keystream(&mut plaintext);
+
+        let key = WitnessKey::from_bytes(self.scheme, &plaintext)?;
+        if key.public_key() != self.public_key {
+            anyhow::bail!("Keystore public key does not match the decrypted key");
+        }
+
+        Ok(key)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keystore: {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse keystore: {:?}", path))
+    }
+
+    /// Write the keystore, readable by the owner only
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to create keystore: {:?}", path))?;
+        std::io::Write::write_all(&mut file, serde_json::to_string_pretty(self)?.as_bytes())
+            .with_context(|| format!("Failed to write keystore: {:?}", path))
+    }
+
+    fn mac(&self, mac_key: &[u8; 32]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(mac_key).expect("HMAC accepts any key length");
+        mac.update(KEYSTORE_MAC_DOMAIN_TAG);
+        mac.update(&self.version.to_be_bytes());
+        for field in [
+            self.scheme.to_string().as_str(),
+            &self.public_key,
+            &self.kdf.salt,
+            &self.iv,
+            &self.ciphertext,
+        ] {
+            mac.update(&(field.len() as u32).to_be_bytes());
+            mac.update(field.as_bytes());
+        }
+        mac.update(&[self.kdf.log_n]);
+        mac.update(&self.kdf.r.to_be_bytes());
+        mac.update(&self.kdf.p.to_be_bytes());
+        mac
+    }
+}
+
+/// scrypt the passphrase into an encryption key and a MAC key
+fn derive_keys(passphrase: &[u8], kdf: &KdfParams) -> Result<(DerivedKey, DerivedKey)> {
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p)
+        .map_err(|e| anyhow::anyhow!("Invalid scrypt parameters: {}", e))?;
+    let salt = hex::decode(&kdf.salt).context("Invalid keystore salt")?;
+
+    let mut derived = Zeroizing::new([0u8; 64]);
+    scrypt::scrypt(passphrase, &salt, &params, derived.as_mut_slice())
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+
+    let mut enc_key = Zeroizing::new([0u8; 32]);
+    let mut mac_key = Zeroizing::new([0u8; 32]);
+    enc_key.copy_from_slice(&derived[..32]);
+    mac_key.copy_from_slice(&derived[32..]);
+    Ok((enc_key, mac_key))
+}
+
+/// Where to read the keystore passphrase from
+#[derive(Debug, Clone, Default)]
+pub enum PassphraseSource {
+    /// A file descriptor inherited from the parent process (read to the end, once)
+    Fd(FdPassphrase),
+    /// A passphrase or keyfile on disk
+    File(PathBuf),
+    /// `WITNESS_KEY_PASSPHRASE`, already taken out of the environment
+    Env(CapturedPassphrase),
+    /// `WITNESS_KEY_PASSPHRASE`, otherwise an interactive prompt
+    #[default]
+    EnvOrPrompt,
+}
+
+/// Passphrase read from the environment, never printed
+#[derive(Clone)]
+pub struct CapturedPassphrase(Arc<Zeroizing<Vec<u8>>>);
+
+impl std::fmt::Debug for CapturedPassphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CapturedPassphrase(..)")
+    }
+}
+
+/// Passphrase file descriptor; the descriptor is consumed by the first read,
+/// so its contents are kept for later ones
+#[derive(Clone)]
+pub struct FdPassphrase {
+    fd: i32,
+    contents: Arc<Mutex<Option<Zeroizing<Vec<u8>>>>>,
+}
+
+impl FdPassphrase {
+    fn new(fd: i32) -> Self {
+        Self {
+            fd,
+            contents: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn read(&self, passphrase: &mut Vec<u8>) -> Result<()> {
+        let mut contents = self.contents.lock().unwrap();
+        if contents.is_none() {
+            let mut read = Zeroizing::new(Vec::new());
+            read_fd(self.fd, &mut read)?;
+            *contents = Some(read);
+        }
+        passphrase.extend_from_slice(contents.as_ref().unwrap());
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for FdPassphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FdPassphrase({})", self.fd)
+    }
+}
+
+impl PassphraseSource {
+    /// Pick the passphrase source for the command-line options
+    ///
+    /// Without either option, `WITNESS_KEY_PASSPHRASE` is moved out of the
+    /// environment so nothing spawned later inherits it. Changing the
+    /// environment is only sound while the process is single-threaded, so
+    /// call this before starting the async runtime.
+    pub fn new(fd: Option<i32>, file: Option<PathBuf>) -> Self {
+        match (fd, file) {
+            (Some(fd), _) => Self::Fd(FdPassphrase::new(fd)),
+            (None, Some(file)) => Self::File(file),
+            (None, None) => match std::env::var_os(PASSPHRASE_ENV) {
+                Some(value) => {
+                    std::env::remove_var(PASSPHRASE_ENV);
+                    let passphrase = Zeroizing::new(value.into_encoded_bytes());
+                    Self::Env(CapturedPassphrase(Arc::new(passphrase)))
+                }
+                None => Self::EnvOrPrompt,
+            },
+        }
+    }
+
+    /// Read the passphrase; `prompt` is shown if the terminal is asked
+    pub fn read(&self, prompt: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let mut passphrase = Zeroizing::new(Vec::new());
+
+        match self {
+            Self::Fd(fd) => fd.read(&mut passphrase)?,
+            Self::File(path) => {
+                let mut file = std::fs::File::open(path)
+                    .with_context(|| format!("Failed to open passphrase file: {:?}", path))?;
+                file.read_to_end(&mut passphrase)
+                    .with_context(|| format!("Failed to read passphrase file: {:?}", path))?;
+            }
+            Self::Env(captured) => passphrase.extend_from_slice(&captured.0),
+            Self::EnvOrPrompt => match std::env::var_os(PASSPHRASE_ENV) {
+                Some(value) => passphrase.extend_from_slice(value.as_encoded_bytes()),
+                None => prompt_passphrase(prompt, &mut passphrase)?,
+            },
+        }
+
+        // A trailing newline from `echo` or an editor is not part of the passphrase
+        if passphrase.ends_with(b"\n") {
+            passphrase.pop();
+            if passphrase.ends_with(b"\r") {
+                passphrase.pop();
+            }
+        }
+
+        if passphrase.is_empty() {
+            anyhow::bail!("Empty keystore passphrase");
+        }
+        Ok(passphrase)
+    }
+
+    /// Read the passphrase for a new keystore, asking twice at a prompt
+    pub fn read_new(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let prompting =
+            matches!(self, Self::EnvOrPrompt) && std::env::var_os(PASSPHRASE_ENV).is_none();
+
+        let passphrase = self.read("New keystore passphrase: ")?;
+        if prompting && self.read("Repeat passphrase: ")? != passphrase {
+            anyhow::bail!("Passphrases do not match");
+        }
+        Ok(passphrase)
+    }
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32, passphrase: &mut Vec<u8>) -> Result<()> {
+    use std::os::unix::io::FromRawFd;
+
+    // Safety: the descriptor was handed to us to read from; it is closed after use
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+    file.read_to_end(passphrase)
+        .with_context(|| format!("Failed to read passphrase from file descriptor {}", fd))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32, _passphrase: &mut Vec<u8>) -> Result<()> {
+    anyhow::bail!("Reading the passphrase from a file descriptor is only supported on Unix")
+}
+
+/// Read a line from the terminal with echo turned off
+#[cfg(unix)]
+fn prompt_passphrase(prompt: &str, passphrase: &mut Vec<u8>) -> Result<()> {
+    use std::io::{BufRead, Write};
+    use std::os::unix::io::AsRawFd;
+
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .with_context(|| {
+            format!(
+                "No terminal to prompt for the passphrase; set {} or use --passphrase-fd/--passphrase-file",
+                PASSPHRASE_ENV
+            )
+        })?;
+    let fd = tty.as_raw_fd();
+
+    // Safety: plain termios calls on a descriptor we own
+    let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        anyhow::bail!("Failed to read terminal settings");
+    }
+    let mut silent = original;
+    silent.c_lflag &= !libc::ECHO;
+    silent.c_lflag |= libc::ECHONL;
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &silent) };
+
+    let mut writer = &tty;
+    let result = write!(writer, "{}", prompt)
+        .and_then(|_| writer.flush())
+        .and_then(|_| std::io::BufReader::new(&tty).read_until(b'\n', passphrase));
+
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+    result.context("Failed to read passphrase")?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn prompt_passphrase(_prompt: &str, _passphrase: &mut Vec<u8>) -> Result<()> {
+    anyhow::bail!(
+        "Passphrase prompt is only supported on Unix; set {} or use --passphrase-file",
+        PASSPHRASE_ENV
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        for scheme in [SignatureScheme::Ed25519, SignatureScheme::BLS] {
+            let key = WitnessKey::generate(scheme);
+
+            // Cheap KDF cost so the test stays fast
+            let keystore = Keystore::encrypt_with_cost(&key, scheme, b"correct horse", 4).unwrap();
+            assert_eq!(keystore.public_key, key.public_key());
+            assert!(!keystore.ciphertext.contains(&hex::encode(key.to_bytes().as_slice())));
+
+            let decrypted = keystore.decrypt(b"correct horse").unwrap();
+            assert_eq!(decrypted.public_key(), key.public_key());
+
+            assert!(keystore.decrypt(b"wrong horse").is_err());
+
+            // Metadata is authenticated
+            let mut tampered = keystore.clone();
+            tampered.kdf.log_n = 5;
+            assert!(tampered.decrypt(b"correct horse").is_err());
+
+            let mut tampered = keystore.clone();
+            tampered.public_key = WitnessKey::generate(scheme).public_key();
+            assert!(tampered.decrypt(b"correct horse").is_err());
+        }
+    }
+
+    #[test]
+    fn test_passphrase_file_strips_newline() {
+        let path = std::env::temp_dir().join(format!("witness-passphrase-{}", std::process::id()));
+        std::fs::write(&path, "hunter2\n").unwrap();
+
+        let passphrase = PassphraseSource::File(path.clone()).read("").unwrap();
+        assert_eq!(passphrase.as_slice(), b"hunter2");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_passphrase_fd_read_once() {
+        use std::os::unix::io::IntoRawFd;
+
+        let path = std::env::temp_dir().join(format!("witness-passphrase-fd-{}", std::process::id()));
+        std::fs::write(&path, "hunter2\n").unwrap();
+        let fd = std::fs::File::open(&path).unwrap().into_raw_fd();
+
+        // Every profile reads the source; the descriptor is only read (and closed) once
+        let source = PassphraseSource::new(Some(fd), None);
+        assert_eq!(source.read("").unwrap().as_slice(), b"hunter2");
+        assert_eq!(source.clone().read("").unwrap().as_slice(), b"hunter2");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
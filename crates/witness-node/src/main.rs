@@ -1,37 +1,117 @@
 mod server;
+mod auth;
+mod clock;
 mod config;
+mod dkg;
+mod keystore;
+#[cfg(unix)]
+mod pkcs11;
+mod policy;
+mod remote_signer;
+mod signer;
+mod signer_daemon;
+mod signing_log;
 
-use anyhow::Result;
-use clap::Parser;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
 use tracing_subscriber;
+use witness_core::{SignatureScheme, SignedManifest};
 
 use config::WitnessNodeConfig;
+use keystore::{Keystore, PassphraseSource, WitnessKey};
 use server::WitnessServer;
+use signer_daemon::{SignerDaemon, SignerDaemonConfig};
 
 #[derive(Parser, Debug)]
 #[command(name = "witness-node")]
 #[command(about = "Witness node for signing attestations", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to witness configuration file
-    #[arg(short, long, default_value = "witness.json")]
+    #[arg(short, long, default_value = "witness.json", global = true)]
     config: PathBuf,
 
+    /// Read the keystore passphrase from this file descriptor
+    #[arg(long, global = true)]
+    passphrase_fd: Option<i32>,
+
+    /// Read the keystore passphrase from this file (or use it as a keyfile)
+    #[arg(long, global = true, conflicts_with = "passphrase_fd")]
+    passphrase_file: Option<PathBuf>,
+
     /// HTTP port to listen on
     #[arg(short, long)]
     port: Option<u16>,
 
-    /// Generate a new keypair and exit
+    /// Generate a new keypair, print it (including the private key) and exit.
+    /// Prefer `keygen`, which writes an encrypted keystore instead.
     #[arg(long)]
     generate_key: bool,
 
     /// Use BLS signatures instead of Ed25519 (for --generate-key)
     #[arg(long)]
     bls: bool,
+
+    /// Generate threshold BLS key shares with this threshold (for --generate-key)
+    #[arg(long, requires = "shares")]
+    threshold: Option<usize>,
+
+    /// Number of threshold BLS key shares to generate (for --generate-key)
+    #[arg(long, requires = "threshold")]
+    shares: Option<usize>,
+
+    /// Co-sign a network manifest file in place with this witness's key and exit
+    #[arg(long)]
+    sign_manifest: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate (or import) a private key into an encrypted keystore
+    Keygen {
+        /// Keystore file to create
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Key scheme: ed25519, bls or threshold_bls
+        #[arg(long, default_value = "ed25519", value_parser = parse_scheme)]
+        scheme: SignatureScheme,
+
+        /// Encrypt an existing hex private key read from stdin instead of generating one
+        #[arg(long)]
+        import: bool,
+    },
+
+    /// Check that the configured keystores (or one given keystore) unlock
+    Unlock {
+        /// Keystore file to check instead of the witness configuration
+        #[arg(short, long)]
+        keystore: Option<PathBuf>,
+    },
+
+    /// Run a signer daemon holding a witness key for a remote witness node
+    Signer,
+}
+
+fn parse_scheme(value: &str) -> Result<SignatureScheme, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("unknown signature scheme '{}'", value))
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    // Takes the passphrase out of the environment, so this must run before
+    // the runtime starts any threads
+    let passphrase = PassphraseSource::new(args.passphrase_fd, args.passphrase_file.clone());
+
+    tokio::runtime::Runtime::new()?.block_on(run(args, passphrase))
+}
+
+async fn run(args: Args, passphrase: PassphraseSource) -> Result<()> {
     // Initialize tracing
     tracing_subscriber::fmt()
         .with_env_filter(
@@ -40,20 +120,60 @@ async fn main() -> Result<()> {
         )
         .init();
 
-    let args = Args::parse();
+    match args.command {
+        Some(Command::Keygen { out, scheme, import }) => {
+            return keygen(&out, scheme, import, &passphrase)
+        }
+        Some(Command::Unlock { keystore }) => {
+            return unlock(&args.config, keystore.as_deref(), &passphrase)
+        }
+        Some(Command::Signer) => {
+            let config = SignerDaemonConfig::load(&args.config, &passphrase)?;
+            drop(passphrase);
+            tracing::info!("Public key: {}", config.keys.signer().public_key_at(config::now()));
+            return SignerDaemon::new(config)?.run().await;
+        }
+        None => {}
+    }
 
     // Handle key generation
     if args.generate_key {
-        if args.bls {
+        eprintln!("Warning: --generate-key prints the private key; prefer `witness-node keygen`");
+        if let (Some(threshold), Some(shares)) = (args.threshold, args.shares) {
+            let keys = witness_core::generate_threshold_keys(threshold, shares)?;
+
+            println!("Generated {}-of-{} threshold BLS key shares:", threshold, shares);
+            println!(
+                "Group public key: {}",
+                witness_core::encode_bls_public_key(&keys.group_public_key)
+            );
+            for share in &keys.shares {
+                println!();
+                println!("Share {}:", share.index);
+                println!("  Public key:  {}", witness_core::encode_bls_public_key(&share.public_key()));
+                println!("  Private key: {}", witness_core::encode_bls_secret_key(&share.secret_key));
+            }
+            println!("\nGive each witness one private key share and destroy this output.");
+            println!("This dealer saw every share; use a DKG ceremony to avoid that.");
+            println!("\nIn each witness config, set:");
+            println!("  \"signature_scheme\": \"threshold_bls\"");
+            println!("\nIn the network config, set \"signature_scheme\": \"threshold_bls\",");
+            println!("\"group_public_key\", and each witness's \"pubkey\" and \"share_index\".");
+        } else if args.bls {
             let (secret_key, public_key) = witness_core::generate_bls_keypair();
 
             println!("Generated new BLS keypair:");
             println!("Public key:  {}", witness_core::encode_bls_public_key(&public_key));
             println!("Private key: {}", witness_core::encode_bls_secret_key(&secret_key));
+            println!(
+                "Proof of possession: {}",
+                hex::encode(witness_core::generate_bls_pop(&secret_key))
+            );
             println!("\nStore the private key securely in your witness configuration.");
-            println!("Share the public key with the network coordinator.");
+            println!("Share the public key and proof of possession with the network coordinator.");
             println!("\nIn your witness config, set:");
             println!("  \"signature_scheme\": \"bls\"");
+            println!("\nIn the network config, set this witness's \"pop\" to the proof of possession.");
         } else {
             let (signing_key, verifying_key) = witness_core::generate_keypair();
 
@@ -69,16 +189,139 @@ async fn main() -> Result<()> {
     }
 
     // Load configuration
-    let config = WitnessNodeConfig::load(&args.config)?;
+    let config = WitnessNodeConfig::load(&args.config, &passphrase)?;
+    drop(passphrase);
+
+    if let Some(path) = args.sign_manifest {
+        return sign_manifest(&config, &path);
+    }
     let port = args.port.unwrap_or(config.port);
 
     tracing::info!("Starting witness node: {}", config.id);
-    tracing::info!("Public key: {}", config.public_key());
+    for profile in config.profiles() {
+        tracing::info!("Network {}: signing with {}", profile.network_id, profile.signer().describe());
+        tracing::info!("Network {}: public key {}", profile.network_id, profile.public_key());
+        if profile.keys.is_plaintext() {
+            tracing::warn!(
+                "Network {}: private key is stored in plaintext; move it to a keystore with `witness-node keygen --import`",
+                profile.network_id
+            );
+        }
+        match &profile.signing_log {
+            Some(path) => tracing::info!("Network {}: signing log {:?}", profile.network_id, path),
+            None => tracing::warn!(
+                "Network {}: no signing_log configured; equivocation protection does not survive restarts",
+                profile.network_id
+            ),
+        }
+    }
     tracing::info!("Listening on port: {}", port);
 
+    if config.authorized_gateways.is_empty() {
+        tracing::warn!("No authorized_gateways configured; any host can request signatures");
+    } else {
+        tracing::info!("Authorized gateways: {}", config.authorized_gateways.len());
+    }
+
     // Start server
-    let server = WitnessServer::new(config);
+    let server = WitnessServer::new(config)?;
     server.run(port).await?;
 
     Ok(())
 }
+
+/// Create an encrypted keystore; the private key is never printed
+fn keygen(out: &Path, scheme: SignatureScheme, import: bool, passphrase: &PassphraseSource) -> Result<()> {
+    if out.exists() {
+        anyhow::bail!("Refusing to overwrite existing file: {:?}", out);
+    }
+
+    let key = if import {
+        let mut line = zeroize::Zeroizing::new(String::new());
+        eprint!("Private key (hex): ");
+        std::io::stdin()
+            .read_line(&mut line)
+            .context("Failed to read private key from stdin")?;
+        WitnessKey::from_hex(scheme, line.trim())?
+    } else {
+        WitnessKey::generate(scheme)
+    };
+
+    let passphrase = passphrase.read_new()?;
+    Keystore::encrypt(&key, scheme, &passphrase)?.save(out)?;
+
+    println!("Wrote {} keystore: {:?}", scheme, out);
+    println!("Public key:  {}", key.public_key());
+    if let (WitnessKey::Bls(secret_key), SignatureScheme::BLS) = (&key, scheme) {
+        println!(
+            "Proof of possession: {}",
+            hex::encode(witness_core::generate_bls_pop(secret_key))
+        );
+    }
+    println!("\nIn your witness config, remove \"private_key\" and set:");
+    println!("  \"signature_scheme\": \"{}\",", scheme);
+    println!("  \"keystore\": {:?}", out);
+    println!("\nShare the public key with the network coordinator.");
+    Ok(())
+}
+
+/// Decrypt keystores without starting the node, to check the passphrase
+fn unlock(config: &Path, keystore: Option<&Path>, passphrase: &PassphraseSource) -> Result<()> {
+    let Some(path) = keystore else {
+        let config = WitnessNodeConfig::load(config, passphrase)?;
+        println!("✓ Unlocked keys for witness {}", config.id);
+        for profile in config.profiles() {
+            println!("Network:    {}", profile.network_id);
+            println!("Public key: {}", profile.public_key());
+            if let Some((public_key, activate_at)) = profile.pending_key() {
+                println!("Next key:   {} (from {})", public_key, activate_at);
+            }
+        }
+        return Ok(());
+    };
+
+    let keystore = Keystore::load(path)?;
+    let key = keystore.decrypt(&passphrase.read(&format!("Passphrase for {:?}: ", path))?)?;
+    println!("✓ Unlocked {} keystore {:?}", keystore.scheme, path);
+    println!("Public key: {}", key.public_key());
+    Ok(())
+}
+
+/// Add this witness's signature to a manifest after showing what is being signed
+fn sign_manifest(config: &WitnessNodeConfig, path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read manifest: {:?}", path))?;
+    let mut signed: SignedManifest =
+        serde_json::from_str(&content).context("Failed to parse manifest JSON")?;
+    let manifest = &signed.manifest;
+
+    let Some(profile) = config.profile(&manifest.network.id) else {
+        anyhow::bail!(
+            "Manifest is for network '{}', which this witness does not belong to",
+            manifest.network.id
+        );
+    };
+
+    println!("Network:   {}", manifest.network.id);
+    println!("Version:   {}", manifest.version);
+    println!("Previous:  {}", manifest.previous_hash.as_deref().unwrap_or("(none)"));
+    println!(
+        "Threshold: {} of {} ({})",
+        manifest.network.threshold,
+        manifest.network.witnesses.len(),
+        manifest.network.signature_scheme
+    );
+    for witness in &manifest.network.witnesses {
+        println!("  {} {}", witness.id, witness.pubkey);
+    }
+
+    let signature = profile.signer().sign_manifest(manifest)?;
+
+    signed.add_signature(config.id.clone(), signature);
+    std::fs::write(path, serde_json::to_string_pretty(&signed)?)
+        .with_context(|| format!("Failed to write manifest: {:?}", path))?;
+
+    println!();
+    println!("Signed as {} ({} signatures so far)", config.id, signed.signatures.len());
+    Ok(())
+}
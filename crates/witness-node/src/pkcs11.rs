@@ -0,0 +1,564 @@
+//! PKCS#11 signing backend
+//!
+//! Signs with an Ed25519 key (`CKK_EC_EDWARDS`, mechanism `CKM_EDDSA`) that
+//! never leaves a PKCS#11 token such as an HSM or SoftHSM. The vendor module
+//! is loaded at runtime, so no PKCS#11 library is needed to build the node.
+//! All calls go through one logged-in session, one at a time.
+
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use std::ffi::{c_void, CString};
+use std::os::raw::{c_uchar, c_ulong};
+use std::sync::Mutex;
+
+use crate::signer::{Pkcs11Config, Signer};
+
+type CkRv = c_ulong;
+
+const CKR_OK: CkRv = 0x0;
+const CKR_USER_ALREADY_LOGGED_IN: CkRv = 0x100;
+const CKR_CRYPTOKI_ALREADY_INITIALIZED: CkRv = 0x191;
+
+const CKF_SERIAL_SESSION: c_ulong = 0x4;
+const CKU_USER: c_ulong = 1;
+
+const CKA_CLASS: c_ulong = 0x0;
+const CKA_LABEL: c_ulong = 0x3;
+const CKA_KEY_TYPE: c_ulong = 0x100;
+const CKA_EC_POINT: c_ulong = 0x181;
+
+const CKO_PUBLIC_KEY: c_ulong = 2;
+const CKO_PRIVATE_KEY: c_ulong = 3;
+const CKK_EC_EDWARDS: c_ulong = 0x40;
+const CKM_EDDSA: c_ulong = 0x1057;
+
+#[repr(C)]
+struct CkVersion {
+    major: u8,
+    minor: u8,
+}
+
+#[repr(C)]
+struct CkAttribute {
+    kind: c_ulong,
+    value: *mut c_void,
+    value_len: c_ulong,
+}
+
+#[repr(C)]
+struct CkMechanism {
+    mechanism: c_ulong,
+    parameter: *mut c_void,
+    parameter_len: c_ulong,
+}
+
+#[repr(C)]
+struct CkTokenInfo {
+    label: [u8; 32],
+    manufacturer_id: [u8; 32],
+    model: [u8; 16],
+    serial_number: [u8; 16],
+    flags: c_ulong,
+    counters: [c_ulong; 10],
+    hardware_version: CkVersion,
+    firmware_version: CkVersion,
+    utc_time: [u8; 16],
+}
+
+/// Entry of the function list this backend does not call
+type Unused = *const c_void;
+
+/// Leading part of `CK_FUNCTION_LIST`, up to `C_Sign`
+#[repr(C)]
+struct CkFunctionList {
+    version: CkVersion,
+    initialize: Option<unsafe extern "C" fn(*mut c_void) -> CkRv>,
+    finalize: Option<unsafe extern "C" fn(*mut c_void) -> CkRv>,
+    get_info: Unused,
+    get_function_list: Unused,
+    get_slot_list: Option<unsafe extern "C" fn(c_uchar, *mut c_ulong, *mut c_ulong) -> CkRv>,
+    get_slot_info: Unused,
+    get_token_info: Option<unsafe extern "C" fn(c_ulong, *mut CkTokenInfo) -> CkRv>,
+    get_mechanism_list: Unused,
+    get_mechanism_info: Unused,
+    init_token: Unused,
+    init_pin: Unused,
+    set_pin: Unused,
+    open_session:
+        Option<unsafe extern "C" fn(c_ulong, c_ulong, *mut c_void, *mut c_void, *mut c_ulong) -> CkRv>,
+    close_session: Option<unsafe extern "C" fn(c_ulong) -> CkRv>,
+    close_all_sessions: Unused,
+    get_session_info: Unused,
+    get_operation_state: Unused,
+    set_operation_state: Unused,
+    login: Option<unsafe extern "C" fn(c_ulong, c_ulong, *const c_uchar, c_ulong) -> CkRv>,
+    logout: Unused,
+    create_object: Unused,
+    copy_object: Unused,
+    destroy_object: Unused,
+    get_object_size: Unused,
+    get_attribute_value: Option<unsafe extern "C" fn(c_ulong, c_ulong, *mut CkAttribute, c_ulong) -> CkRv>,
+    set_attribute_value: Unused,
+    find_objects_init: Option<unsafe extern "C" fn(c_ulong, *mut CkAttribute, c_ulong) -> CkRv>,
+    find_objects: Option<unsafe extern "C" fn(c_ulong, *mut c_ulong, c_ulong, *mut c_ulong) -> CkRv>,
+    find_objects_final: Option<unsafe extern "C" fn(c_ulong) -> CkRv>,
+    encrypt_init: Unused,
+    encrypt: Unused,
+    encrypt_update: Unused,
+    encrypt_final: Unused,
+    decrypt_init: Unused,
+    decrypt: Unused,
+    decrypt_update: Unused,
+    decrypt_final: Unused,
+    digest_init: Unused,
+    digest: Unused,
+    digest_update: Unused,
+    digest_key: Unused,
+    digest_final: Unused,
+    sign_init: Option<unsafe extern "C" fn(c_ulong, *mut CkMechanism, c_ulong) -> CkRv>,
+    sign: Option<unsafe extern "C" fn(c_ulong, *const c_uchar, c_ulong, *mut c_uchar, *mut c_ulong) -> CkRv>,
+}
+
+type GetFunctionList = unsafe extern "C" fn(*mut *const CkFunctionList) -> CkRv;
+
+fn check(rv: CkRv, call: &str) -> Result<()> {
+    if rv == CKR_OK {
+        Ok(())
+    } else {
+        anyhow::bail!("{} failed with CKR 0x{:x}", call, rv)
+    }
+}
+
+/// Look up a function the module must provide
+fn required<F>(function: Option<F>, name: &str) -> Result<F> {
+    function.with_context(|| format!("PKCS#11 module does not implement {}", name))
+}
+
+/// A loaded PKCS#11 module
+struct Module {
+    /// `dlopen` handle; null for a function list not loaded from a library
+    handle: *mut c_void,
+    functions: &'static CkFunctionList,
+
+    /// Whether our `C_Initialize` call initialized the library, so ours must finalize it
+    initialized: bool,
+}
+
+impl Module {
+    fn load(path: &std::path::Path) -> Result<Self> {
+        let c_path = CString::new(path.as_os_str().as_encoded_bytes())
+            .context("PKCS#11 module path contains a NUL byte")?;
+
+        // Safety: loading a shared library runs its initializers; the module
+        // path is operator configuration, like any other binary it runs
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            anyhow::bail!("Failed to load PKCS#11 module {:?}", path);
+        }
+
+        let symbol = unsafe { libc::dlsym(handle, c"C_GetFunctionList".as_ptr()) };
+        if symbol.is_null() {
+            unsafe { libc::dlclose(handle) };
+            anyhow::bail!("{:?} is not a PKCS#11 module (no C_GetFunctionList)", path);
+        }
+
+        // Safety: C_GetFunctionList has this signature in every PKCS#11 version
+        let get_function_list: GetFunctionList = unsafe { std::mem::transmute(symbol) };
+        let mut functions: *const CkFunctionList = std::ptr::null();
+        let rv = unsafe { get_function_list(&mut functions) };
+        if rv != CKR_OK || functions.is_null() {
+            unsafe { libc::dlclose(handle) };
+            anyhow::bail!("C_GetFunctionList failed with CKR 0x{:x}", rv);
+        }
+
+        // Safety: the list is static data of the module, which stays loaded until drop
+        Ok(Self {
+            handle,
+            functions: unsafe { &*functions },
+            initialized: false,
+        })
+    }
+
+    /// Initialize the library, unless another user in this process already has
+    fn initialize(&mut self) -> Result<()> {
+        let rv = unsafe { required(self.functions.initialize, "C_Initialize")?(std::ptr::null_mut()) };
+        if rv != CKR_CRYPTOKI_ALREADY_INITIALIZED {
+            check(rv, "C_Initialize")?;
+            self.initialized = true;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Module {
+    fn drop(&mut self) {
+        // Finalizing a library someone else initialized would pull it out from under them
+        if self.initialized {
+            if let Some(finalize) = self.functions.finalize {
+                unsafe { finalize(std::ptr::null_mut()) };
+            }
+        }
+        if !self.handle.is_null() {
+            unsafe { libc::dlclose(self.handle) };
+        }
+    }
+}
+
+/// Ed25519 signer backed by a key on a PKCS#11 token
+pub struct Pkcs11Signer {
+    // Field order matters: the session closes before the module unloads
+    session: Mutex<c_ulong>,
+    private_key: c_ulong,
+    public_key: String,
+    token_label: String,
+    module: Module,
+}
+
+// Safety: the function list is immutable, and every call that uses the
+// session holds the session mutex
+unsafe impl Send for Pkcs11Signer {}
+unsafe impl Sync for Pkcs11Signer {}
+
+impl Pkcs11Signer {
+    /// Load the module, log in to the token with `pin` and find the key
+    pub fn open(config: &Pkcs11Config, pin: &[u8]) -> Result<Self> {
+        Self::open_module(Module::load(&config.module)?, config, pin)
+    }
+
+    /// Dropping `module` on any error below finalizes it if we initialized it
+    fn open_module(mut module: Module, config: &Pkcs11Config, pin: &[u8]) -> Result<Self> {
+        module.initialize()?;
+        let f = module.functions;
+
+        let slot = find_token(f, &config.token_label)?;
+
+        let mut session: c_ulong = 0;
+        check(
+            unsafe {
+                required(f.open_session, "C_OpenSession")?(
+                    slot,
+                    CKF_SERIAL_SESSION,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    &mut session,
+                )
+            },
+            "C_OpenSession",
+        )?;
+
+        let mut signer = Self {
+            session: Mutex::new(session),
+            private_key: 0,
+            public_key: String::new(),
+            token_label: config.token_label.clone(),
+            module,
+        };
+
+        let rv = unsafe { required(f.login, "C_Login")?(session, CKU_USER, pin.as_ptr(), pin.len() as c_ulong) };
+        if rv != CKR_USER_ALREADY_LOGGED_IN {
+            check(rv, "C_Login").context("Failed to log in to the PKCS#11 token (wrong PIN?)")?;
+        }
+
+        signer.private_key = signer
+            .find_key(CKO_PRIVATE_KEY, &config.key_label)
+            .context("Ed25519 private key not found on token")?;
+        let public_key = signer
+            .find_key(CKO_PUBLIC_KEY, &config.key_label)
+            .context("Ed25519 public key not found on token")?;
+        signer.public_key = hex::encode(signer.ec_point(public_key)?);
+
+        // Catch a token that signs with some other mechanism or key now, not on the first request
+        let probe = b"witness pkcs11 probe";
+        let signature = signer.sign_raw(probe)?;
+        let verifying_key = witness_core::decode_public_key(&signer.public_key)?;
+        let signature = Signature::from_slice(&signature).context("Token returned a malformed signature")?;
+        verifying_key
+            .verify(probe, &signature)
+            .context("Token signature does not verify under its public key")?;
+
+        Ok(signer)
+    }
+
+    /// The single object of `class` with `label` (Ed25519 keys only)
+    fn find_key(&self, class: c_ulong, label: &str) -> Result<c_ulong> {
+        let f = self.module.functions;
+        let session = *self.session.lock().unwrap();
+
+        let mut class = class;
+        let mut key_type = CKK_EC_EDWARDS;
+        let mut label = label.as_bytes().to_vec();
+        let mut template = [
+            ulong_attribute(CKA_CLASS, &mut class),
+            ulong_attribute(CKA_KEY_TYPE, &mut key_type),
+            CkAttribute {
+                kind: CKA_LABEL,
+                value: label.as_mut_ptr().cast(),
+                value_len: label.len() as c_ulong,
+            },
+        ];
+
+        let mut objects = [0 as c_ulong; 2];
+        let mut count: c_ulong = 0;
+        unsafe {
+            check(
+                required(f.find_objects_init, "C_FindObjectsInit")?(
+                    session,
+                    template.as_mut_ptr(),
+                    template.len() as c_ulong,
+                ),
+                "C_FindObjectsInit",
+            )?;
+            let rv = required(f.find_objects, "C_FindObjects")?(
+                session,
+                objects.as_mut_ptr(),
+                objects.len() as c_ulong,
+                &mut count,
+            );
+            required(f.find_objects_final, "C_FindObjectsFinal")?(session);
+            check(rv, "C_FindObjects")?;
+        }
+
+        match count {
+            1 => Ok(objects[0]),
+            0 => anyhow::bail!("no key labelled {:?}", String::from_utf8_lossy(&label)),
+            _ => anyhow::bail!("more than one key labelled {:?}", String::from_utf8_lossy(&label)),
+        }
+    }
+
+    /// Raw 32-byte public key of an Ed25519 public key object
+    fn ec_point(&self, object: c_ulong) -> Result<[u8; 32]> {
+        let f = self.module.functions;
+        let session = *self.session.lock().unwrap();
+        let get_attribute_value = required(f.get_attribute_value, "C_GetAttributeValue")?;
+
+        let mut attribute = CkAttribute {
+            kind: CKA_EC_POINT,
+            value: std::ptr::null_mut(),
+            value_len: 0,
+        };
+        check(
+            unsafe { get_attribute_value(session, object, &mut attribute, 1) },
+            "C_GetAttributeValue",
+        )?;
+
+        let mut point = vec![0u8; attribute.value_len as usize];
+        attribute.value = point.as_mut_ptr().cast();
+        check(
+            unsafe { get_attribute_value(session, object, &mut attribute, 1) },
+            "C_GetAttributeValue",
+        )?;
+        point.truncate(attribute.value_len as usize);
+
+        parse_ec_point(&point)
+    }
+
+    fn sign_raw(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let f = self.module.functions;
+        let session = self.session.lock().unwrap();
+
+        let mut mechanism = CkMechanism {
+            mechanism: CKM_EDDSA,
+            parameter: std::ptr::null_mut(),
+            parameter_len: 0,
+        };
+        let mut signature = vec![0u8; 64];
+        let mut signature_len = signature.len() as c_ulong;
+
+        unsafe {
+            check(
+                required(f.sign_init, "C_SignInit")?(*session, &mut mechanism, self.private_key),
+                "C_SignInit",
+            )?;
+            check(
+                required(f.sign, "C_Sign")?(
+                    *session,
+                    message.as_ptr(),
+                    message.len() as c_ulong,
+                    signature.as_mut_ptr(),
+                    &mut signature_len,
+                ),
+                "C_Sign",
+            )?;
+        }
+
+        signature.truncate(signature_len as usize);
+        Ok(signature)
+    }
+}
+
+impl Signer for Pkcs11Signer {
+    fn public_key_at(&self, _timestamp: u64) -> String {
+        self.public_key.clone()
+    }
+
+    fn sign_at(&self, message: &[u8], _timestamp: u64) -> Result<Vec<u8>> {
+        self.sign_raw(message)
+    }
+
+    fn describe(&self) -> String {
+        format!("PKCS#11 token {:?}", self.token_label)
+    }
+}
+
+impl Drop for Pkcs11Signer {
+    fn drop(&mut self) {
+        // The module finalizes (if we initialized it) and unloads after this
+        let f = self.module.functions;
+        let session = *self.session.get_mut().unwrap();
+        if let Some(close_session) = f.close_session {
+            unsafe { close_session(session) };
+        }
+    }
+}
+
+fn ulong_attribute(kind: c_ulong, value: &mut c_ulong) -> CkAttribute {
+    CkAttribute {
+        kind,
+        value: (value as *mut c_ulong).cast(),
+        value_len: std::mem::size_of::<c_ulong>() as c_ulong,
+    }
+}
+
+/// Slot of the token labelled `label`
+fn find_token(f: &CkFunctionList, label: &str) -> Result<c_ulong> {
+    let get_slot_list = required(f.get_slot_list, "C_GetSlotList")?;
+    let get_token_info = required(f.get_token_info, "C_GetTokenInfo")?;
+
+    let mut count: c_ulong = 0;
+    check(unsafe { get_slot_list(1, std::ptr::null_mut(), &mut count) }, "C_GetSlotList")?;
+    let mut slots = vec![0 as c_ulong; count as usize];
+    check(unsafe { get_slot_list(1, slots.as_mut_ptr(), &mut count) }, "C_GetSlotList")?;
+    slots.truncate(count as usize);
+
+    for slot in slots {
+        // Safety: CK_TOKEN_INFO is plain data
+        let mut info: CkTokenInfo = unsafe { std::mem::zeroed() };
+        if unsafe { get_token_info(slot, &mut info) } != CKR_OK {
+            continue;
+        }
+        // Labels are blank-padded to 32 bytes
+        if String::from_utf8_lossy(&info.label).trim_end() == label {
+            return Ok(slot);
+        }
+    }
+
+    anyhow::bail!("No PKCS#11 token labelled {:?}", label)
+}
+
+/// `CKA_EC_POINT` of an Edwards key: a DER OCTET STRING, or the raw key on some tokens
+fn parse_ec_point(point: &[u8]) -> Result<[u8; 32]> {
+    let raw = match point {
+        [0x04, 0x20, rest @ ..] if rest.len() == 32 => rest,
+        _ => point,
+    };
+    let key: [u8; 32] = raw
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Unexpected Ed25519 public key encoding on token"))?;
+
+    VerifyingKey::from_bytes(&key).context("Invalid Ed25519 public key on token")?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ec_point() {
+        let (_, verifying_key) = witness_core::generate_keypair();
+        let raw = verifying_key.to_bytes();
+
+        let mut der = vec![0x04, 0x20];
+        der.extend_from_slice(&raw);
+        assert_eq!(parse_ec_point(&der).unwrap(), raw);
+        assert_eq!(parse_ec_point(&raw).unwrap(), raw);
+        assert!(parse_ec_point(&der[..20]).is_err());
+    }
+
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    static INITIALIZE_RV: AtomicU64 = AtomicU64::new(CKR_OK);
+    static FINALIZED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "C" fn fake_initialize(_args: *mut c_void) -> CkRv {
+        INITIALIZE_RV.load(Ordering::SeqCst) as CkRv
+    }
+
+    unsafe extern "C" fn fake_finalize(_reserved: *mut c_void) -> CkRv {
+        FINALIZED.fetch_add(1, Ordering::SeqCst);
+        CKR_OK
+    }
+
+    /// A module without tokens
+    unsafe extern "C" fn fake_get_slot_list(_present: c_uchar, _slots: *mut c_ulong, count: *mut c_ulong) -> CkRv {
+        *count = 0;
+        CKR_OK
+    }
+
+    fn fake_module() -> Module {
+        // Safety: every entry is a nullable pointer or an Option of one
+        let mut functions: CkFunctionList = unsafe { std::mem::zeroed() };
+        functions.initialize = Some(fake_initialize);
+        functions.finalize = Some(fake_finalize);
+        functions.get_slot_list = Some(fake_get_slot_list);
+
+        Module {
+            handle: std::ptr::null_mut(),
+            functions: Box::leak(Box::new(functions)),
+            initialized: false,
+        }
+    }
+
+    #[test]
+    fn test_finalize_only_what_we_initialized() {
+        let config = Pkcs11Config {
+            module: "fake".into(),
+            token_label: "missing".to_string(),
+            key_label: "witness-key".to_string(),
+        };
+
+        // We initialized the library, so a failed open finalizes it
+        INITIALIZE_RV.store(CKR_OK, Ordering::SeqCst);
+        assert!(Pkcs11Signer::open_module(fake_module(), &config, b"1234").is_err());
+        assert_eq!(FINALIZED.load(Ordering::SeqCst), 1);
+
+        // Someone else in the process did; leave it initialized for them
+        INITIALIZE_RV.store(CKR_CRYPTOKI_ALREADY_INITIALIZED, Ordering::SeqCst);
+        assert!(Pkcs11Signer::open_module(fake_module(), &config, b"1234").is_err());
+        assert_eq!(FINALIZED.load(Ordering::SeqCst), 1);
+
+        // A failed C_Initialize leaves nothing to finalize
+        INITIALIZE_RV.store(0x5, Ordering::SeqCst);
+        assert!(Pkcs11Signer::open_module(fake_module(), &config, b"1234").is_err());
+        assert_eq!(FINALIZED.load(Ordering::SeqCst), 1);
+    }
+
+    /// Signs with a SoftHSM key. Set up a token and key, then run with
+    /// `WITNESS_TEST_PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so cargo test -p witness-node -- --ignored`:
+    ///
+    /// ```text
+    /// softhsm2-util --init-token --free --label witness-test --pin 1234 --so-pin 5678
+    /// pkcs11-tool --module $WITNESS_TEST_PKCS11_MODULE --token-label witness-test --login --pin 1234 \
+    ///     --keypairgen --key-type EC:edwards25519 --label witness-key
+    /// ```
+    #[test]
+    #[ignore = "needs SoftHSM with a witness-test token"]
+    fn test_softhsm_signer() {
+        let module = std::env::var("WITNESS_TEST_PKCS11_MODULE")
+            .expect("WITNESS_TEST_PKCS11_MODULE is not set");
+        let config = Pkcs11Config {
+            module: module.into(),
+            token_label: "witness-test".to_string(),
+            key_label: "witness-key".to_string(),
+        };
+
+        assert!(Pkcs11Signer::open(&config, b"wrong").is_err());
+
+        let signer = Pkcs11Signer::open(&config, b"1234").unwrap();
+        let attestation = witness_core::Attestation::new([7; 32], "test-net".to_string(), 1);
+        let signature = signer.sign_attestation(&attestation).unwrap();
+
+        let verifying_key = witness_core::decode_public_key(&signer.public_key_at(0)).unwrap();
+        assert!(witness_core::verify_signature(&attestation, &signature, &verifying_key).is_ok());
+    }
+}
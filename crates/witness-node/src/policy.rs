@@ -0,0 +1,221 @@
+//! Signing policy
+//!
+//! The checks a witness applies before signing, shared by the HTTP API and
+//! the signer daemon so that both refuse exactly the same requests.
+
+use witness_core::{checked_timestamp_ms, format_timestamp_ms, Attestation, CrossAnchorStatement};
+
+/// Why a request was refused
+#[derive(Debug)]
+pub enum PolicyError {
+    InvalidTimestamp(String),
+    InvalidNetwork(String),
+    UnsupportedVersion(String),
+    InvalidLeafCount(String),
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::InvalidTimestamp(reason)
+            | PolicyError::InvalidNetwork(reason)
+            | PolicyError::UnsupportedVersion(reason)
+            | PolicyError::InvalidLeafCount(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Limits on what a witness will sign
+#[derive(Debug, Clone)]
+pub struct SigningPolicy {
+    /// Networks the witness signs for
+    pub networks: Vec<String>,
+
+    /// Maximum distance between a signed timestamp and the local clock (seconds)
+    pub max_clock_skew: u64,
+
+    /// Whether legacy (v0) attestation encodings may be signed
+    pub allow_legacy_encoding: bool,
+}
+
+impl SigningPolicy {
+    fn check_network(&self, network_id: &str) -> Result<(), PolicyError> {
+        if self.networks.iter().any(|network| network == network_id) {
+            return Ok(());
+        }
+        Err(PolicyError::InvalidNetwork(format!(
+            "network {} is not one of {}",
+            network_id,
+            self.networks.join(", ")
+        )))
+    }
+
+    fn check_time_ms(&self, timestamp_ms: u64, now_ms: u64) -> Result<(), PolicyError> {
+        if timestamp_ms.abs_diff(now_ms) > self.max_clock_skew.saturating_mul(1000) {
+            return Err(PolicyError::InvalidTimestamp(format!(
+                "timestamp {} is too far from the local clock ({})",
+                format_timestamp_ms(timestamp_ms),
+                format_timestamp_ms(now_ms)
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn check_attestation(&self, attestation: &Attestation, now_ms: u64) -> Result<(), PolicyError> {
+        if !attestation.has_valid_millis() {
+            return Err(PolicyError::InvalidTimestamp(format!(
+                "invalid millisecond part {:?}",
+                attestation.timestamp_millis
+            )));
+        }
+        let timestamp_ms = attestation.timestamp_ms().ok_or_else(|| out_of_range(attestation.timestamp))?;
+        self.check_time_ms(timestamp_ms, now_ms)?;
+
+        if !attestation.is_supported_version() {
+            return Err(PolicyError::UnsupportedVersion(format!(
+                "unsupported attestation encoding version {}",
+                attestation.version
+            )));
+        }
+
+        if attestation.is_legacy() && !self.allow_legacy_encoding {
+            return Err(PolicyError::UnsupportedVersion(
+                "legacy (v0) attestation encoding is not allowed".to_string(),
+            ));
+        }
+
+        // A merkle root covers at least one hash
+        if attestation.leaf_count == Some(0) {
+            return Err(PolicyError::InvalidLeafCount(
+                "merkle root attestation covers no leaves".to_string(),
+            ));
+        }
+
+        self.check_network(&attestation.network_id)
+    }
+
+    /// A witness clock reading must match the local clock
+    pub fn check_time_reading(&self, attestation: &Attestation, time_ms: u64, now_ms: u64) -> Result<(), PolicyError> {
+        self.check_network(&attestation.network_id)?;
+        self.check_time_ms(time_ms, now_ms)
+    }
+
+    pub fn check_cross_anchor(&self, statement: &CrossAnchorStatement, now_ms: u64) -> Result<(), PolicyError> {
+        let timestamp_ms = checked_timestamp_ms(statement.timestamp, None)
+            .ok_or_else(|| out_of_range(statement.timestamp))?;
+        self.check_time_ms(timestamp_ms, now_ms)?;
+
+        if statement.version != witness_core::CROSS_ANCHOR_VERSION {
+            return Err(PolicyError::UnsupportedVersion(format!(
+                "unsupported cross-anchor version {}",
+                statement.version
+            )));
+        }
+
+        // A witness only cross-anchors on behalf of its own network
+        self.check_network(&statement.witnessing_network)
+    }
+}
+
+fn out_of_range(timestamp: u64) -> PolicyError {
+    PolicyError::InvalidTimestamp(format!("timestamp {} is out of range", timestamp))
+}
+
+pub(crate) fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_policy() {
+        let policy = SigningPolicy {
+            networks: vec!["test-net".to_string()],
+            max_clock_skew: 60,
+            allow_legacy_encoding: false,
+        };
+        let now_ms = 1_700_000_000_000;
+
+        let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        attestation.timestamp = 1_700_000_030;
+        attestation.timestamp_millis = Some(500);
+        assert!(policy.check_attestation(&attestation, now_ms).is_ok());
+        assert!(policy.check_time_reading(&attestation, now_ms + 59_000, now_ms).is_ok());
+        assert!(policy.check_time_reading(&attestation, now_ms + 61_000, now_ms).is_err());
+
+        attestation.timestamp = 1_700_000_061;
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::InvalidTimestamp(_))
+        ));
+
+        attestation.timestamp = 1_700_000_000;
+        attestation.network_id = "other-net".to_string();
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::InvalidNetwork(_))
+        ));
+
+        attestation.network_id = "test-net".to_string();
+        attestation.version = 0;
+        attestation.timestamp_millis = None;
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::UnsupportedVersion(_))
+        ));
+
+        attestation.version = witness_core::ATTESTATION_VERSION;
+        attestation.timestamp_millis = Some(0);
+        attestation.leaf_count = Some(1000);
+        assert!(policy.check_attestation(&attestation, now_ms).is_ok());
+        attestation.leaf_count = Some(0);
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::InvalidLeafCount(_))
+        ));
+    }
+
+    #[test]
+    fn test_timestamp_overflow() {
+        let policy = SigningPolicy {
+            networks: vec!["test-net".to_string()],
+            max_clock_skew: 60,
+            allow_legacy_encoding: false,
+        };
+        let now_ms = 1_700_000_000_000;
+
+        // Neither wraps around to a time near the local clock
+        let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        attestation.timestamp = u64::MAX;
+        attestation.timestamp_millis = Some(999);
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::InvalidTimestamp(_))
+        ));
+        attestation.timestamp = u64::MAX / 1000;
+        assert!(matches!(
+            policy.check_attestation(&attestation, now_ms),
+            Err(PolicyError::InvalidTimestamp(_))
+        ));
+
+        let batch = witness_core::AttestationBatch {
+            id: 1,
+            network_id: "peer-net".to_string(),
+            merkle_root: [2; 32],
+            period_start: 0,
+            period_end: 60,
+            attestation_count: 1,
+        };
+        let mut statement = CrossAnchorStatement::new(batch, "test-net".to_string());
+        statement.timestamp = u64::MAX;
+        assert!(matches!(
+            policy.check_cross_anchor(&statement, now_ms),
+            Err(PolicyError::InvalidTimestamp(_))
+        ));
+    }
+}
@@ -0,0 +1,388 @@
+//! Remote signer protocol
+//!
+//! A witness node can leave its key with a signer daemon (`witness-node
+//! signer`) on another host or user account, and do only validation and the
+//! HTTP API itself. The node sends typed sign requests (never raw bytes) over
+//! a Unix socket or TCP connection, one JSON object per line, so the daemon
+//! can apply its own signing policy and signing log to everything it signs.
+//!
+//! Over TCP each request is authenticated with the node's Ed25519 client key,
+//! the same way gateways authenticate to witnesses.
+
+use anyhow::{Context, Result};
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use witness_core::{
+    Attestation, CrossAnchorStatement, DkgComplaint, DkgDeal, NetworkManifest, RequestAuth,
+    SignatureScheme,
+};
+
+use crate::signer::Signer;
+
+/// Path bound into request authentication, so a signer request can never be
+/// replayed as a gateway sign request
+pub const SIGNER_REQUEST_PATH: &str = "witness-signer";
+
+/// Where a signer daemon listens: `unix:<path>` or `tcp:<host>:<port>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SignerAddress {
+    Unix(PathBuf),
+    Tcp(String),
+}
+
+impl TryFrom<String> for SignerAddress {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, String> {
+        if let Some(path) = value.strip_prefix("unix:") {
+            Ok(SignerAddress::Unix(PathBuf::from(path)))
+        } else if let Some(address) = value.strip_prefix("tcp:") {
+            Ok(SignerAddress::Tcp(address.to_string()))
+        } else {
+            Err(format!("signer address must start with unix: or tcp:, got '{}'", value))
+        }
+    }
+}
+
+impl From<SignerAddress> for String {
+    fn from(address: SignerAddress) -> Self {
+        address.to_string()
+    }
+}
+
+impl std::fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+            SignerAddress::Tcp(address) => write!(f, "tcp:{}", address),
+        }
+    }
+}
+
+/// Node-side settings for reaching a signer daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    /// Address of the signer daemon
+    pub address: SignerAddress,
+
+    /// File holding this node's hex-encoded Ed25519 client key, listed in the
+    /// daemon's `authorized_clients` (required for TCP)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+
+    /// How long to wait for the daemon before failing a request (milliseconds)
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// A typed request to the signer daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerRequest {
+    /// Public keys the daemon signs with
+    Keys,
+    /// An attestation, and this node's clock reading for it if `time_ms` is
+    /// set. Readings are only signed together with their attestation.
+    Attestation {
+        attestation: Attestation,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        time_ms: Option<u64>,
+    },
+    CrossAnchor { statement: CrossAnchorStatement },
+    Manifest { manifest: NetworkManifest },
+    DkgDeal { deal: DkgDeal },
+    DkgComplaint { complaint: DkgComplaint },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerResponse {
+    Keys {
+        scheme: SignatureScheme,
+        /// Key in use (before `next.activate_at`, if a rotation is scheduled)
+        current: RemoteKey,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next: Option<RemoteKey>,
+    },
+    Signature {
+        /// Hex-encoded signature
+        signature: String,
+        /// Hex-encoded signature on the clock reading, if one was requested
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        time_reading: Option<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// A public key held by the signer daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteKey {
+    pub public_key: String,
+
+    /// BLS proof of possession (bls only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pop: Option<String>,
+
+    /// Unix time from which this key signs (scheduled rotation only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activate_at: Option<u64>,
+}
+
+/// One line on the wire: the request as a JSON string, so the daemon checks
+/// the authentication over exactly the bytes the node signed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignerEnvelope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<RequestAuth>,
+    pub request: String,
+}
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+type Connection = BufReader<Box<dyn Stream>>;
+
+/// Signer backed by a signer daemon. Requests block on the connection, so
+/// the HTTP handlers call it from the blocking pool (`server::signing_task`).
+pub struct RemoteSigner {
+    address: SignerAddress,
+    timeout: Duration,
+    client_key: Option<SigningKey>,
+    scheme: SignatureScheme,
+    current: RemoteKey,
+    next: Option<RemoteKey>,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl RemoteSigner {
+    /// Connect to the daemon and fetch the keys it signs with
+    pub fn connect(config: &RemoteSignerConfig, scheme: SignatureScheme) -> Result<Self> {
+        let client_key = match &config.client_key {
+            Some(path) => Some(load_client_key(path)?),
+            None if matches!(config.address, SignerAddress::Tcp(_)) => {
+                anyhow::bail!("A client_key is required to reach a signer over TCP")
+            }
+            None => None,
+        };
+
+        let mut signer = Self {
+            address: config.address.clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+            client_key,
+            scheme,
+            current: RemoteKey {
+                public_key: String::new(),
+                pop: None,
+                activate_at: None,
+            },
+            next: None,
+            connection: Mutex::new(None),
+        };
+
+        match signer.call(&SignerRequest::Keys)? {
+            SignerResponse::Keys {
+                scheme: remote_scheme,
+                current,
+                next,
+            } => {
+                if remote_scheme != scheme {
+                    anyhow::bail!(
+                        "Signer holds a {} key, but the witness uses {}",
+                        remote_scheme,
+                        scheme
+                    );
+                }
+                signer.current = current;
+                signer.next = next;
+            }
+            _ => anyhow::bail!("Unexpected response to a keys request"),
+        }
+
+        Ok(signer)
+    }
+
+    fn key_at(&self, timestamp: u64) -> &RemoteKey {
+        match &self.next {
+            Some(next) if next.activate_at.is_some_and(|activate_at| timestamp >= activate_at) => next,
+            _ => &self.current,
+        }
+    }
+
+    fn open(&self) -> Result<Connection> {
+        let stream: Box<dyn Stream> = match &self.address {
+            SignerAddress::Tcp(address) => {
+                let stream = std::net::TcpStream::connect(address)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                stream.set_nodelay(true)?;
+                Box::new(stream)
+            }
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                Box::new(stream)
+            }
+            #[cfg(not(unix))]
+            SignerAddress::Unix(_) => anyhow::bail!("Unix sockets are only supported on Unix"),
+        };
+        Ok(BufReader::new(stream))
+    }
+
+    /// Send one request, reconnecting once if the connection has gone away
+    fn call(&self, request: &SignerRequest) -> Result<SignerResponse> {
+        let body = serde_json::to_string(request)?;
+        let mut connection = self.connection.lock().unwrap();
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            // Authenticate every attempt afresh; the daemon refuses reused nonces
+            let envelope = SignerEnvelope {
+                auth: self
+                    .client_key
+                    .as_ref()
+                    .map(|key| RequestAuth::sign(SIGNER_REQUEST_PATH, body.as_bytes(), key)),
+                request: body.clone(),
+            };
+            let mut line = serde_json::to_string(&envelope)?;
+            line.push('\n');
+
+            let result = match connection.as_mut() {
+                Some(open) => exchange(open, &line),
+                None => self.open().and_then(|open| exchange(connection.insert(open), &line)),
+            };
+
+            match result {
+                Ok(SignerResponse::Error { message }) => anyhow::bail!("Signer refused: {}", message),
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    *connection = None;
+                    if attempt == 2 {
+                        return Err(e).with_context(|| format!("Signer {} is unreachable", self.address));
+                    }
+                }
+            }
+        }
+    }
+
+    fn signature(&self, request: SignerRequest) -> Result<Vec<u8>> {
+        match self.call(&request)? {
+            SignerResponse::Signature { signature, .. } => {
+                hex::decode(signature).context("Signer returned a malformed signature")
+            }
+            _ => anyhow::bail!("Unexpected response to a sign request"),
+        }
+    }
+}
+
+fn exchange(connection: &mut Connection, line: &str) -> Result<SignerResponse> {
+    let stream = connection.get_mut();
+    stream.write_all(line.as_bytes())?;
+    stream.flush()?;
+
+    let mut response = String::new();
+    if connection.read_line(&mut response)? == 0 {
+        anyhow::bail!("Signer closed the connection");
+    }
+    serde_json::from_str(&response).context("Malformed response from signer")
+}
+
+impl Signer for RemoteSigner {
+    fn public_key_at(&self, timestamp: u64) -> String {
+        self.key_at(timestamp).public_key.clone()
+    }
+
+    fn sign_at(&self, _message: &[u8], _timestamp: u64) -> Result<Vec<u8>> {
+        anyhow::bail!("The remote signer only signs typed requests")
+    }
+
+    fn pop_at(&self, timestamp: u64) -> Option<String> {
+        self.key_at(timestamp).pop.clone()
+    }
+
+    fn rotation(&self) -> Option<u64> {
+        self.next.as_ref().and_then(|next| next.activate_at)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} key on signer {}", self.scheme, self.address)
+    }
+
+    fn sign_attestation(&self, attestation: &Attestation) -> Result<Vec<u8>> {
+        self.signature(SignerRequest::Attestation {
+            attestation: attestation.clone(),
+            time_ms: None,
+        })
+    }
+
+    fn sign_attestation_with_reading(&self, attestation: &Attestation, time_ms: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+        let request = SignerRequest::Attestation {
+            attestation: attestation.clone(),
+            time_ms: Some(time_ms),
+        };
+        match self.call(&request)? {
+            SignerResponse::Signature {
+                signature,
+                time_reading: Some(time_reading),
+            } => Ok((
+                hex::decode(signature).context("Signer returned a malformed signature")?,
+                hex::decode(time_reading).context("Signer returned a malformed time reading signature")?,
+            )),
+            _ => anyhow::bail!("Unexpected response to a sign request"),
+        }
+    }
+
+    fn sign_cross_anchor(&self, statement: &CrossAnchorStatement) -> Result<Vec<u8>> {
+        self.signature(SignerRequest::CrossAnchor {
+            statement: statement.clone(),
+        })
+    }
+
+    fn sign_manifest(&self, manifest: &NetworkManifest) -> Result<Vec<u8>> {
+        self.signature(SignerRequest::Manifest {
+            manifest: manifest.clone(),
+        })
+    }
+
+    fn sign_dkg_deal(&self, deal: &mut DkgDeal) -> Result<()> {
+        deal.signature = self.signature(SignerRequest::DkgDeal { deal: deal.clone() })?;
+        Ok(())
+    }
+
+    fn sign_dkg_complaint(&self, complaint: &mut DkgComplaint) -> Result<()> {
+        complaint.signature = self.signature(SignerRequest::DkgComplaint {
+            complaint: complaint.clone(),
+        })?;
+        Ok(())
+    }
+}
+
+/// Read a hex-encoded Ed25519 client key from a file
+fn load_client_key(path: &Path) -> Result<SigningKey> {
+    let content = zeroize::Zeroizing::new(
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read signer client key: {:?}", path))?,
+    );
+
+    let bytes: zeroize::Zeroizing<[u8; 32]> = hex::decode(content.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(zeroize::Zeroizing::new)
+        .with_context(|| format!("Signer client key must be 32 hex-encoded bytes: {:?}", path))?;
+
+    Ok(SigningKey::from_bytes(&bytes))
+}
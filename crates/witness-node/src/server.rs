@@ -1,35 +1,92 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
-use witness_core::{SignRequest, SignResponse, SignatureScheme};
+use witness_core::{
+    Attestation, CrossAnchorSignRequest, SignRequest, SignResponse, SignRootRequest,
+    SignedManifest, TimeReading,
+};
 
-use crate::config::WitnessNodeConfig;
+use crate::auth::{self, NonceCache};
+use crate::clock::ClockMonitor;
+use crate::config::{NetworkProfile, WitnessNodeConfig};
+use crate::dkg::{self, DkgSessions};
+use crate::policy::{PolicyError, SigningPolicy};
+use crate::signing_log::{LogError, SigningLog};
 
 #[derive(Clone)]
 pub struct WitnessServer {
-    config: Arc<WitnessNodeConfig>,
+    pub(crate) config: Arc<WitnessNodeConfig>,
+    pub(crate) dkg_sessions: Arc<DkgSessions>,
+    networks: Arc<Vec<HostedNetwork>>,
+    pub(crate) nonces: Arc<NonceCache>,
+    clock: Arc<ClockMonitor>,
+}
+
+/// A network this node signs for, with its own policy and signing log
+struct HostedNetwork {
+    profile: NetworkProfile,
+    policy: SigningPolicy,
+    signing_log: SigningLog,
+}
+
+impl HostedNetwork {
+    fn new(profile: &NetworkProfile) -> anyhow::Result<Self> {
+        if profile.signing_log.is_none() {
+            tracing::error!(
+                "Network {} has no durable signing log (set signing_log or data_dir); \
+                 signed sequences are forgotten on restart, so the node could sign conflicting attestations",
+                profile.network_id
+            );
+        }
+
+        Ok(Self {
+            profile: profile.clone(),
+            policy: SigningPolicy {
+                networks: vec![profile.network_id.clone()],
+                max_clock_skew: profile.max_clock_skew,
+                allow_legacy_encoding: profile.allow_legacy_encoding,
+            },
+            signing_log: SigningLog::open(profile.signing_log.as_deref())?,
+        })
+    }
 }
 
 impl WitnessServer {
-    pub fn new(config: WitnessNodeConfig) -> Self {
-        Self {
+    pub fn new(config: WitnessNodeConfig) -> anyhow::Result<Self> {
+        let networks = config
+            .profiles()
+            .map(HostedNetwork::new)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let clock = ClockMonitor::new(config.time.clone());
+
+        Ok(Self {
             config: Arc::new(config),
-        }
+            dkg_sessions: Arc::new(DkgSessions::default()),
+            networks: Arc::new(networks),
+            nonces: Arc::new(NonceCache::default()),
+            clock: Arc::new(clock),
+        })
     }
 
     pub async fn run(self, port: u16) -> anyhow::Result<()> {
-        let app = Router::new()
-            .route("/health", get(health_handler))
-            .route("/v1/sign", post(sign_handler))
-            .route("/v1/info", get(info_handler))
-            .layer(CorsLayer::permissive())
-            .with_state(self);
+        // Validate the clock before accepting sign requests
+        if self.config.time.is_some() {
+            self.clock.poll().await;
+            match self.clock.check() {
+                Ok(()) => tracing::info!("Local clock validated against time sources"),
+                Err(reason) => tracing::warn!("Local clock not trusted: {}", reason),
+            }
+            self.clock.clone().start();
+        }
+
+        let app = self.router();
 
         let addr = format!("0.0.0.0:{}", port);
         let listener = tokio::net::TcpListener::bind(&addr).await?;
@@ -39,18 +96,179 @@ impl WitnessServer {
         axum::serve(listener, app).await?;
         Ok(())
     }
+
+    pub(crate) fn router(self) -> Router {
+        // Signing is for configured gateways only, never for browsers
+        let signing = Router::new()
+            .route("/v1/sign", post(sign_handler))
+            .route("/v1/sign/root", post(sign_root_handler))
+            .route("/v1/sign/cross-anchor", post(sign_cross_anchor_handler))
+            .route_layer(middleware::from_fn_with_state(
+                self.clone(),
+                auth::require_gateway_auth,
+            ));
+
+        // Ceremonies are driven by configured coordinators only
+        let dkg = Router::new()
+            .route("/v1/dkg/start", post(dkg::start_handler))
+            .route("/v1/dkg/deals", post(dkg::deals_handler))
+            .route("/v1/dkg/receive", post(dkg::receive_handler))
+            .route("/v1/dkg/reveal", post(dkg::reveal_handler))
+            .route("/v1/dkg/resolve", post(dkg::resolve_handler))
+            .route("/v1/dkg/finalize", post(dkg::finalize_handler))
+            .route_layer(middleware::from_fn_with_state(
+                self.clone(),
+                auth::require_coordinator_auth,
+            ));
+
+        Router::new()
+            .route("/health", get(health_handler))
+            .route("/v1/info", get(info_handler))
+            .route("/v1/manifest", get(manifest_handler))
+            .route("/v1/log/head", get(log_head_handler))
+            .route("/v1/log/:sequence", get(log_entry_handler))
+            .layer(CorsLayer::permissive())
+            .merge(signing)
+            .merge(dkg)
+            .with_state(self)
+    }
 }
 
-async fn health_handler() -> impl IntoResponse {
-    Json(serde_json::json!({ "status": "ok" }))
+/// Selects a hosted network; the primary network if none is given
+#[derive(serde::Deserialize)]
+struct NetworkQuery {
+    network: Option<String>,
+}
+
+impl WitnessServer {
+    /// The hosted network with this ID
+    fn network(&self, network_id: &str) -> Result<&HostedNetwork, AppError> {
+        self.networks
+            .iter()
+            .find(|network| network.profile.network_id == network_id)
+            .ok_or_else(|| {
+                tracing::warn!("Request for network {}, which this node does not witness", network_id);
+                AppError::InvalidNetwork
+            })
+    }
+
+    fn network_for(&self, query: &NetworkQuery) -> Result<&HostedNetwork, AppError> {
+        match &query.network {
+            Some(network_id) => self.network(network_id).map_err(|_| AppError::NotFound),
+            None => Ok(&self.networks[0]),
+        }
+    }
+}
+
+async fn health_handler(State(server): State<WitnessServer>) -> impl IntoResponse {
+    let Some(clock) = server.clock.health() else {
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "ok" })));
+    };
+
+    // A witness that will not sign is not healthy
+    if server.clock.check().is_ok() {
+        (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "clock": clock })))
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "status": "clock_untrusted", "clock": clock })),
+        )
+    }
+}
+
+/// Refuse to sign while the local clock disagrees with the time sources
+fn check_clock(server: &WitnessServer) -> Result<(), AppError> {
+    server.clock.check().map_err(|reason| {
+        tracing::warn!("Refusing to sign: {}", reason);
+        AppError::ClockUntrusted(reason)
+    })
+}
+
+/// Keys and key history of one hosted network
+fn network_info(profile: &NetworkProfile) -> serde_json::Value {
+    let mut info = serde_json::json!({
+        "network_id": profile.network_id,
+        "public_key": profile.public_key(),
+        "signature_scheme": profile.keys.signature_scheme,
+    });
+
+    if let Some(pop) = profile.pop() {
+        info["pop"] = pop.into();
+    }
+
+    // Advertise retired keys so verifiers can still check older attestations
+    let (key_activated_at, previous_keys) = profile.key_history();
+    if let Some(activated_at) = key_activated_at {
+        info["key_activated_at"] = activated_at.into();
+    }
+    if !previous_keys.is_empty() {
+        info["previous_keys"] = serde_json::json!(previous_keys);
+    }
+
+    if let Some((public_key, activate_at)) = profile.pending_key() {
+        info["next_key"] = serde_json::json!({
+            "public_key": public_key,
+            "activate_at": activate_at,
+        });
+    }
+
+    info
 }
 
 async fn info_handler(State(server): State<WitnessServer>) -> impl IntoResponse {
-    Json(serde_json::json!({
-        "id": server.config.id,
-        "public_key": server.config.public_key(),
-        "network_id": server.config.network_id,
-    }))
+    // The top level describes the primary network, as for single-network nodes
+    let mut info = network_info(&server.config.primary);
+    info["id"] = server.config.id.clone().into();
+    info["networks"] = server.config.profiles().map(network_info).collect();
+
+    Json(info)
+}
+
+async fn manifest_handler(
+    State(server): State<WitnessServer>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let network = server.network_for(&query)?;
+    let path = network.profile.manifest_path.as_ref().ok_or(AppError::NotFound)?;
+
+    // Re-read on every request so a newly co-signed manifest is served without a restart
+    let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+        tracing::error!("Failed to read manifest {:?}: {}", path, e);
+        AppError::NotFound
+    })?;
+
+    let manifest: SignedManifest = serde_json::from_str(&content).map_err(|e| {
+        tracing::error!("Failed to parse manifest {:?}: {}", path, e);
+        AppError::InternalError
+    })?;
+
+    Ok(Json(manifest))
+}
+
+async fn log_head_handler(
+    State(server): State<WitnessServer>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let network = server.network_for(&query)?;
+    Ok(Json(
+        network
+            .signing_log
+            .head(&server.config.id, &network.profile.network_id),
+    ))
+}
+
+async fn log_entry_handler(
+    State(server): State<WitnessServer>,
+    Path(sequence): Path<u64>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let network = server.network_for(&query)?;
+    let entry = network.signing_log.find(sequence).await.map_err(|e| {
+        tracing::error!("Failed to search signing log: {:#}", e);
+        AppError::InternalError
+    })?;
+
+    Ok(Json(entry.ok_or(AppError::NotFound)?))
 }
 
 async fn sign_handler(
@@ -59,87 +277,213 @@ async fn sign_handler(
 ) -> Result<impl IntoResponse, AppError> {
     tracing::debug!("Received sign request: {}", request.attestation);
 
-    // Validate timestamp (basic sanity check)
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
+    // Roots go through /v1/sign/root, so a root is never signed as a plain hash
+    if request.attestation.is_root() {
+        return Err(AppError::InvalidLeafCount(
+            "Merkle root attestations must be sent to /v1/sign/root".to_string(),
+        ));
+    }
 
-    let time_diff = if request.attestation.timestamp > now {
-        request.attestation.timestamp - now
-    } else {
-        now - request.attestation.timestamp
-    };
+    let attestation = request.attestation;
+    signing_task(move || sign_attestation(&server, attestation)).await.map(Json)
+}
 
-    if time_diff > server.config.max_clock_skew {
-        tracing::warn!(
-            "Timestamp too far from current time: {} vs {}",
-            request.attestation.timestamp,
-            now
-        );
-        return Err(AppError::InvalidTimestamp);
-    }
-
-    // Validate network ID
-    if request.attestation.network_id != server.config.network_id {
-        tracing::warn!(
-            "Network ID mismatch: expected {}, got {}",
-            server.config.network_id,
-            request.attestation.network_id
-        );
-        return Err(AppError::InvalidNetwork);
-    }
-
-    // Sign the attestation based on signature scheme
-    let signature = match server.config.signature_scheme {
-        SignatureScheme::Ed25519 => {
-            let signing_key = server.config.ed25519_signing_key()
-                .map_err(|e| {
-                    tracing::error!("Failed to get Ed25519 signing key: {}", e);
-                    AppError::InternalError
-                })?;
-
-            witness_core::sign_attestation(&request.attestation, &signing_key)
-        }
-        SignatureScheme::BLS => {
-            let secret_key = server.config.bls_secret_key()
-                .map_err(|e| {
-                    tracing::error!("Failed to get BLS secret key: {}", e);
-                    AppError::InternalError
-                })?;
-
-            witness_core::sign_attestation_bls(&request.attestation, &secret_key)
-        }
-    };
+/// Sign a merkle root over a gateway micro-batch of hashes
+async fn sign_root_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<SignRootRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    tracing::debug!("Received sign root request: {}", request.attestation);
 
-    tracing::info!(
-        "Signed attestation {} for hash {} using {}",
-        request.attestation.sequence,
-        hex::encode(request.attestation.hash),
-        server.config.signature_scheme
-    );
+    if !request.attestation.is_root() {
+        return Err(AppError::InvalidLeafCount(
+            "Root attestation is missing its leaf count".to_string(),
+        ));
+    }
+
+    let attestation = request.attestation;
+    signing_task(move || sign_attestation(&server, attestation)).await.map(Json)
+}
+
+/// Run signing off the async runtime. The signing log syncs to disk, and a
+/// remote signer or PKCS#11 token blocks on I/O.
+pub(crate) async fn signing_task<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    tokio::task::spawn_blocking(task).await.map_err(|e| {
+        tracing::error!("Signing task failed: {}", e);
+        AppError::InternalError
+    })?
+}
+
+fn sign_attestation(server: &WitnessServer, attestation: Attestation) -> Result<SignResponse, AppError> {
+    check_clock(server)?;
+
+    let network = server.network(&attestation.network_id)?;
+    let now_ms = crate::policy::now_ms();
+    network
+        .policy
+        .check_attestation(&attestation, now_ms)
+        .map_err(|e| {
+            tracing::warn!("Refusing to sign attestation: {}", e);
+            AppError::from(e)
+        })?;
+
+    // Never sign two different attestations for the same place in history
+    network
+        .signing_log
+        .record(&attestation)
+        .map_err(|e| match e {
+            LogError::Equivocation(reason) => {
+                tracing::warn!("Refusing to sign attestation: {}", reason);
+                AppError::Equivocation(reason)
+            }
+            LogError::Storage(e) => {
+                tracing::error!("Failed to write signing log: {}", e);
+                AppError::InternalError
+            }
+        })?;
+
+    // Sign the attestation and this witness's own clock reading
+    let signer = network.profile.signer();
+    let (signature, reading_signature) = signer.sign_attestation_with_reading(&attestation, now_ms).map_err(|e| {
+        tracing::error!("Failed to sign with {}: {:#}", signer.describe(), e);
+        AppError::InternalError
+    })?;
+
+    match attestation.leaf_count {
+        Some(leaf_count) => tracing::info!(
+            "Signed attestation {} for root {} over {} hashes on {} using {}",
+            attestation.sequence,
+            hex::encode(attestation.hash),
+            leaf_count,
+            attestation.network_id,
+            network.profile.keys.signature_scheme
+        ),
+        None => tracing::info!(
+            "Signed attestation {} for hash {} on {} using {}",
+            attestation.sequence,
+            hex::encode(attestation.hash),
+            attestation.network_id,
+            network.profile.keys.signature_scheme
+        ),
+    }
 
     let response = SignResponse {
         witness_id: server.config.id.clone(),
         signature,
+        time_reading: Some(TimeReading {
+            witness_id: server.config.id.clone(),
+            time_ms: now_ms,
+            signature: reading_signature,
+        }),
     };
 
-    Ok(Json(response))
+    Ok(response)
+}
+
+async fn sign_cross_anchor_handler(
+    State(server): State<WitnessServer>,
+    Json(request): Json<CrossAnchorSignRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let statement = &request.statement;
+
+    tracing::debug!(
+        "Received cross-anchor sign request for batch {} of network {}",
+        statement.batch.id,
+        statement.batch.network_id
+    );
+
+    check_clock(&server)?;
+
+    // A witness only cross-anchors on behalf of a network it belongs to
+    let network = server.network(&statement.witnessing_network)?;
+    network
+        .policy
+        .check_cross_anchor(statement, crate::policy::now_ms())
+        .map_err(|e| {
+            tracing::warn!("Refusing to sign cross-anchor: {}", e);
+            AppError::from(e)
+        })?;
+
+    let signer = network.profile.signer().clone();
+    let to_sign = statement.clone();
+    let signature = signing_task(move || {
+        signer.sign_cross_anchor(&to_sign).map_err(|e| {
+            tracing::error!("Failed to sign with {}: {:#}", signer.describe(), e);
+            AppError::InternalError
+        })
+    })
+    .await?;
+
+    tracing::info!(
+        "Signed cross-anchor for batch {} of network {} (root: {})",
+        statement.batch.id,
+        statement.batch.network_id,
+        hex::encode(statement.batch.merkle_root)
+    );
+
+    Ok(Json(SignResponse {
+        witness_id: server.config.id.clone(),
+        signature,
+        time_reading: None,
+    }))
 }
 
 // Error handling
-enum AppError {
+pub(crate) enum AppError {
     InvalidTimestamp,
     InvalidNetwork,
+    UnsupportedVersion,
+    InvalidLeafCount(String),
+    NotFound,
+    Unauthorized(String),
+    ClockUntrusted(String),
+    Equivocation(String),
+    DkgDisabled,
+    Dkg(String),
     InternalError,
 }
 
+impl From<PolicyError> for AppError {
+    fn from(error: PolicyError) -> Self {
+        match error {
+            PolicyError::InvalidTimestamp(_) => AppError::InvalidTimestamp,
+            PolicyError::InvalidNetwork(_) => AppError::InvalidNetwork,
+            PolicyError::UnsupportedVersion(_) => AppError::UnsupportedVersion,
+            PolicyError::InvalidLeafCount(reason) => AppError::InvalidLeafCount(reason),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
-            AppError::InvalidTimestamp => (StatusCode::BAD_REQUEST, "Invalid timestamp"),
-            AppError::InvalidNetwork => (StatusCode::BAD_REQUEST, "Invalid network ID"),
-            AppError::InternalError => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error"),
+            AppError::InvalidTimestamp => (StatusCode::BAD_REQUEST, "Invalid timestamp".to_string()),
+            AppError::InvalidNetwork => (StatusCode::BAD_REQUEST, "Invalid network ID".to_string()),
+            AppError::UnsupportedVersion => (
+                StatusCode::BAD_REQUEST,
+                "Unsupported attestation encoding version".to_string(),
+            ),
+            AppError::InvalidLeafCount(message) => (StatusCode::BAD_REQUEST, message),
+            AppError::NotFound => (StatusCode::NOT_FOUND, "Not found".to_string()),
+            AppError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message),
+            AppError::ClockUntrusted(reason) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Witness clock is not trusted: {}", reason),
+            ),
+            AppError::Equivocation(reason) => (
+                StatusCode::CONFLICT,
+                format!("Conflicts with signing log: {}", reason),
+            ),
+            AppError::DkgDisabled => (
+                StatusCode::FORBIDDEN,
+                "DKG is not enabled on this witness (set dkg_dir)".to_string(),
+            ),
+            AppError::Dkg(message) => (StatusCode::BAD_REQUEST, message),
+            AppError::InternalError => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string())
+            }
         };
 
         (status, Json(serde_json::json!({ "error": message }))).into_response()
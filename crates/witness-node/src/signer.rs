@@ -0,0 +1,178 @@
+//! Signing backends
+//!
+//! Everything a witness signs goes through a `Signer`: keys held in memory
+//! (from `private_key` or an encrypted keystore), an Ed25519 key that never
+//! leaves a PKCS#11 token, or a signer daemon (see `remote_signer`). Signers only see the canonical,
+//! domain-separated message bytes from witness-core, so every backend
+//! produces the same signatures for the same key.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use witness_core::{
+    Attestation, CrossAnchorStatement, DkgComplaint, DkgDeal, NetworkManifest, SignatureScheme, TimeReading,
+};
+
+use crate::keystore::WitnessKey;
+
+pub trait Signer: Send + Sync {
+    /// Public key (hex encoded) of the key that signs messages dated `timestamp`
+    fn public_key_at(&self, timestamp: u64) -> String;
+
+    /// Sign canonical message bytes with the key valid at `timestamp`
+    fn sign_at(&self, message: &[u8], timestamp: u64) -> Result<Vec<u8>>;
+
+    /// BLS proof of possession for the key valid at `timestamp` (bls only)
+    fn pop_at(&self, _timestamp: u64) -> Option<String> {
+        None
+    }
+
+    /// Activation time of a scheduled key rotation, if any
+    fn rotation(&self) -> Option<u64> {
+        None
+    }
+
+    /// Where the key lives, for logs
+    fn describe(&self) -> String;
+
+    fn sign_attestation(&self, attestation: &Attestation) -> Result<Vec<u8>> {
+        self.sign_at(&attestation.to_bytes(), attestation.timestamp)
+    }
+
+    fn sign_time_reading(&self, attestation: &Attestation, time_ms: u64) -> Result<Vec<u8>> {
+        self.sign_at(&TimeReading::message(attestation, time_ms), attestation.timestamp)
+    }
+
+    /// Sign an attestation and this witness's clock reading for it together
+    fn sign_attestation_with_reading(&self, attestation: &Attestation, time_ms: u64) -> Result<(Vec<u8>, Vec<u8>)> {
+        Ok((
+            self.sign_attestation(attestation)?,
+            self.sign_time_reading(attestation, time_ms)?,
+        ))
+    }
+
+    fn sign_cross_anchor(&self, statement: &CrossAnchorStatement) -> Result<Vec<u8>> {
+        self.sign_at(&statement.to_bytes(), statement.timestamp)
+    }
+
+    fn sign_manifest(&self, manifest: &NetworkManifest) -> Result<Vec<u8>> {
+        self.sign_at(&manifest.to_bytes(), manifest.issued_at)
+    }
+
+    /// Sign a DKG deal with the current long-term key
+    fn sign_dkg_deal(&self, deal: &mut DkgDeal) -> Result<()> {
+        deal.signature = self.sign_at(&deal.to_bytes(), crate::config::now())?;
+        Ok(())
+    }
+
+    /// Sign a DKG complaint with the current long-term key
+    fn sign_dkg_complaint(&self, complaint: &mut DkgComplaint) -> Result<()> {
+        complaint.signature = self.sign_at(&complaint.to_bytes(), crate::config::now())?;
+        Ok(())
+    }
+}
+
+/// Key held on a PKCS#11 token (Ed25519 only)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pkcs11Config {
+    /// Path to the vendor's PKCS#11 module (e.g. /usr/lib/softhsm/libsofthsm2.so)
+    pub module: PathBuf,
+
+    /// Label of the token holding the key
+    pub token_label: String,
+
+    /// `CKA_LABEL` shared by the Ed25519 private key and its public key
+    pub key_label: String,
+}
+
+/// Keys decoded into memory, with an optional scheduled rotation
+pub struct LocalSigner {
+    scheme: SignatureScheme,
+    current: WitnessKey,
+    next: Option<(WitnessKey, u64)>,
+}
+
+impl LocalSigner {
+    pub fn new(scheme: SignatureScheme, current: WitnessKey, next: Option<(WitnessKey, u64)>) -> Self {
+        Self {
+            scheme,
+            current,
+            next,
+        }
+    }
+
+    fn key_at(&self, timestamp: u64) -> &WitnessKey {
+        match &self.next {
+            Some((next_key, activate_at)) if timestamp >= *activate_at => next_key,
+            _ => &self.current,
+        }
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key_at(&self, timestamp: u64) -> String {
+        self.key_at(timestamp).public_key()
+    }
+
+    fn sign_at(&self, message: &[u8], timestamp: u64) -> Result<Vec<u8>> {
+        Ok(match self.key_at(timestamp) {
+            WitnessKey::Ed25519(signing_key) => witness_core::sign_message(message, signing_key),
+            WitnessKey::Bls(secret_key) => witness_core::sign_message_bls(message, secret_key),
+        })
+    }
+
+    fn pop_at(&self, timestamp: u64) -> Option<String> {
+        match self.key_at(timestamp) {
+            WitnessKey::Bls(secret_key) if self.scheme == SignatureScheme::BLS => {
+                Some(hex::encode(witness_core::generate_bls_pop(secret_key)))
+            }
+            _ => None,
+        }
+    }
+
+    fn rotation(&self) -> Option<u64> {
+        self.next.as_ref().map(|(_, activate_at)| *activate_at)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} key in memory", self.scheme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_signer_matches_core() {
+        let current = WitnessKey::generate(SignatureScheme::Ed25519);
+        let next = WitnessKey::generate(SignatureScheme::Ed25519);
+        let (WitnessKey::Ed25519(current_key), WitnessKey::Ed25519(next_key)) = (current.clone(), next.clone()) else {
+            unreachable!()
+        };
+        let signer = LocalSigner::new(SignatureScheme::Ed25519, current, Some((next, 2000)));
+
+        let mut attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        attestation.timestamp = 1999;
+        assert_eq!(
+            signer.sign_attestation(&attestation).unwrap(),
+            witness_core::sign_attestation(&attestation, &current_key)
+        );
+        assert_eq!(
+            signer.sign_time_reading(&attestation, 1_999_000).unwrap(),
+            witness_core::sign_time_reading(&attestation, 1_999_000, &current_key)
+        );
+
+        // The scheduled key takes over at its activation time
+        attestation.timestamp = 2000;
+        assert_eq!(
+            signer.sign_attestation(&attestation).unwrap(),
+            witness_core::sign_attestation(&attestation, &next_key)
+        );
+        assert_eq!(signer.public_key_at(1999), witness_core::encode_public_key(&current_key.verifying_key()));
+        assert!(signer.pop_at(1999).is_none());
+
+        let bls = LocalSigner::new(SignatureScheme::BLS, WitnessKey::generate(SignatureScheme::BLS), None);
+        assert!(bls.pop_at(0).is_some());
+    }
+}
@@ -0,0 +1,544 @@
+//! Signer daemon (`witness-node signer`)
+//!
+//! Holds a witness key for a witness node that runs elsewhere, in the spirit
+//! of a validator key management service. The daemon enforces its own
+//! signing policy (allowed networks, clock skew against its own clock) and
+//! keeps its own signing log, so a compromised witness node cannot obtain a
+//! conflicting or back-dated signature. Manifest and DKG signatures are
+//! refused unless explicitly allowed.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use witness_core::RequestAuth;
+
+use crate::auth::NonceCache;
+use crate::config::KeyConfig;
+use crate::keystore::PassphraseSource;
+use crate::policy::SigningPolicy;
+use crate::remote_signer::{
+    RemoteKey, SignerAddress, SignerEnvelope, SignerRequest, SignerResponse, SIGNER_REQUEST_PATH,
+};
+use crate::signing_log::{LogError, SigningLog};
+
+/// Largest request line accepted
+const MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerDaemonConfig {
+    /// Identifier used in logs
+    pub id: String,
+
+    /// Address to listen on: `unix:<path>` or `tcp:<host>:<port>`
+    pub listen: SignerAddress,
+
+    /// The key this daemon signs with
+    #[serde(flatten)]
+    pub keys: KeyConfig,
+
+    /// Networks the daemon signs for
+    pub networks: Vec<String>,
+
+    /// Maximum clock skew allowed (seconds)
+    #[serde(default = "crate::config::default_max_clock_skew")]
+    pub max_clock_skew: u64,
+
+    /// Sign attestations using the legacy v0 encoding (no domain separation)
+    #[serde(default)]
+    pub allow_legacy_encoding: bool,
+
+    /// Ed25519 public keys (hex encoded) of witness nodes allowed to request
+    /// signatures. Required when listening on TCP.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorized_clients: Vec<String>,
+
+    /// Append-only log of signed sequences (see the witness node option).
+    /// Only valid with a single network; use `signing_logs` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_log: Option<PathBuf>,
+
+    /// One signing log per network, keyed by network ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub signing_logs: BTreeMap<String, PathBuf>,
+
+    /// Co-sign network manifests on request
+    #[serde(default)]
+    pub allow_manifests: bool,
+
+    /// Sign DKG deals and complaints on request
+    #[serde(default)]
+    pub allow_dkg: bool,
+}
+
+impl SignerDaemonConfig {
+    pub fn load(path: &Path, passphrase: &PassphraseSource) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+
+        let mut config: SignerDaemonConfig = serde_json::from_str(&content)
+            .with_context(|| "Failed to parse signer config JSON")?;
+
+        if config.keys.remote_signer.is_some() {
+            anyhow::bail!("A signer daemon cannot itself use a remote_signer");
+        }
+        if config.networks.is_empty() {
+            anyhow::bail!("No networks configured for the signer");
+        }
+        if matches!(config.listen, SignerAddress::Tcp(_)) && config.authorized_clients.is_empty() {
+            anyhow::bail!("authorized_clients must be set when listening on TCP");
+        }
+        for key in &config.authorized_clients {
+            witness_core::decode_public_key(key)
+                .with_context(|| format!("Invalid authorized client key: {}", key))?;
+        }
+
+        // Sequences are per network, so networks cannot share a signing log
+        if config.signing_log.is_some() && (config.networks.len() > 1 || !config.signing_logs.is_empty()) {
+            anyhow::bail!("signing_log only covers a single network; set signing_logs for each network instead");
+        }
+        let mut paths = HashSet::new();
+        for (network, path) in &config.signing_logs {
+            if !config.networks.contains(network) {
+                anyhow::bail!("signing_logs lists network {}, which is not in networks", network);
+            }
+            if !paths.insert(path) {
+                anyhow::bail!("Networks cannot share the signing log {:?}", path);
+            }
+        }
+
+        config.keys.unlock(passphrase, &mut Default::default())?;
+        Ok(config)
+    }
+
+    /// Where the signing log for `network` is kept, if anywhere
+    fn signing_log_path(&self, network: &str) -> Option<&Path> {
+        match self.signing_logs.get(network) {
+            Some(path) => Some(path),
+            None if self.networks.len() == 1 => self.signing_log.as_deref(),
+            None => None,
+        }
+    }
+}
+
+pub struct SignerDaemon {
+    config: SignerDaemonConfig,
+    policy: SigningPolicy,
+    signing_logs: HashMap<String, SigningLog>,
+    nonces: NonceCache,
+}
+
+impl SignerDaemon {
+    pub fn new(config: SignerDaemonConfig) -> Result<Self> {
+        let signing_logs = config
+            .networks
+            .iter()
+            .map(|network| {
+                let path = config.signing_log_path(network);
+                if path.is_none() {
+                    tracing::error!(
+                        "Network {} has no durable signing log (set signing_log or signing_logs); \
+                         signed sequences are forgotten on restart",
+                        network
+                    );
+                }
+                Ok((network.clone(), SigningLog::open(path)?))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        let policy = SigningPolicy {
+            networks: config.networks.clone(),
+            max_clock_skew: config.max_clock_skew,
+            allow_legacy_encoding: config.allow_legacy_encoding,
+        };
+
+        Ok(Self {
+            config,
+            policy,
+            signing_logs,
+            nonces: NonceCache::default(),
+        })
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let daemon = Arc::new(self);
+        let listen = daemon.config.listen.clone();
+
+        tracing::info!("Signer {} listening on {}", daemon.config.id, listen);
+
+        match listen {
+            SignerAddress::Tcp(address) => {
+                let listener = tokio::net::TcpListener::bind(&address).await?;
+                loop {
+                    let (stream, peer) = listener.accept().await?;
+                    tracing::info!("Witness node connected from {}", peer);
+                    stream.set_nodelay(true)?;
+                    tokio::spawn(daemon.clone().serve(stream));
+                }
+            }
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+                // Replace a socket left behind by an earlier run, but nothing else
+                if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+                    if !metadata.file_type().is_socket() {
+                        anyhow::bail!("Refusing to replace {:?}, which is not a socket", path);
+                    }
+                    std::fs::remove_file(&path)?;
+                }
+
+                let listener = tokio::net::UnixListener::bind(&path)
+                    .with_context(|| format!("Failed to bind signer socket: {:?}", path))?;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+                loop {
+                    let (stream, _) = listener.accept().await?;
+                    tracing::info!("Witness node connected on {:?}", path);
+                    tokio::spawn(daemon.clone().serve(stream));
+                }
+            }
+            #[cfg(not(unix))]
+            SignerAddress::Unix(_) => anyhow::bail!("Unix sockets are only supported on Unix"),
+        }
+    }
+
+    /// Answer requests on one connection until it closes
+    async fn serve<S: AsyncRead + AsyncWrite>(self: Arc<Self>, stream: S) {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut reader = BufReader::new(reader);
+
+        loop {
+            let mut line = String::new();
+            match (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line).await {
+                Ok(0) => break,
+                Ok(_) if !line.ends_with('\n') => {
+                    tracing::warn!("Dropping connection after an oversized or truncated request");
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Signer connection failed: {}", e);
+                    break;
+                }
+            }
+
+            // Signing may write the signing log or wait on a token
+            let daemon = self.clone();
+            let response = tokio::task::spawn_blocking(move || daemon.handle(&line))
+                .await
+                .unwrap_or_else(|e| Err(format!("signer task failed: {}", e)))
+                .unwrap_or_else(|message| {
+                    tracing::warn!("Refusing signer request: {}", message);
+                    SignerResponse::Error { message }
+                });
+
+            let mut response = serde_json::to_string(&response).expect("response serializes");
+            response.push('\n');
+            if writer.write_all(response.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn handle(&self, line: &str) -> std::result::Result<SignerResponse, String> {
+        let envelope: SignerEnvelope =
+            serde_json::from_str(line).map_err(|e| format!("malformed request: {}", e))?;
+        self.authenticate(&envelope)?;
+
+        let request: SignerRequest =
+            serde_json::from_str(&envelope.request).map_err(|e| format!("malformed request: {}", e))?;
+        self.process(request)
+    }
+
+    fn authenticate(&self, envelope: &SignerEnvelope) -> std::result::Result<(), String> {
+        if self.config.authorized_clients.is_empty() {
+            return Ok(());
+        }
+
+        let auth: &RequestAuth = envelope.auth.as_ref().ok_or("missing client authentication")?;
+        if !self
+            .config
+            .authorized_clients
+            .iter()
+            .any(|key| key.eq_ignore_ascii_case(&auth.gateway_key))
+        {
+            return Err(format!("client key {} is not authorized", auth.gateway_key));
+        }
+
+        let now = crate::config::now();
+        if auth.timestamp.abs_diff(now) > self.config.max_clock_skew {
+            return Err("client authentication timestamp is outside the allowed clock skew".to_string());
+        }
+
+        auth.verify(SIGNER_REQUEST_PATH, envelope.request.as_bytes())
+            .map_err(|_| format!("invalid client signature from {}", auth.gateway_key))?;
+
+        if !self
+            .nonces
+            .insert(&auth.nonce, auth.timestamp, now, self.config.max_clock_skew)
+        {
+            return Err(format!("replayed request from {}", auth.gateway_key));
+        }
+        Ok(())
+    }
+
+    fn process(&self, request: SignerRequest) -> std::result::Result<SignerResponse, String> {
+        let signer = self.config.keys.signer();
+        let now_ms = crate::policy::now_ms();
+        let policy_error = |e: crate::policy::PolicyError| e.to_string();
+        let sign_error = |e: anyhow::Error| {
+            tracing::error!("Failed to sign with {}: {:#}", signer.describe(), e);
+            "signing failed".to_string()
+        };
+
+        let signature = match request {
+            SignerRequest::Keys => {
+                let rotation = signer.rotation();
+                let before = rotation.map_or(0, |activate_at| activate_at.saturating_sub(1));
+                return Ok(SignerResponse::Keys {
+                    scheme: self.config.keys.signature_scheme,
+                    current: RemoteKey {
+                        public_key: signer.public_key_at(before),
+                        pop: signer.pop_at(before),
+                        activate_at: None,
+                    },
+                    next: rotation.map(|activate_at| RemoteKey {
+                        public_key: signer.public_key_at(activate_at),
+                        pop: signer.pop_at(activate_at),
+                        activate_at: Some(activate_at),
+                    }),
+                });
+            }
+            SignerRequest::Attestation { attestation, time_ms } => {
+                self.policy
+                    .check_attestation(&attestation, now_ms)
+                    .map_err(policy_error)?;
+                if let Some(time_ms) = time_ms {
+                    self.policy
+                        .check_time_reading(&attestation, time_ms, now_ms)
+                        .map_err(policy_error)?;
+                }
+
+                let signing_log = self
+                    .signing_logs
+                    .get(&attestation.network_id)
+                    .ok_or_else(|| format!("network {} is not allowed", attestation.network_id))?;
+                signing_log.record(&attestation).map_err(|e| match e {
+                    LogError::Equivocation(reason) => format!("conflicts with signing log: {}", reason),
+                    LogError::Storage(e) => {
+                        tracing::error!("Failed to write signing log: {}", e);
+                        "signing log unavailable".to_string()
+                    }
+                })?;
+
+                // A clock reading is only ever signed over an attestation just logged
+                let signature = signer.sign_attestation(&attestation).map_err(sign_error)?;
+                let time_reading = time_ms
+                    .map(|time_ms| signer.sign_time_reading(&attestation, time_ms))
+                    .transpose()
+                    .map_err(sign_error)?;
+                tracing::info!(
+                    "Signed attestation {} for network {}",
+                    attestation.sequence,
+                    attestation.network_id
+                );
+                return Ok(SignerResponse::Signature {
+                    signature: hex::encode(signature),
+                    time_reading: time_reading.map(hex::encode),
+                });
+            }
+            SignerRequest::CrossAnchor { statement } => {
+                self.policy
+                    .check_cross_anchor(&statement, now_ms)
+                    .map_err(policy_error)?;
+
+                let signature = signer.sign_cross_anchor(&statement).map_err(sign_error)?;
+                tracing::info!(
+                    "Signed cross-anchor for batch {} of network {}",
+                    statement.batch.id,
+                    statement.batch.network_id
+                );
+                signature
+            }
+            SignerRequest::Manifest { manifest } => {
+                if !self.config.allow_manifests {
+                    return Err("manifest signing is not allowed (set allow_manifests)".to_string());
+                }
+                if !self.policy.networks.contains(&manifest.network.id) {
+                    return Err(format!("network {} is not allowed", manifest.network.id));
+                }
+
+                let signature = signer.sign_manifest(&manifest).map_err(sign_error)?;
+                tracing::info!(
+                    "Signed manifest version {} for network {}",
+                    manifest.version,
+                    manifest.network.id
+                );
+                signature
+            }
+            SignerRequest::DkgDeal { mut deal } => {
+                if !self.config.allow_dkg {
+                    return Err("DKG deal signing is not allowed (set allow_dkg)".to_string());
+                }
+
+                signer.sign_dkg_deal(&mut deal).map_err(sign_error)?;
+                tracing::info!("Signed DKG deal for ceremony {}", deal.ceremony_id);
+                deal.signature
+            }
+            SignerRequest::DkgComplaint { mut complaint } => {
+                if !self.config.allow_dkg {
+                    return Err("DKG complaint signing is not allowed (set allow_dkg)".to_string());
+                }
+
+                signer.sign_dkg_complaint(&mut complaint).map_err(sign_error)?;
+                tracing::info!(
+                    "Signed DKG complaint against dealer {} for ceremony {}",
+                    complaint.dealer,
+                    complaint.ceremony_id
+                );
+                complaint.signature
+            }
+        };
+
+        Ok(SignerResponse::Signature {
+            signature: hex::encode(signature),
+            time_reading: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::{LocalSigner, Signer};
+    use witness_core::{Attestation, SignatureScheme};
+
+    fn daemon(authorized_clients: Vec<String>) -> SignerDaemon {
+        daemon_for(&["test-net"], authorized_clients)
+    }
+
+    fn daemon_for(networks: &[&str], authorized_clients: Vec<String>) -> SignerDaemon {
+        let mut config: SignerDaemonConfig = serde_json::from_value(serde_json::json!({
+            "id": "signer-1",
+            "listen": "unix:/tmp/witness-signer-test.sock",
+            "private_key": hex::encode([7u8; 32]),
+            "networks": networks,
+            "authorized_clients": authorized_clients,
+        }))
+        .unwrap();
+        config.keys.unlock(&PassphraseSource::EnvOrPrompt, &mut Default::default()).unwrap();
+        SignerDaemon::new(config).unwrap()
+    }
+
+    fn envelope(request: &SignerRequest, client_key: Option<&ed25519_dalek::SigningKey>) -> String {
+        let body = serde_json::to_string(request).unwrap();
+        serde_json::to_string(&SignerEnvelope {
+            auth: client_key.map(|key| RequestAuth::sign(SIGNER_REQUEST_PATH, body.as_bytes(), key)),
+            request: body,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signer_daemon_policy() {
+        let daemon = daemon(vec![]);
+        let local = LocalSigner::new(
+            SignatureScheme::Ed25519,
+            crate::keystore::WitnessKey::from_hex(SignatureScheme::Ed25519, &hex::encode([7u8; 32])).unwrap(),
+            None,
+        );
+
+        let attestation = Attestation::new([1; 32], "test-net".to_string(), 1);
+        let time_ms = crate::policy::now_ms();
+        let request = SignerRequest::Attestation {
+            attestation: attestation.clone(),
+            time_ms: Some(time_ms),
+        };
+        match daemon.handle(&envelope(&request, None)).unwrap() {
+            SignerResponse::Signature { signature, time_reading } => {
+                assert_eq!(signature, hex::encode(local.sign_attestation(&attestation).unwrap()));
+                assert_eq!(
+                    time_reading,
+                    Some(hex::encode(local.sign_time_reading(&attestation, time_ms).unwrap()))
+                );
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        // Same sequence, different hash: no signature and no clock reading
+        let mut conflicting = attestation.clone();
+        conflicting.hash = [2; 32];
+        let request = SignerRequest::Attestation {
+            attestation: conflicting,
+            time_ms: Some(time_ms),
+        };
+        assert!(daemon.handle(&envelope(&request, None)).is_err());
+
+        // Another network, a back-dated timestamp, and a back-dated reading are all refused
+        let mut other_network = Attestation::new([3; 32], "other-net".to_string(), 2);
+        let request = SignerRequest::Attestation {
+            attestation: other_network.clone(),
+            time_ms: None,
+        };
+        assert!(daemon.handle(&envelope(&request, None)).is_err());
+
+        other_network.network_id = "test-net".to_string();
+        let mut backdated = other_network.clone();
+        backdated.timestamp -= 3600;
+        let request = SignerRequest::Attestation {
+            attestation: backdated,
+            time_ms: None,
+        };
+        assert!(daemon.handle(&envelope(&request, None)).is_err());
+
+        let request = SignerRequest::Attestation {
+            attestation: other_network.clone(),
+            time_ms: Some(time_ms - 3_600_000),
+        };
+        assert!(daemon.handle(&envelope(&request, None)).is_err());
+
+        // The refused reading did not use up the sequence
+        let request = SignerRequest::Attestation {
+            attestation: other_network,
+            time_ms: Some(time_ms),
+        };
+        assert!(daemon.handle(&envelope(&request, None)).is_ok());
+    }
+
+    #[test]
+    fn test_signer_daemon_signing_log_per_network() {
+        let daemon = daemon_for(&["net-a", "net-b"], vec![]);
+        let sign = |network: &str, hash: u8| {
+            let request = SignerRequest::Attestation {
+                attestation: Attestation::new([hash; 32], network.to_string(), 1),
+                time_ms: None,
+            };
+            daemon.handle(&envelope(&request, None))
+        };
+
+        // Each network has its own sequence 1
+        assert!(matches!(sign("net-a", 1), Ok(SignerResponse::Signature { .. })));
+        assert!(matches!(sign("net-b", 2), Ok(SignerResponse::Signature { .. })));
+
+        // but still cannot sign a conflicting one
+        assert!(sign("net-a", 3).is_err());
+        assert!(sign("net-b", 4).is_err());
+    }
+
+    #[test]
+    fn test_signer_daemon_authentication() {
+        let client_key = ed25519_dalek::SigningKey::from_bytes(&[9u8; 32]);
+        let stranger = ed25519_dalek::SigningKey::from_bytes(&[8u8; 32]);
+        let daemon = daemon(vec![witness_core::encode_public_key(&client_key.verifying_key())]);
+
+        assert!(daemon.handle(&envelope(&SignerRequest::Keys, None)).is_err());
+        assert!(daemon.handle(&envelope(&SignerRequest::Keys, Some(&stranger))).is_err());
+
+        let line = envelope(&SignerRequest::Keys, Some(&client_key));
+        assert!(matches!(daemon.handle(&line), Ok(SignerResponse::Keys { .. })));
+
+        // The same authenticated request cannot be replayed
+        assert!(daemon.handle(&line).is_err());
+    }
+}
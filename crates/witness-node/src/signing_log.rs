@@ -0,0 +1,319 @@
+//! Persistent signing log (equivocation protection)
+//!
+//! Every attestation is appended to the log, and synced to disk, before its
+//! signature leaves the node. The node refuses to sign a second hash or
+//! timestamp for a sequence it has already signed, and refuses sequences whose
+//! timestamps are out of order with their neighbours, so a gateway cannot get
+//! witnesses to sign two conflicting histories. The log head is published at
+//! `/v1/log/head` for auditors.
+//!
+//! Concurrent requests from a gateway can arrive slightly out of order, so a
+//! gap below the highest signed sequence may still be filled, as long as it
+//! falls within the last `REORDER_WINDOW` sequences.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use witness_core::{format_timestamp_ms, Attestation, SigningLogEntry, SigningLogHead};
+
+/// Number of recent sequences kept in memory for conflict and ordering checks
+const REORDER_WINDOW: usize = 1024;
+
+/// Why the log refused an attestation
+#[derive(Debug)]
+pub enum LogError {
+    /// Signing would contradict an earlier signature
+    Equivocation(String),
+    /// The entry could not be made durable
+    Storage(std::io::Error),
+}
+
+pub struct SigningLog {
+    path: Option<PathBuf>,
+    state: Mutex<LogState>,
+}
+
+struct LogState {
+    file: Option<File>,
+    recent: BTreeMap<u64, SigningLogEntry>,
+    entries: u64,
+    log_hash: [u8; 32],
+}
+
+impl LogState {
+    fn new(file: Option<File>) -> Self {
+        Self {
+            file,
+            recent: BTreeMap::new(),
+            entries: 0,
+            log_hash: [0u8; 32],
+        }
+    }
+
+    /// Check an entry against the log; `Ok(true)` means it was already signed
+    fn check(&self, entry: &SigningLogEntry) -> std::result::Result<bool, String> {
+        if let Some(existing) = self.recent.get(&entry.sequence) {
+            if existing == entry {
+                return Ok(true);
+            }
+            return Err(format!(
+                "sequence {} was already signed for hash {} at {}",
+                existing.sequence, existing.hash, existing.timestamp
+            ));
+        }
+
+        // Older sequences have left the window, so they can no longer be checked
+        let trimmed = self.entries as usize > self.recent.len();
+        if let Some(&oldest) = self.recent.keys().next() {
+            if trimmed && entry.sequence < oldest {
+                return Err(format!(
+                    "sequence {} is older than the signing log window (oldest {})",
+                    entry.sequence, oldest
+                ));
+            }
+        }
+
+        // Timestamps come from requests; refuse one that overflows in milliseconds
+        let timestamp_ms = |entry: &SigningLogEntry| {
+            entry
+                .timestamp_ms()
+                .ok_or_else(|| format!("timestamp {} for sequence {} is out of range", entry.timestamp, entry.sequence))
+        };
+        let entry_ms = timestamp_ms(entry)?;
+
+        if let Some(previous) = self.recent.range(..entry.sequence).next_back().map(|(_, e)| e) {
+            let previous_ms = timestamp_ms(previous)?;
+            if entry_ms < previous_ms {
+                return Err(format!(
+                    "timestamp {} for sequence {} precedes sequence {} at {}",
+                    format_timestamp_ms(entry_ms),
+                    entry.sequence,
+                    previous.sequence,
+                    format_timestamp_ms(previous_ms)
+                ));
+            }
+        }
+
+        if let Some(next) = self.recent.range(entry.sequence + 1..).next().map(|(_, e)| e) {
+            let next_ms = timestamp_ms(next)?;
+            if entry_ms > next_ms {
+                return Err(format!(
+                    "timestamp {} for sequence {} follows sequence {} at {}",
+                    format_timestamp_ms(entry_ms),
+                    entry.sequence,
+                    next.sequence,
+                    format_timestamp_ms(next_ms)
+                ));
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn apply(&mut self, entry: SigningLogEntry, hash: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.log_hash);
+        hasher.update(entry.sequence.to_be_bytes());
+        hasher.update(hash);
+        hasher.update(entry.timestamp.to_be_bytes());
+        if let Some(millis) = entry.timestamp_millis {
+            hasher.update(millis.to_be_bytes());
+        }
+        if let Some(leaf_count) = entry.leaf_count {
+            hasher.update(leaf_count.to_be_bytes());
+        }
+        self.log_hash = hasher.finalize().into();
+
+        self.entries += 1;
+        self.recent.insert(entry.sequence, entry);
+        while self.recent.len() > REORDER_WINDOW {
+            self.recent.pop_first();
+        }
+    }
+}
+
+impl SigningLog {
+    /// Open (or create) the log at `path`, replaying existing entries;
+    /// without a path the log is kept in memory only
+    pub fn open(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self {
+                path: None,
+                state: Mutex::new(LogState::new(None)),
+            });
+        };
+
+        let mut content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read signing log: {:?}", path))
+            }
+        };
+
+        // A crash mid-append leaves a partial line; that signature was never released
+        let complete = content.rfind('\n').map_or(0, |i| i + 1);
+        if complete < content.len() {
+            tracing::warn!("Discarding incomplete final entry in signing log {:?}", path);
+            content.truncate(complete);
+            std::fs::write(path, &content)
+                .with_context(|| format!("Failed to repair signing log: {:?}", path))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open signing log: {:?}", path))?;
+
+        let mut state = LogState::new(Some(file));
+        for (line_number, line) in content.lines().enumerate() {
+            let entry: SigningLogEntry = serde_json::from_str(line).with_context(|| {
+                format!("Corrupt signing log {:?} at line {}", path, line_number + 1)
+            })?;
+            let hash = hex::decode(&entry.hash).with_context(|| {
+                format!("Corrupt signing log {:?} at line {}", path, line_number + 1)
+            })?;
+            state.apply(entry, &hash);
+        }
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            state: Mutex::new(state),
+        })
+    }
+
+    /// Record an attestation before signing it
+    ///
+    /// Re-signing an identical attestation is allowed so gateways can retry.
+    pub fn record(&self, attestation: &Attestation) -> std::result::Result<(), LogError> {
+        let entry = SigningLogEntry::from(attestation);
+        let mut state = self.state.lock().unwrap();
+
+        if state.check(&entry).map_err(LogError::Equivocation)? {
+            return Ok(());
+        }
+
+        if let Some(file) = state.file.as_mut() {
+            let mut line = serde_json::to_vec(&entry).expect("log entry serializes");
+            line.push(b'\n');
+            file.write_all(&line)
+                .and_then(|_| file.sync_data())
+                .map_err(LogError::Storage)?;
+        }
+
+        state.apply(entry, &attestation.hash);
+        Ok(())
+    }
+
+    pub fn head(&self, witness_id: &str, network_id: &str) -> SigningLogHead {
+        let state = self.state.lock().unwrap();
+
+        SigningLogHead {
+            witness_id: witness_id.to_string(),
+            network_id: network_id.to_string(),
+            entries: state.entries,
+            latest: state.recent.values().next_back().cloned(),
+            log_hash: hex::encode(state.log_hash),
+        }
+    }
+
+    /// Look up what was signed for a sequence
+    pub async fn find(&self, sequence: u64) -> Result<Option<SigningLogEntry>> {
+        if let Some(entry) = self.state.lock().unwrap().recent.get(&sequence) {
+            return Ok(Some(entry.clone()));
+        }
+
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read signing log: {:?}", path))?;
+
+        // A concurrent append may leave a partial last line; skip anything unparsable
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<SigningLogEntry>(line).ok())
+            .find(|entry| entry.sequence == sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attestation(hash: u8, timestamp: u64, sequence: u64) -> Attestation {
+        Attestation {
+            hash: [hash; 32],
+            timestamp,
+            network_id: "test-network".to_string(),
+            sequence,
+            version: witness_core::ATTESTATION_VERSION,
+            timestamp_millis: None,
+            leaf_count: None,
+        }
+    }
+
+    #[test]
+    fn test_signing_log_refuses_equivocation() {
+        let log = SigningLog::open(None).unwrap();
+
+        assert!(log.record(&attestation(1, 1000, 1)).is_ok());
+        assert!(log.record(&attestation(2, 1001, 3)).is_ok());
+
+        // Identical retry is fine; a different hash or timestamp for the same sequence is not
+        assert!(log.record(&attestation(1, 1000, 1)).is_ok());
+        assert!(log.record(&attestation(9, 1000, 1)).is_err());
+        assert!(log.record(&attestation(1, 1002, 1)).is_err());
+
+        // Signing the same hash as a root over a batch is a different statement
+        let mut root = attestation(1, 1000, 1);
+        root.leaf_count = Some(2);
+        assert!(log.record(&root).is_err());
+
+        // A gap may be filled only with a timestamp between its neighbours
+        assert!(log.record(&attestation(3, 1002, 2)).is_err());
+        assert!(log.record(&attestation(3, 999, 2)).is_err());
+        assert!(log.record(&attestation(3, 1000, 2)).is_ok());
+
+        // Timestamps may not go backwards
+        assert!(log.record(&attestation(4, 1000, 4)).is_err());
+
+        let head = log.head("w1", "test-network");
+        assert_eq!(head.entries, 3);
+        assert_eq!(head.latest.unwrap().sequence, 3);
+    }
+
+    #[test]
+    fn test_signing_log_survives_restart() {
+        let path = std::env::temp_dir()
+            .join(format!("witness-signing-log-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let log = SigningLog::open(Some(&path)).unwrap();
+        log.record(&attestation(1, 1000, 1)).unwrap();
+        log.record(&attestation(2, 1001, 2)).unwrap();
+        let head = log.head("w1", "test-network");
+        drop(log);
+
+        // Simulate a torn write
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"sequence\":3,").unwrap();
+        drop(file);
+
+        let reopened = SigningLog::open(Some(&path)).unwrap();
+        let reopened_head = reopened.head("w1", "test-network");
+        assert_eq!(reopened_head.entries, 2);
+        assert_eq!(reopened_head.log_hash, head.log_hash);
+        assert!(reopened.record(&attestation(9, 1001, 2)).is_err());
+        assert!(reopened.record(&attestation(3, 1002, 3)).is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
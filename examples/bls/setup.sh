@@ -29,6 +29,8 @@ for i in 1 2 3; do
 
     PUBKEY=$(echo "$OUTPUT" | grep "Public key:" | awk '{print $3}')
     PRIVKEY=$(echo "$OUTPUT" | grep "Private key:" | awk '{print $3}')
+    POP=$(echo "$OUTPUT" | grep "Proof of possession:" | awk '{print $4}')
+    eval "POP$i=$POP"
 
     # Create witness config
     cat > "$BLS_DIR/witness-$i.json" <<EOF
@@ -38,6 +40,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "network_id": "bls-network",
   "port": $((8000 + i)),
+  "data_dir": "$BLS_DIR/data/witness-$i",
   "max_clock_skew": 300
 }
 EOF
@@ -67,12 +70,14 @@ if [ -z "$PUBKEY1" ]; then
         OUTPUT=$("$PROJECT_ROOT/target/release/witness-node" --generate-key --bls)
         PUBKEY=$(echo "$OUTPUT" | grep "Public key:" | awk '{print $3}')
         PRIVKEY=$(echo "$OUTPUT" | grep "Private key:" | awk '{print $3}')
+        POP=$(echo "$OUTPUT" | grep "Proof of possession:" | awk '{print $4}')
 
         # Update witness config
         jq --arg pk "$PRIVKEY" '.private_key = $pk' "$BLS_DIR/witness-$i.json" > "$BLS_DIR/witness-$i.json.tmp"
         mv "$BLS_DIR/witness-$i.json.tmp" "$BLS_DIR/witness-$i.json"
 
         eval "PUBKEY$i=$PUBKEY"
+        eval "POP$i=$POP"
     done
 fi
 
@@ -85,16 +90,19 @@ cat > "$BLS_DIR/network.json" <<EOF
     {
       "id": "witness-1",
       "pubkey": "$PUBKEY1",
+      "pop": "$POP1",
       "endpoint": "http://localhost:8001"
     },
     {
       "id": "witness-2",
       "pubkey": "$PUBKEY2",
+      "pop": "$POP2",
       "endpoint": "http://localhost:8002"
     },
     {
       "id": "witness-3",
       "pubkey": "$PUBKEY3",
+      "pop": "$POP3",
       "endpoint": "http://localhost:8003"
     }
   ]
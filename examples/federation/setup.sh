@@ -42,6 +42,7 @@ for net in a b c; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "network-$net",
+  "data_dir": "examples/federation/data/witness-$net-$i",
   "max_clock_skew": 300
 }
 EOF
@@ -53,131 +54,63 @@ done
 echo "Creating network configurations with federation..."
 echo
 
-# Network A (port 9001)
-cat > "examples/federation/network-a.json" <<EOF
-{
-  "id": "network-a",
-  "threshold": 2,
-  "witnesses": [
-    {
-      "id": "witness-a-1",
-      "pubkey": "$NET_A_W1_PUBKEY",
-      "endpoint": "http://localhost:$NET_A_W1_PORT"
-    },
-    {
-      "id": "witness-a-2",
-      "pubkey": "$NET_A_W2_PUBKEY",
-      "endpoint": "http://localhost:$NET_A_W2_PORT"
-    },
-    {
-      "id": "witness-a-3",
-      "pubkey": "$NET_A_W3_PUBKEY",
-      "endpoint": "http://localhost:$NET_A_W3_PORT"
-    }
-  ],
-  "federation": {
-    "enabled": true,
-    "batch_period": 60,
-    "peer_networks": [
-      {
-        "id": "network-b",
-        "gateway": "http://localhost:9002",
-        "min_witnesses": 2
-      },
-      {
-        "id": "network-c",
-        "gateway": "http://localhost:9003",
-        "min_witnesses": 2
-      }
-    ],
-    "cross_anchor_threshold": 2
-  }
+# Witness list for a network, as it appears in its own config and in its peers'
+witnesses_json() {
+    local net=$1 indent=$2
+    for i in 1 2 3; do
+        local pubkey="NET_${net^^}_W${i}_PUBKEY" port="NET_${net^^}_W${i}_PORT"
+        [ $i -gt 1 ] && echo ","
+        printf '%s{\n' "$indent"
+        printf '%s  "id": "witness-%s-%s",\n' "$indent" "$net" "$i"
+        printf '%s  "pubkey": "%s",\n' "$indent" "${!pubkey}"
+        printf '%s  "endpoint": "http://localhost:%s"\n' "$indent" "${!port}"
+        printf '%s}' "$indent"
+    done
+    echo
 }
-EOF
 
-# Network B (port 9002)
-cat > "examples/federation/network-b.json" <<EOF
-{
-  "id": "network-b",
-  "threshold": 2,
-  "witnesses": [
-    {
-      "id": "witness-b-1",
-      "pubkey": "$NET_B_W1_PUBKEY",
-      "endpoint": "http://localhost:$NET_B_W1_PORT"
-    },
-    {
-      "id": "witness-b-2",
-      "pubkey": "$NET_B_W2_PUBKEY",
-      "endpoint": "http://localhost:$NET_B_W2_PORT"
-    },
-    {
-      "id": "witness-b-3",
-      "pubkey": "$NET_B_W3_PUBKEY",
-      "endpoint": "http://localhost:$NET_B_W3_PORT"
-    }
-  ],
-  "federation": {
-    "enabled": true,
-    "batch_period": 60,
-    "peer_networks": [
-      {
-        "id": "network-a",
-        "gateway": "http://localhost:9001",
-        "min_witnesses": 2
-      },
+# Peer entry pinning the peer's witness keys; they are never fetched from the peer gateway
+peer_json() {
+    local net=$1
+    cat <<EOF
       {
-        "id": "network-c",
-        "gateway": "http://localhost:9003",
-        "min_witnesses": 2
+        "id": "network-$net",
+        "gateway": "http://localhost:$((9001 + $(printf '%d' "'$net") - 97))",
+        "min_witnesses": 2,
+        "threshold": 2,
+        "witnesses": [
+$(witnesses_json "$net" "          ")
+        ]
       }
-    ],
-    "cross_anchor_threshold": 2
-  }
-}
 EOF
+}
+
+# Network A (port 9001), B (port 9002), C (port 9003)
+for net in a b c; do
+    peers=()
+    for peer in a b c; do
+        [ "$peer" != "$net" ] && peers+=("$peer")
+    done
 
-# Network C (port 9003)
-cat > "examples/federation/network-c.json" <<EOF
+    cat > "examples/federation/network-$net.json" <<EOF
 {
-  "id": "network-c",
+  "id": "network-$net",
   "threshold": 2,
   "witnesses": [
-    {
-      "id": "witness-c-1",
-      "pubkey": "$NET_C_W1_PUBKEY",
-      "endpoint": "http://localhost:$NET_C_W1_PORT"
-    },
-    {
-      "id": "witness-c-2",
-      "pubkey": "$NET_C_W2_PUBKEY",
-      "endpoint": "http://localhost:$NET_C_W2_PORT"
-    },
-    {
-      "id": "witness-c-3",
-      "pubkey": "$NET_C_W3_PUBKEY",
-      "endpoint": "http://localhost:$NET_C_W3_PORT"
-    }
+$(witnesses_json "$net" "    ")
   ],
   "federation": {
     "enabled": true,
     "batch_period": 60,
     "peer_networks": [
-      {
-        "id": "network-a",
-        "gateway": "http://localhost:9001",
-        "min_witnesses": 2
-      },
-      {
-        "id": "network-b",
-        "gateway": "http://localhost:9002",
-        "min_witnesses": 2
-      }
+$(peer_json "${peers[0]}"),
+$(peer_json "${peers[1]}")
     ],
     "cross_anchor_threshold": 2
   }
 }
 EOF
+done
 
 echo "✓ Federation setup complete!"
 echo
@@ -44,6 +44,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "gateway1-network",
+  "data_dir": "examples/gateway1/data/witness$i",
   "max_clock_skew": 300
 }
 EOF
@@ -72,6 +73,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "gateway2-network",
+  "data_dir": "examples/gateway2/data/witness$i",
   "max_clock_skew": 300
 }
 EOF
@@ -45,6 +45,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "gateway1-network",
+  "data_dir": "examples/gateway1/data/witness$i",
   "max_clock_skew": 300
 }
 EOF
@@ -73,6 +74,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "gateway2-network",
+  "data_dir": "examples/gateway2/data/witness$i",
   "max_clock_skew": 300
 }
 EOF
@@ -101,6 +103,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $PORT,
   "network_id": "gateway3-network",
+  "data_dir": "examples/gateway3/data/witness$i",
   "max_clock_skew": 300
 }
 EOF
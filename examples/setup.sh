@@ -36,6 +36,7 @@ for i in 1 2 3; do
   "private_key": "$PRIVKEY",
   "port": $((3000 + i)),
   "network_id": "example-network",
+  "data_dir": "examples/data/witness-$i",
   "max_clock_skew": 300
 }
 EOF